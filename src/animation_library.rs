@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::character::*;
+
+// a named set of animations that characters sharing a skeleton can reference
+// (mixamo animations downloaded "without skin" work on any mixamo rig)
+#[derive(Deserialize, Asset, TypePath)]
+pub struct AnimationSet {
+    pub animations: HashMap<String, AnimationData>,
+}
+
+// shared animation sets and clips
+// clips are keyed by path so that characters using the same clip share the handle
+#[derive(Default, Resource)]
+pub struct AnimationLibrary {
+    sets: HashMap<String, Handle<AnimationSet>>,
+    clips: HashMap<String, Handle<AnimationClip>>,
+}
+
+impl AnimationLibrary {
+    pub fn load_set(
+        &mut self,
+        id: impl Into<String>,
+        path: impl Into<String>,
+        asset_server: &AssetServer,
+    ) {
+        let id = id.into();
+        let path = path.into();
+
+        info!("Loading animation set '{}' from '{}' ...", id, path);
+        self.sets
+            .insert(id, asset_server.load::<AnimationSet>(path));
+    }
+
+    pub fn set(&self, id: impl AsRef<str>) -> Option<&Handle<AnimationSet>> {
+        self.sets.get(id.as_ref())
+    }
+
+    pub(crate) fn find_set(&self, asset_id: AssetId<AnimationSet>) -> Option<&String> {
+        self.sets
+            .iter()
+            .find(|(_, set)| set.id() == asset_id)
+            .map(|(id, _)| id)
+    }
+
+    // returns the existing handle if the clip has already been loaded
+    pub fn clip(
+        &mut self,
+        path: impl Into<String>,
+        asset_server: &AssetServer,
+    ) -> Handle<AnimationClip> {
+        self.clips
+            .entry(path.into())
+            .or_insert_with_key(|path| asset_server.load::<AnimationClip>(path.clone()))
+            .clone()
+    }
+
+    // drops the cached clips that aren't in use so that their assets can be freed
+    pub(crate) fn release_clips(&mut self, in_use: impl Fn(AssetId<AnimationClip>) -> bool) {
+        self.clips.retain(|_, clip| in_use(clip.id()));
+    }
+
+    // (finished, total) across every animation set
+    // failed sets count as finished so that a bad path doesn't block forever
+    pub fn load_progress(&self, asset_server: &AssetServer) -> (usize, usize) {
+        let finished = self
+            .sets
+            .values()
+            .filter(|set| {
+                let load_state = asset_server.load_state(*set);
+                load_state.is_loaded() || load_state.is_failed()
+            })
+            .count();
+        (finished, self.sets.len())
+    }
+
+    // the character's animations, including any from its animation sets
+    // the character's own animations override those from its sets
+    pub fn resolve_animations(
+        &self,
+        character_data: &CharacterData,
+        animation_sets: &Assets<AnimationSet>,
+    ) -> HashMap<String, AnimationData> {
+        let mut animations = HashMap::new();
+        for set_id in &character_data.animation_sets {
+            let Some(set) = self.sets.get(set_id) else {
+                warn!(
+                    "Character '{}' uses unknown animation set '{}'",
+                    character_data.id, set_id
+                );
+                continue;
+            };
+
+            // sets that are still loading are picked up once they finish
+            if let Some(set) = animation_sets.get(set) {
+                animations.extend(set.animations.clone());
+            }
+        }
+
+        animations.extend(
+            character_data
+                .animation_paths
+                .iter()
+                .map(|(name, animation_data)| (name.clone(), animation_data.clone())),
+        );
+        animations
+    }
+}
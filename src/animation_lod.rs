@@ -0,0 +1,180 @@
+use std::mem;
+
+use bevy::{
+    camera::primitives::{Frustum, Sphere},
+    diagnostic::FrameCount,
+    prelude::*,
+};
+
+// samples far away and offscreen animation players less often to keep big crowds affordable
+// the skipped time is caught up on the next frame that the player is sampled
+#[derive(Debug, Clone, Resource)]
+pub struct AnimationLodSettings {
+    pub enabled: bool,
+
+    // sorted by distance, players further than a level's distance use its interval
+    pub levels: Vec<AnimationLodLevel>,
+
+    // how often players outside every camera's frustum update, None pauses them
+    pub offscreen_interval: Option<u32>,
+
+    // radius around the player used to test if it's onscreen
+    pub bounding_radius: f32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AnimationLodLevel {
+    pub distance: f32,
+
+    // update every this many frames
+    pub interval: u32,
+}
+
+impl Default for AnimationLodSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            levels: vec![
+                AnimationLodLevel {
+                    distance: 10.0,
+                    interval: 2,
+                },
+                AnimationLodLevel {
+                    distance: 25.0,
+                    interval: 4,
+                },
+                AnimationLodLevel {
+                    distance: 50.0,
+                    interval: 8,
+                },
+            ],
+            offscreen_interval: None,
+            bounding_radius: 1.5,
+        }
+    }
+}
+
+impl AnimationLodSettings {
+    // None if the player shouldn't update at all
+    fn interval(&self, distance: f32, onscreen: bool) -> Option<u32> {
+        if !onscreen {
+            return self.offscreen_interval.map(|interval| interval.max(1));
+        }
+
+        Some(
+            self.levels
+                .iter()
+                .rev()
+                .find(|level| distance >= level.distance)
+                .map(|level| level.interval.max(1))
+                .unwrap_or(1),
+        )
+    }
+}
+
+// tracks an animation player's skipped frames
+#[derive(Debug, Default, Component)]
+pub struct AnimationLod {
+    // the player's graph while it's swapped out for a skipped frame
+    graph: Option<Handle<AnimationGraph>>,
+
+    // seconds that the player hasn't been advanced by
+    skipped_seconds: f32,
+
+    // the speeds to put back after catching up
+    speeds: Vec<(AnimationNodeIndex, f32)>,
+}
+
+impl AnimationLod {
+    pub fn skipped_seconds(&self) -> f32 {
+        self.skipped_seconds
+    }
+}
+
+pub(crate) fn insert_animation_lod(event: On<Add, AnimationPlayer>, mut commands: Commands) {
+    commands
+        .entity(event.entity)
+        .insert(AnimationLod::default());
+}
+
+// players without a graph aren't advanced or sampled, so skipped frames swap it out
+// players that are due catch up by playing faster for one frame
+pub(crate) fn skip_animation_lod_frames(
+    settings: Res<AnimationLodSettings>,
+    time: Res<Time>,
+    frame_count: Res<FrameCount>,
+    cameras: Query<(&GlobalTransform, &Frustum), With<Camera3d>>,
+    mut animation_players: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut AnimationPlayer,
+        &mut AnimationGraphHandle,
+        &mut AnimationLod,
+    )>,
+) {
+    let delta = time.delta_secs();
+    for (entity, transform, mut player, mut graph, mut lod) in &mut animation_players {
+        let interval = if settings.enabled {
+            let position = transform.translation();
+            let sphere = Sphere {
+                center: position.into(),
+                radius: settings.bounding_radius,
+            };
+            let onscreen = cameras
+                .iter()
+                .any(|(_, frustum)| frustum.intersects_sphere(&sphere, true));
+            let distance = cameras
+                .iter()
+                .map(|(camera_transform, _)| camera_transform.translation().distance(position))
+                .reduce(f32::min)
+                .unwrap_or_default();
+            settings.interval(distance, onscreen)
+        } else {
+            Some(1)
+        };
+
+        // spread the players out over the interval so they don't all update on the same frame
+        let due = interval.is_some_and(|interval| {
+            (frame_count.0.wrapping_add(entity.index().index())) % interval == 0
+        });
+        if !due {
+            lod.skipped_seconds += delta;
+            lod.graph = Some(mem::take(&mut graph.0));
+            continue;
+        }
+
+        if lod.skipped_seconds <= 0.0 || delta <= 0.0 {
+            continue;
+        }
+
+        let scale = (lod.skipped_seconds + delta) / delta;
+        lod.skipped_seconds = 0.0;
+        lod.speeds.clear();
+        for (animation_index, animation) in player.playing_animations_mut() {
+            lod.speeds.push((*animation_index, animation.speed()));
+            animation.set_speed(animation.speed() * scale);
+        }
+    }
+}
+
+pub(crate) fn restore_animation_lod_speeds(
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationLod)>,
+) {
+    for (mut player, mut lod) in &mut animation_players {
+        for (animation_index, speed) in lod.speeds.drain(..) {
+            if let Some(animation) = player.animation_mut(animation_index) {
+                animation.set_speed(speed);
+            }
+        }
+    }
+}
+
+pub(crate) fn restore_animation_lod_graphs(
+    mut animation_players: Query<(&mut AnimationGraphHandle, &mut AnimationLod)>,
+) {
+    for (mut graph, mut lod) in &mut animation_players {
+        if let Some(handle) = lod.graph.take() {
+            graph.0 = handle;
+        }
+    }
+}
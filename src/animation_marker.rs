@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+// a named point in an animation, e.g. a footstep or the frame a hit lands
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct AnimationMarkerData {
+    // seconds from the start of the clip
+    pub time: f32,
+    pub name: String,
+
+    // a particle effect spawned when the marker fires, e.g. dust at the foot on a footstep
+    #[serde(default)]
+    pub effect: Option<MarkerEffectData>,
+}
+
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct MarkerEffectData {
+    // an .effect.json
+    pub path: String,
+
+    // spawned at the model's origin without one
+    #[serde(default)]
+    pub bone: Option<String>,
+}
+
+// triggered when an animation's playback crosses one of its markers
+#[derive(Debug, Clone, Event)]
+pub struct AnimationMarker {
+    // the AnimationPlayer playing the animation
+    pub entity: Entity,
+    pub character: String,
+    pub animation: String,
+    pub name: String,
+}
+
+// copies the clip with the markers added as clip events
+// the events know which character and animation they belong to,
+// so unlike retargeted clips these can't be shared between characters
+pub fn add_animation_markers(
+    clip: &AnimationClip,
+    character: &str,
+    animation: &str,
+    markers: &[AnimationMarkerData],
+) -> AnimationClip {
+    let mut clip = clip.clone();
+    for marker in markers {
+        let event = AnimationMarker {
+            entity: Entity::PLACEHOLDER,
+            character: character.to_string(),
+            animation: animation.to_string(),
+            name: marker.name.clone(),
+        };
+
+        clip.add_event_fn(marker.time, move |commands, entity, _, weight| {
+            // blend space points and faded out animations keep playing at no weight
+            if weight <= 0.0 {
+                return;
+            }
+
+            commands.trigger(AnimationMarker {
+                entity,
+                ..event.clone()
+            });
+        });
+    }
+    clip
+}
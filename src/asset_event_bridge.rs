@@ -1,4 +1,56 @@
-use bevy::prelude::*;
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{AssetLoadError, AssetLoadFailedEvent, AssetPath},
+    prelude::*,
+};
+
+// bridges AssetEvent<A> messages to observable Asset*Event<A> triggers
+pub struct AssetEventBridgePlugin<A>(PhantomData<A>)
+where
+    A: Asset;
+
+impl<A> Default for AssetEventBridgePlugin<A>
+where
+    A: Asset,
+{
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<A> Plugin for AssetEventBridgePlugin<A>
+where
+    A: Asset,
+{
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                bridge_asset_events::<A>,
+                bridge_asset_load_failed_events::<A>,
+            ),
+        );
+    }
+}
+
+pub trait AssetEventBridgeAppExt {
+    fn bridge_asset_events<A>(&mut self) -> &mut Self
+    where
+        A: Asset;
+}
+
+impl AssetEventBridgeAppExt for App {
+    fn bridge_asset_events<A>(&mut self) -> &mut Self
+    where
+        A: Asset,
+    {
+        if !self.is_plugin_added::<AssetEventBridgePlugin<A>>() {
+            self.add_plugins(AssetEventBridgePlugin::<A>::default());
+        }
+        self
+    }
+}
 
 #[derive(Event)]
 pub struct AssetLoadedEvent<A>
@@ -8,6 +60,48 @@ where
     pub asset_id: AssetId<A>,
 }
 
+#[derive(Event)]
+pub struct AssetAddedEvent<A>
+where
+    A: Asset,
+{
+    pub asset_id: AssetId<A>,
+}
+
+#[derive(Event)]
+pub struct AssetModifiedEvent<A>
+where
+    A: Asset,
+{
+    pub asset_id: AssetId<A>,
+}
+
+#[derive(Event)]
+pub struct AssetRemovedEvent<A>
+where
+    A: Asset,
+{
+    pub asset_id: AssetId<A>,
+}
+
+#[derive(Event)]
+pub struct AssetUnusedEvent<A>
+where
+    A: Asset,
+{
+    pub asset_id: AssetId<A>,
+}
+
+#[derive(Event)]
+pub struct AssetFailedEvent<A>
+where
+    A: Asset,
+{
+    pub asset_id: AssetId<A>,
+    pub path: AssetPath<'static>,
+    pub error: AssetLoadError,
+}
+
 // bridge method because we can't observe asset events yet
 // https://github.com/bevyengine/bevy/issues/16041
 pub fn bridge_asset_events<A>(mut events: MessageReader<AssetEvent<A>>, mut commands: Commands)
@@ -15,9 +109,43 @@ where
     A: Asset,
 {
     for event in events.read() {
-        if let AssetEvent::LoadedWithDependencies { id } = event {
-            debug!("bridging asset load for {}", id);
-            commands.trigger(AssetLoadedEvent { asset_id: *id });
+        match *event {
+            AssetEvent::Added { id } => {
+                debug!("bridging asset add for {}", id);
+                commands.trigger(AssetAddedEvent { asset_id: id });
+            }
+            AssetEvent::Modified { id } => {
+                debug!("bridging asset modify for {}", id);
+                commands.trigger(AssetModifiedEvent { asset_id: id });
+            }
+            AssetEvent::Removed { id } => {
+                debug!("bridging asset remove for {}", id);
+                commands.trigger(AssetRemovedEvent { asset_id: id });
+            }
+            AssetEvent::Unused { id } => {
+                debug!("bridging asset unused for {}", id);
+                commands.trigger(AssetUnusedEvent { asset_id: id });
+            }
+            AssetEvent::LoadedWithDependencies { id } => {
+                debug!("bridging asset load for {}", id);
+                commands.trigger(AssetLoadedEvent { asset_id: id });
+            }
         }
     }
 }
+
+pub fn bridge_asset_load_failed_events<A>(
+    mut events: MessageReader<AssetLoadFailedEvent<A>>,
+    mut commands: Commands,
+) where
+    A: Asset,
+{
+    for event in events.read() {
+        debug!("bridging asset load failure for {}", event.id);
+        commands.trigger(AssetFailedEvent {
+            asset_id: event.id,
+            path: event.path.clone(),
+            error: event.error.clone(),
+        });
+    }
+}
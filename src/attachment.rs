@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, scene::SceneInstanceReady};
+use serde::Deserialize;
+
+use crate::character::*;
+use crate::retarget::*;
+
+// a named attachment point on a bone, loaded as part of the character data
+// so that props line up without every caller knowing the offset
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct SocketData {
+    pub bone: String,
+
+    // relative to the bone
+    #[serde(default)]
+    pub translation: [f32; 3],
+    // XYZ euler angles in degrees
+    #[serde(default)]
+    pub rotation: [f32; 3],
+    #[serde(default = "default_socket_scale")]
+    pub scale: f32,
+}
+
+fn default_socket_scale() -> f32 {
+    1.0
+}
+
+impl SocketData {
+    pub fn transform(&self) -> Transform {
+        Transform {
+            translation: Vec3::from_array(self.translation),
+            rotation: Quat::from_euler(
+                EulerRot::XYZ,
+                self.rotation[0].to_radians(),
+                self.rotation[1].to_radians(),
+                self.rotation[2].to_radians(),
+            ),
+            scale: Vec3::splat(self.scale),
+        }
+    }
+}
+
+// the model's socket entities by socket name, set once the model's scene has spawned
+#[derive(Debug, Clone, Default, Component)]
+pub struct CharacterSockets(pub HashMap<String, Entity>);
+
+// a prop attached to a character model
+#[derive(Debug, Clone)]
+pub struct CharacterAttachment {
+    // a socket name or a bone name
+    pub bone: String,
+    pub scene: Handle<Scene>,
+    pub transform: Transform,
+
+    // the spawned prop, None until the model's scene has spawned
+    pub entity: Option<Entity>,
+}
+
+// the model's props, respawned whenever the model's scene is (e.g. by a model LOD swap)
+#[derive(Debug, Clone, Default, Component)]
+pub struct CharacterAttachments(pub Vec<CharacterAttachment>);
+
+// parents a prop scene to a character model's bone (or socket) so that it follows the animation
+// models that haven't finished spawning get the prop once they do
+// props inherit the bone's scale, which is usually centimeters for mixamo rigs
+#[derive(Debug, Clone, Event)]
+pub struct AttachToBone {
+    pub character: Entity,
+    pub bone: String,
+    pub scene: Handle<Scene>,
+    pub transform: Transform,
+}
+
+impl AttachToBone {
+    pub fn new(character: Entity, bone: impl Into<String>, scene: Handle<Scene>) -> Self {
+        Self {
+            character,
+            bone: bone.into(),
+            scene,
+            transform: Transform::default(),
+        }
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+}
+
+pub fn attach_to_bone(
+    commands: &mut Commands,
+    character: Entity,
+    bone: impl Into<String>,
+    scene: Handle<Scene>,
+) {
+    commands.trigger(AttachToBone::new(character, bone, scene));
+}
+
+fn spawn_attachment(
+    commands: &mut Commands,
+    model: Entity,
+    attachment: &mut CharacterAttachment,
+    sockets: &CharacterSockets,
+    children: &Query<&Children>,
+    names: &Query<&Name>,
+) {
+    let Some(parent) = sockets
+        .0
+        .get(&attachment.bone)
+        .copied()
+        .or_else(|| find_bone(model, &attachment.bone, children, names))
+    else {
+        warn!("Cannot attach prop to unknown bone '{}'", attachment.bone);
+        attachment.entity = None;
+        return;
+    };
+
+    attachment.entity = Some(
+        commands
+            .spawn((
+                SceneRoot(attachment.scene.clone()),
+                attachment.transform,
+                Name::new(format!("Attachment: {}", attachment.bone)),
+                ChildOf(parent),
+            ))
+            .id(),
+    );
+}
+
+pub(crate) fn on_attach_to_bone(
+    event: On<AttachToBone>,
+    mut commands: Commands,
+    mut character_models: Query<
+        (Option<&CharacterSockets>, Option<&mut CharacterAttachments>),
+        With<CharacterModel>,
+    >,
+    children: Query<&Children>,
+    names: Query<&Name>,
+) {
+    let Ok((sockets, attachments)) = character_models.get_mut(event.character) else {
+        warn!(
+            "Cannot attach prop to bone '{}' of an entity that isn't a character model",
+            event.bone
+        );
+        return;
+    };
+
+    let mut attachment = CharacterAttachment {
+        bone: event.bone.clone(),
+        scene: event.scene.clone(),
+        transform: event.transform,
+        entity: None,
+    };
+
+    // the scene hasn't spawned yet if the sockets haven't been set up
+    if let Some(sockets) = sockets {
+        spawn_attachment(
+            &mut commands,
+            event.character,
+            &mut attachment,
+            sockets,
+            &children,
+            &names,
+        );
+    }
+
+    match attachments {
+        Some(mut attachments) => attachments.0.push(attachment),
+        None => {
+            commands
+                .entity(event.character)
+                .insert(CharacterAttachments(vec![attachment]));
+        }
+    }
+}
+
+// spawns the data's sockets and any props once the scene spawns
+pub(crate) fn setup_character_attachments(
+    scene_ready: On<SceneInstanceReady>,
+    mut commands: Commands,
+    character_datum: Res<Assets<CharacterData>>,
+    mut character_models: Query<(&CharacterModel, Option<&mut CharacterAttachments>)>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+) {
+    let Ok((character_model, attachments)) = character_models.get_mut(scene_ready.entity) else {
+        return;
+    };
+    let Some(character_data) = character_datum.get(&character_model.0) else {
+        return;
+    };
+
+    let mut sockets = CharacterSockets::default();
+    for (socket_name, socket_data) in &character_data.sockets {
+        let Some(bone) = find_bone(scene_ready.entity, &socket_data.bone, &children, &names) else {
+            warn!(
+                "Character '{}' socket '{}' has unknown bone '{}'",
+                character_data.id, socket_name, socket_data.bone
+            );
+            continue;
+        };
+
+        let socket = commands
+            .spawn((
+                socket_data.transform(),
+                Visibility::default(),
+                Name::new(format!("Socket: {socket_name}")),
+                ChildOf(bone),
+            ))
+            .id();
+        sockets.0.insert(socket_name.clone(), socket);
+    }
+
+    // the previous scene's props were despawned with it
+    if let Some(mut attachments) = attachments {
+        for attachment in &mut attachments.0 {
+            spawn_attachment(
+                &mut commands,
+                scene_ready.entity,
+                attachment,
+                &sockets,
+                &children,
+                &names,
+            );
+        }
+    }
+
+    commands.entity(scene_ready.entity).insert(sockets);
+}
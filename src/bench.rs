@@ -0,0 +1,158 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_mixamo::*;
+use serde::Serialize;
+
+use crate::crowd::*;
+use crate::selection::*;
+
+// spawns a crowd, measures frame times for a while, writes a report and exits
+// run with --bench crowd=500 [--bench-duration 10] [--bench-output report.json]
+pub struct BenchPlugin(pub CrowdBench);
+
+impl Plugin for BenchPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.0.clone())
+            .add_systems(Update, update_crowd_bench);
+    }
+}
+
+// frame times from before the crowd has finished spawning are thrown away
+const WARMUP_SECONDS: f32 = 3.0;
+
+const DEFAULT_BENCH_SECONDS: f32 = 10.0;
+
+#[derive(Debug, Clone, Default)]
+enum BenchPhase {
+    // for the first character to spawn so there's something to make a crowd of
+    #[default]
+    WaitingForCharacter,
+    WarmingUp(Timer),
+    Measuring(Timer),
+    // waiting for the app to exit
+    Done,
+}
+
+#[derive(Debug, Clone, Resource)]
+pub struct CrowdBench {
+    pub count: u32,
+    pub duration: Duration,
+    pub output: PathBuf,
+
+    phase: BenchPhase,
+    frame_times: Vec<f32>,
+}
+
+impl CrowdBench {
+    pub fn new(count: u32) -> Self {
+        Self {
+            count,
+            duration: Duration::from_secs_f32(DEFAULT_BENCH_SECONDS),
+            output: PathBuf::from(format!("bench-crowd-{count}.json")),
+            phase: BenchPhase::default(),
+            frame_times: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct CrowdBenchReport {
+    characters: usize,
+    seconds: f32,
+    frames: usize,
+    fps: f32,
+    avg_ms: f32,
+    p50_ms: f32,
+    p95_ms: f32,
+    p99_ms: f32,
+    max_ms: f32,
+}
+
+impl CrowdBenchReport {
+    fn new(characters: usize, frame_times: &[f32]) -> Self {
+        let mut sorted = frame_times.to_vec();
+        sorted.sort_by(f32::total_cmp);
+
+        let seconds = sorted.iter().sum::<f32>();
+        let percentile = |p: f32| {
+            let index = ((sorted.len() as f32 * p).ceil() as usize).saturating_sub(1);
+            sorted.get(index).copied().unwrap_or_default() * 1000.0
+        };
+        Self {
+            characters,
+            seconds,
+            frames: sorted.len(),
+            fps: sorted.len() as f32 / seconds.max(f32::EPSILON),
+            avg_ms: seconds * 1000.0 / sorted.len().max(1) as f32,
+            p50_ms: percentile(0.5),
+            p95_ms: percentile(0.95),
+            p99_ms: percentile(0.99),
+            max_ms: percentile(1.0),
+        }
+    }
+}
+
+fn update_crowd_bench(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bench: ResMut<CrowdBench>,
+    selected_animation_players: Query<(), With<SelectedAnimationPlayer>>,
+    character_models: Query<(), With<CharacterModel>>,
+    mut app_exit: MessageWriter<AppExit>,
+) {
+    let bench = &mut *bench;
+    match &mut bench.phase {
+        BenchPhase::WaitingForCharacter => {
+            if selected_animation_players.is_empty() {
+                return;
+            }
+
+            info!("Benchmarking a crowd of {} characters ...", bench.count);
+            commands.trigger(SpawnCrowd::with_count(bench.count));
+            bench.phase =
+                BenchPhase::WarmingUp(Timer::from_seconds(WARMUP_SECONDS, TimerMode::Once));
+        }
+        BenchPhase::WarmingUp(timer) => {
+            if timer.tick(time.delta()).is_finished() {
+                bench.phase = BenchPhase::Measuring(Timer::new(bench.duration, TimerMode::Once));
+            }
+        }
+        BenchPhase::Measuring(timer) => {
+            bench.frame_times.push(time.delta_secs());
+            if !timer.tick(time.delta()).is_finished() {
+                return;
+            }
+
+            bench.phase = BenchPhase::Done;
+
+            let report = CrowdBenchReport::new(character_models.iter().count(), &bench.frame_times);
+            info!(
+                "Crowd of {} characters: {:.2} fps, avg {:.2}ms, p95 {:.2}ms, p99 {:.2}ms",
+                report.characters, report.fps, report.avg_ms, report.p95_ms, report.p99_ms
+            );
+
+            let result = serde_json::to_string_pretty(&report)
+                .map_err(|err| err.to_string())
+                .and_then(|json| {
+                    std::fs::write(&bench.output, json).map_err(|err| err.to_string())
+                });
+            match result {
+                Ok(()) => {
+                    info!("Wrote bench report to '{}'", bench.output.display());
+                    app_exit.write(AppExit::Success);
+                }
+                Err(err) => {
+                    error!(
+                        "Failed to write bench report to '{}': {}",
+                        bench.output.display(),
+                        err
+                    );
+                    app_exit.write(AppExit::error());
+                }
+            }
+        }
+        BenchPhase::Done => {}
+    }
+}
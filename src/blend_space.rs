@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+
+use bevy::{animation::RepeatAnimation, prelude::*};
+use serde::Deserialize;
+
+use crate::character::*;
+
+// a 1D blend space, animations are placed along a single axis (e.g. speed)
+// and blended by where the character's position on that axis falls between them
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct BlendSpaceData {
+    pub points: Vec<BlendSpacePoint>,
+}
+
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct BlendSpacePoint {
+    pub animation: String,
+    pub position: f32,
+}
+
+// the graph nodes for a blend space
+#[derive(Debug, Clone, Reflect)]
+pub struct BlendSpaceNodes {
+    // an empty node that is played like any other animation so that transitions
+    // crossfade it in and out, its weight is passed on to the points
+    pub(crate) node: AnimationNodeIndex,
+
+    // (position, animation, node) sorted by position
+    pub(crate) points: Vec<(f32, String, AnimationNodeIndex)>,
+}
+
+impl BlendSpaceNodes {
+    pub fn node(&self) -> AnimationNodeIndex {
+        self.node
+    }
+
+    // (min, max) position
+    pub fn range(&self) -> Option<(f32, f32)> {
+        let (min, _, _) = self.points.first()?;
+        let (max, _, _) = self.points.last()?;
+        Some((*min, *max))
+    }
+
+    // each point's weight at the given position
+    pub fn weights(&self, position: f32) -> Vec<(AnimationNodeIndex, f32)> {
+        let positions = self
+            .points
+            .iter()
+            .map(|(position, _, _)| *position)
+            .collect::<Vec<_>>();
+
+        self.points
+            .iter()
+            .zip(blend_weights(&positions, position))
+            .map(|((_, _, node), weight)| (*node, weight))
+            .collect()
+    }
+}
+
+// linearly blends between the two points either side of the position
+// positions must be sorted
+pub fn blend_weights(positions: &[f32], position: f32) -> Vec<f32> {
+    let mut weights = vec![0.0; positions.len()];
+    if positions.is_empty() {
+        return weights;
+    }
+
+    let last = positions.len() - 1;
+    if position <= positions[0] {
+        weights[0] = 1.0;
+    } else if position >= positions[last] {
+        weights[last] = 1.0;
+    } else {
+        let next = positions.iter().position(|p| *p > position).unwrap();
+        let (from, to) = (positions[next - 1], positions[next]);
+        let t = (position - from) / (to - from);
+        weights[next - 1] = 1.0 - t;
+        weights[next] = t;
+    }
+    weights
+}
+
+// each blend space's position along its axis, added to the character model
+// blend spaces without a position sit at their first point
+#[derive(Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct BlendSpacePositions(HashMap<String, f32>);
+
+impl BlendSpacePositions {
+    pub fn get(&self, blend_space: impl AsRef<str>) -> Option<f32> {
+        self.0.get(blend_space.as_ref()).copied()
+    }
+
+    pub fn set(&mut self, blend_space: impl Into<String>, position: f32) {
+        self.0.insert(blend_space.into(), position);
+    }
+}
+
+// drives the point weights of every playing blend space
+// this has to run after the transitions update the blend space weights
+pub(crate) fn update_blend_spaces(
+    character_datum: Res<Assets<CharacterData>>,
+    characters: Res<Characters>,
+    character_models: Query<(
+        &CharacterAnimator,
+        &CharacterModel,
+        Option<&BlendSpacePositions>,
+    )>,
+    mut animation_players: Query<&mut AnimationPlayer>,
+) {
+    for (animator, character_model, positions) in &character_models {
+        let Some(character_data) = character_datum.get(&character_model.0) else {
+            continue;
+        };
+        let Some(character) = characters.get(&character_data.id) else {
+            continue;
+        };
+
+        let Ok(mut player) = animation_players.get_mut(animator.player) else {
+            continue;
+        };
+
+        for (name, blend_space) in character.blend_spaces() {
+            let Some(weight) = player
+                .animation(blend_space.node)
+                .map(|animation| animation.weight())
+            else {
+                // stopped (or faded out), so stop its points as well
+                for (_, _, node) in &blend_space.points {
+                    player.stop(*node);
+                }
+                continue;
+            };
+
+            let Some((min, _)) = blend_space.range() else {
+                continue;
+            };
+            let position = positions
+                .and_then(|positions| positions.get(name))
+                .unwrap_or(min);
+
+            for (node, point_weight) in blend_space.weights(position) {
+                player
+                    .play(node)
+                    .set_repeat(RepeatAnimation::Forever)
+                    .set_weight(weight * point_weight);
+            }
+        }
+    }
+}
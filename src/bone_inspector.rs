@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+
+use bevy::{mesh::skinning::SkinnedMesh, prelude::*};
+use bevy_mixamo::*;
+
+use crate::dropdown::{HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
+use crate::scroll::*;
+use crate::selection::*;
+use crate::skeleton::*;
+
+pub struct BoneInspectorPlugin;
+
+impl Plugin for BoneInspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_bone_inspector)
+            .add_systems(
+                Update,
+                (
+                    build_bone_inspector,
+                    toggle_bone_inspector,
+                    handle_bone_toggles,
+                    handle_bone_selection,
+                    update_bone_row_visibility,
+                    update_bone_button_colors,
+                    update_bone_transform_texts,
+                )
+                    .chain(),
+            )
+            .add_observer(on_character_model_ready);
+    }
+}
+
+// the scrolling panel the bone rows are listed in, I toggles it
+#[derive(Component)]
+struct BoneInspector;
+
+#[derive(Component)]
+struct BonesReady;
+
+// one per bone, rows are listed flat in depth first order
+#[derive(Component)]
+struct BoneRow {
+    // the row of the closest bone above this one
+    parent: Option<Entity>,
+
+    // whether the bones below this one are listed
+    expanded: bool,
+}
+
+// expands / collapses a row
+#[derive(Component)]
+struct BoneToggle {
+    row: Entity,
+}
+
+// selects a row's bone
+#[derive(Component)]
+struct BoneSelectButton {
+    bone: Entity,
+}
+
+#[derive(Component)]
+struct BoneTransformText {
+    bone: Entity,
+}
+
+const INDENT: f32 = 12.0;
+
+fn setup_bone_inspector(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            right: Val::Px(10.0),
+            width: Val::Px(380.0),
+            max_height: Val::Percent(80.0),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(5.0)),
+            display: Display::None,
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.9)),
+        ScrollView::with_scrollbar(),
+        Name::new("BoneInspector"),
+        BoneInspector,
+    ));
+}
+
+// bones aren't available until the scene has spawned
+fn on_character_model_ready(character_ready: On<CharacterReady>, mut commands: Commands) {
+    commands.entity(character_ready.entity).insert(BonesReady);
+}
+
+// (re)build the rows for the selected character's skeleton
+#[allow(clippy::too_many_arguments)]
+fn build_bone_inspector(
+    mut commands: Commands,
+    selected_character: Res<SelectedCharacter>,
+    mut selected_bone: ResMut<SelectedBone>,
+    ready_models: Query<Ref<BonesReady>>,
+    children: Query<&Children>,
+    skinned_meshes: Query<&SkinnedMesh>,
+    parents: Query<&ChildOf>,
+    names: Query<&Name>,
+    inspector: Single<Entity, With<BoneInspector>>,
+) {
+    let ready = selected_character
+        .0
+        .and_then(|entity| Some((entity, ready_models.get(entity).ok()?)));
+    let just_ready = ready.as_ref().is_some_and(|(_, ready)| ready.is_added());
+    if !selected_character.is_changed() && !just_ready {
+        return;
+    }
+
+    let inspector = *inspector;
+    commands.entity(inspector).despawn_related::<Children>();
+    selected_bone.0 = None;
+
+    let Some((entity, _)) = ready else {
+        return;
+    };
+
+    let joints = skeleton_joints(entity, &children, &skinned_meshes);
+    let parent_joints = joints
+        .iter()
+        .map(|&joint| parent_joint(joint, &joints, &parents).0)
+        .collect::<Vec<_>>();
+
+    // depth first so that every bone is listed under its parent
+    let mut rows = HashMap::with_capacity(joints.len());
+    for joint in children.iter_descendants_depth_first(entity) {
+        if joints.binary_search(&joint).is_err() {
+            continue;
+        }
+        let (parent_joint, depth) = parent_joint(joint, &joints, &parents);
+        let parent_row = parent_joint.and_then(|parent_joint| rows.get(&parent_joint).copied());
+        let has_children = parent_joints.contains(&Some(joint));
+        let name = names
+            .get(joint)
+            .map(|name| name.to_string())
+            .unwrap_or_else(|_| format!("{joint}"));
+
+        let row = commands
+            .spawn((
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::left(Val::Px(depth as f32 * INDENT)),
+                    ..default()
+                },
+                BoneRow {
+                    parent: parent_row,
+                    expanded: true,
+                },
+                ChildOf(inspector),
+            ))
+            .id();
+        rows.insert(joint, row);
+
+        commands.entity(row).with_children(|parent| {
+            parent
+                .spawn(Node {
+                    column_gap: Val::Px(5.0),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    // leaf bones get an empty toggle so the names line up
+                    let mut toggle = parent.spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(18.0),
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                    ));
+                    if has_children {
+                        toggle.insert((BackgroundColor(NORMAL_BUTTON), BoneToggle { row }));
+                        toggle.with_child((
+                            Text::new("-"),
+                            TextFont {
+                                font_size: 14.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                        ));
+                    }
+
+                    parent.spawn((
+                        Button,
+                        Node {
+                            padding: UiRect::horizontal(Val::Px(4.0)),
+                            ..default()
+                        },
+                        BackgroundColor(NORMAL_BUTTON),
+                        BoneSelectButton { bone: joint },
+                        children![(
+                            Text::new(name),
+                            TextFont {
+                                font_size: 14.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                        )],
+                    ));
+                });
+
+            parent.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 11.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                Node {
+                    margin: UiRect::left(Val::Px(23.0)),
+                    ..default()
+                },
+                BoneTransformText { bone: joint },
+            ));
+        });
+    }
+}
+
+fn toggle_bone_inspector(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut inspector: Single<&mut Node, With<BoneInspector>>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyI) {
+        inspector.display = match inspector.display {
+            Display::None => Display::Flex,
+            _ => Display::None,
+        };
+    }
+}
+
+fn handle_bone_toggles(
+    toggles: Query<(&Interaction, &BoneToggle, &Children), Changed<Interaction>>,
+    mut rows: Query<&mut BoneRow>,
+    mut texts: Query<&mut Text>,
+) {
+    for (interaction, toggle, children) in &toggles {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Ok(mut row) = rows.get_mut(toggle.row) else {
+            continue;
+        };
+        row.expanded = !row.expanded;
+
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                **text = if row.expanded { "-" } else { "+" }.to_string();
+            }
+        }
+    }
+}
+
+// selecting a bone shows the skeleton so the bone can be seen
+fn handle_bone_selection(
+    mut selected_bone: ResMut<SelectedBone>,
+    mut config_store: ResMut<GizmoConfigStore>,
+    buttons: Query<(&Interaction, &BoneSelectButton), Changed<Interaction>>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        if selected_bone.0 == Some(button.bone) {
+            selected_bone.0 = None;
+        } else {
+            selected_bone.0 = Some(button.bone);
+
+            let (config, _) = config_store.config_mut::<SkeletonGizmos>();
+            config.enabled = true;
+        }
+    }
+}
+
+// rows are hidden when any row above them is collapsed
+fn update_bone_row_visibility(
+    changed_rows: Query<(), Changed<BoneRow>>,
+    rows: Query<(Entity, &BoneRow)>,
+    mut nodes: Query<&mut Node, With<BoneRow>>,
+) {
+    if changed_rows.is_empty() {
+        return;
+    }
+
+    for (entity, row) in &rows {
+        let mut visible = true;
+        let mut parent = row.parent;
+        while let Some(parent_row) = parent.and_then(|parent| rows.get(parent).ok()) {
+            visible &= parent_row.1.expanded;
+            parent = parent_row.1.parent;
+        }
+
+        if let Ok(mut node) = nodes.get_mut(entity) {
+            let display = if visible {
+                Display::Flex
+            } else {
+                Display::None
+            };
+            if node.display != display {
+                node.display = display;
+            }
+        }
+    }
+}
+
+// the selected bone stays highlighted
+fn update_bone_button_colors(
+    selected_bone: Res<SelectedBone>,
+    mut buttons: Query<(Ref<Interaction>, &BoneSelectButton, &mut BackgroundColor)>,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        if !selected_bone.is_changed() && !interaction.is_changed() {
+            continue;
+        }
+
+        *color = if selected_bone.0 == Some(button.bone) {
+            PRESSED_BUTTON
+        } else if *interaction == Interaction::Hovered {
+            HOVERED_BUTTON
+        } else {
+            NORMAL_BUTTON
+        }
+        .into();
+    }
+}
+
+fn update_bone_transform_texts(
+    inspector: Single<&Node, With<BoneInspector>>,
+    bones: Query<&Transform>,
+    mut texts: Query<(&mut Text, &BoneTransformText)>,
+) {
+    if inspector.display == Display::None {
+        return;
+    }
+
+    for (mut text, transform_text) in &mut texts {
+        let Ok(transform) = bones.get(transform_text.bone) else {
+            continue;
+        };
+
+        let t = transform.translation;
+        let (x, y, z) = transform.rotation.to_euler(EulerRot::XYZ);
+        let s = transform.scale;
+        **text = format!(
+            "t ({:.2}, {:.2}, {:.2}) r ({:.0}, {:.0}, {:.0}) s ({:.2}, {:.2}, {:.2})",
+            t.x,
+            t.y,
+            t.z,
+            x.to_degrees(),
+            y.to_degrees(),
+            z.to_degrees(),
+            s.x,
+            s.y,
+            s.z
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use bevy::{
+    animation::{AnimatedBy, AnimationTargetId},
+    prelude::*,
+};
+
+use crate::retarget::*;
+
+// the model's bones split into the character's mask groups
+// every bone that isn't in a named mask goes into one more group
+// so that masked animations leave those bones alone too
+#[derive(Debug, Clone, Default)]
+pub struct BoneMaskGroups {
+    groups: HashMap<String, u32>,
+    targets: Vec<(AnimationTargetId, u32)>,
+}
+
+impl BoneMaskGroups {
+    // each mask is a list of bones, every bone includes its descendants
+    pub fn from_scene(
+        scene: &Scene,
+        masks: &HashMap<String, Vec<String>>,
+        normalize_names: bool,
+    ) -> Self {
+        let mut mask_names = masks.keys().collect::<Vec<_>>();
+        mask_names.sort();
+
+        // the last group is reserved for unmasked bones
+        if mask_names.len() >= AnimationMask::BITS as usize {
+            warn!("Too many bone masks, only the first 63 are used");
+            mask_names.truncate(AnimationMask::BITS as usize - 1);
+        }
+
+        let groups = mask_names
+            .into_iter()
+            .enumerate()
+            .map(|(group, name)| (name.clone(), group as u32))
+            .collect::<HashMap<_, _>>();
+        let unmasked_group = groups.len() as u32;
+
+        let world = &scene.world;
+        let Some(mut nodes) = world.try_query::<(Entity, &Name, Option<&ChildOf>)>() else {
+            return Self::default();
+        };
+        let nodes = nodes
+            .iter(world)
+            .map(|(entity, name, parent)| (entity, (name, parent.map(ChildOf::parent))))
+            .collect::<HashMap<_, _>>();
+
+        let Some(mut animation_targets) =
+            world.try_query_filtered::<(Entity, &AnimatedBy), With<AnimationTargetId>>()
+        else {
+            return Self::default();
+        };
+
+        let mut targets = Vec::new();
+        for (entity, animated_by) in animation_targets.iter(world) {
+            let path = bone_path(
+                entity,
+                animated_by.0,
+                |node| nodes.get(&node).map(|(name, _)| *name),
+                |node| nodes.get(&node).and_then(|(_, parent)| *parent),
+            );
+
+            let target_id = if normalize_names {
+                normalized_target_id(&path)
+            } else {
+                AnimationTargetId::from_names(path.iter().copied())
+            };
+
+            let mut masked = false;
+            for (mask_name, group) in &groups {
+                let in_mask = masks[mask_name].iter().any(|bone| {
+                    path.iter()
+                        .any(|name| normalize_bone_name(name) == normalize_bone_name(bone))
+                });
+                if in_mask {
+                    targets.push((target_id, *group));
+                    masked = true;
+                }
+            }
+
+            if !masked {
+                targets.push((target_id, unmasked_group));
+            }
+        }
+
+        Self { groups, targets }
+    }
+
+    pub fn apply(&self, animation_graph: &mut AnimationGraph) {
+        for (target_id, group) in &self.targets {
+            animation_graph.add_target_to_mask_group(*target_id, *group);
+        }
+    }
+
+    // the node mask that limits an animation to the named mask
+    pub fn mask(&self, name: impl AsRef<str>) -> Option<AnimationMask> {
+        let group = self.groups.get(name.as_ref())?;
+        Some(!(1 << group))
+    }
+}
@@ -0,0 +1,2078 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::Path;
+use std::time::Duration;
+
+use bevy::{
+    animation::{
+        ActiveAnimation, RepeatAnimation, advance_animations, animate_targets,
+        transition::advance_transitions,
+    },
+    app::AnimationSystems,
+    asset::{LoadedFolder, RecursiveDependencyLoadState, UntypedAssetId},
+    gltf::Gltf,
+    mesh::InheritWeightSystems,
+    prelude::*,
+    scene::SceneInstanceReady,
+};
+use bevy_common_assets::{json::JsonAssetPlugin, ron::RonAssetPlugin};
+use serde::{
+    Deserialize, Deserializer,
+    de::{MapAccess, Visitor},
+};
+
+use crate::animation_library::*;
+use crate::animation_lod::*;
+use crate::animation_marker::*;
+use crate::asset_event_bridge::*;
+use crate::attachment::*;
+use crate::blend_space::*;
+use crate::bone_mask::*;
+#[cfg(feature = "physics")]
+use crate::character_capsule::*;
+use crate::controller::*;
+use crate::discovery::*;
+use crate::ik::*;
+use crate::look_at::*;
+use crate::loop_mode::*;
+use crate::manifest::*;
+use crate::marker_audio::*;
+use crate::material_variant::*;
+use crate::model_lod::*;
+use crate::morph_target::*;
+use crate::particle::*;
+#[cfg(feature = "physics")]
+use crate::ragdoll::*;
+use crate::retarget::*;
+use crate::root_motion::*;
+use crate::sequence::*;
+use crate::spring_bone::*;
+use crate::state_machine::*;
+use crate::validation::*;
+use crate::vertex_animation::*;
+
+pub struct CharacterPlugin;
+
+// systems that adjust the sampled pose, e.g. IK
+// these run after the animations are applied but before transforms propagate
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct PostAnimationSystems;
+
+impl Plugin for CharacterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            JsonAssetPlugin::<CharacterData>::new(&["character.json"]),
+            // RON is nicer for hand editing (and supports comments)
+            RonAssetPlugin::<CharacterData>::new(&["character.ron"]),
+            // only ever loaded by type, manifest.json's extension is a plain "json"
+            // that other files in a scanned folder share
+            JsonAssetPlugin::<CharacterManifest>::new(&[]),
+            JsonAssetPlugin::<AnimationSet>::new(&["animations.json"]),
+            JsonAssetPlugin::<BoneMap>::new(&["bones.json"]),
+            JsonAssetPlugin::<ParticleEffect>::new(&["effect.json"]),
+        ))
+        .init_resource::<Characters>()
+        .init_resource::<AnimationLibrary>()
+        .init_resource::<RetargetCache>()
+        .init_resource::<AnimationLodSettings>()
+        .init_resource::<PendingUnloadChecks>()
+        .init_resource::<MaterialVariantCache>()
+        .init_resource::<MarkerAudioSettings>()
+        .init_resource::<MarkerSounds>()
+        .init_resource::<ParticleAssets>()
+        .init_resource::<PendingCharacterValidations>()
+        .init_resource::<ValidatedCharacterData>()
+        // so that the remote protocol and inspectors can see into characters
+        .register_type::<Characters>()
+        .register_type::<CharacterModel>()
+        .register_type::<CharacterAnimator>()
+        .register_type::<AnimationStateMachine>()
+        .register_type::<BlendSpacePositions>()
+        .register_type::<AnimationSequence>()
+        .register_type::<AnimationLoopModes>()
+        .register_type::<FootIk>()
+        .register_type::<LookAtTarget>()
+        .register_asset_reflect::<CharacterData>()
+        .bridge_asset_events::<CharacterData>()
+        .bridge_asset_events::<CharacterManifest>()
+        .bridge_asset_events::<AnimationSet>()
+        .bridge_asset_events::<BoneMap>()
+        .bridge_asset_events::<LoadedFolder>()
+        .bridge_asset_events::<Scene>()
+        .bridge_asset_events::<AnimationClip>()
+        .bridge_asset_events::<ParticleEffect>()
+        .add_systems(
+            Update,
+            (
+                (process_character_animations, spawn_pending_characters).chain(),
+                update_animation_state_machines,
+                update_animation_sequences,
+                (update_character_controllers, trigger_gamepad_animations),
+                update_model_lods,
+                verify_character_unloads,
+                validate_loaded_characters,
+                update_particles,
+                (trigger_animation_finished, stop_finished_layered_animations).chain(),
+            ),
+        )
+        .configure_sets(
+            PostUpdate,
+            PostAnimationSystems
+                .after(AnimationSystems)
+                .before(TransformSystems::Propagate),
+        )
+        .add_systems(
+            PostUpdate,
+            (
+                update_blend_spaces
+                    .after(advance_transitions)
+                    .before(animate_targets),
+                update_loop_modes
+                    .after(advance_animations)
+                    .before(animate_targets),
+                skip_animation_lod_frames.before(advance_animations),
+                restore_animation_lod_speeds
+                    .after(advance_animations)
+                    .before(update_loop_modes),
+                restore_animation_lod_graphs
+                    .after(animate_targets)
+                    .before(PostAnimationSystems),
+                (solve_foot_ik, solve_look_at).in_set(PostAnimationSystems),
+                // hair and the like swing from the final pose
+                solve_spring_bones
+                    .in_set(PostAnimationSystems)
+                    .after(solve_foot_ik)
+                    .after(solve_look_at),
+                // after the animations so that set weights win over animated ones
+                apply_morph_target_weights
+                    .in_set(PostAnimationSystems)
+                    .before(InheritWeightSystems),
+            ),
+        )
+        .add_observer(on_character_manifest_loaded)
+        .add_observer(on_character_folder_loaded)
+        .add_observer(on_character_data_loaded)
+        .add_observer(on_character_data_modified)
+        .add_observer(on_character_data_loaded_validate)
+        .add_observer(on_character_data_modified_validate)
+        .add_observer(on_character_data_failed)
+        .add_observer(on_animation_set_loaded)
+        .add_observer(on_bone_map_modified)
+        .add_observer(on_character_asset_failed::<Scene>)
+        .add_observer(on_character_asset_failed::<AnimationClip>)
+        .add_observer(on_character_asset_failed::<BoneMap>)
+        .add_observer(on_character_asset_failed::<ParticleEffect>)
+        .add_observer(on_load_character)
+        .add_observer(on_spawn_character)
+        .add_observer(on_unload_character)
+        .add_observer(on_swap_character_model)
+        .add_observer(on_attach_to_bone)
+        .add_observer(on_set_material_variant)
+        .add_observer(on_set_expression)
+        .add_observer(play_marker_sounds)
+        .add_observer(spawn_marker_effects)
+        .add_observer(on_spawn_particle_effect)
+        .add_observer(find_inserted_look_at_bones)
+        .add_observer(insert_animation_lod);
+
+        setup_vertex_animations(app);
+
+        // the app adds avian's PhysicsPlugins itself
+        #[cfg(feature = "physics")]
+        app.add_systems(
+            PostUpdate,
+            update_ragdolls
+                .in_set(PostAnimationSystems)
+                .after(solve_foot_ik)
+                .after(solve_look_at)
+                .before(solve_spring_bones),
+        )
+        .add_systems(
+            Update,
+            move_character_capsules.after(update_character_controllers),
+        )
+        .add_observer(on_go_ragdoll)
+        .add_observer(on_recover_from_ragdoll)
+        .add_observer(add_character_capsule)
+        .add_observer(spawn_character_capsule_body)
+        .add_observer(despawn_character_capsule_body);
+    }
+}
+
+#[derive(Deserialize, Asset, Reflect)]
+pub struct CharacterData {
+    pub id: String,
+    pub model_path: String,
+
+    #[serde(default)]
+    pub animation_paths: AnimationPaths,
+
+    // shared animation sets (by id) to include animations from
+    #[serde(default)]
+    pub animation_sets: Vec<String>,
+
+    // played when the character spawns, defaults to the first animation by name
+    #[serde(default)]
+    pub default_animation: Option<String>,
+
+    // crossfade duration (in seconds) used when switching animations
+    #[serde(default = "default_transition_duration")]
+    pub transition_duration: f32,
+
+    // per-transition crossfade overrides, keyed by "from" then "to" animation
+    #[serde(default)]
+    pub transitions: HashMap<String, HashMap<String, f32>>,
+
+    // remaps the bone names animations were authored for onto the model's skeleton
+    // for models that aren't rigged the same as the animations
+    #[serde(default)]
+    pub bone_map_path: Option<String>,
+
+    // a model rigged with the skeleton the animations were authored for,
+    // so that they can be converted to the model's rest pose and proportions
+    #[serde(default)]
+    pub source_skeleton_path: Option<String>,
+
+    // drop the "mixamorig:" bone prefix and name clips after their animation keys
+    // so that rigs from other sources can share animations with mixamo rigs
+    #[serde(default)]
+    pub normalize_names: bool,
+
+    // the bone whose translation is stripped from in place animations
+    #[serde(default = "default_root_bone")]
+    pub root_bone: String,
+
+    // named sets of bones that animations can be limited to
+    // (each bone includes its descendants)
+    #[serde(default)]
+    pub masks: HashMap<String, Vec<String>>,
+
+    // blend spaces play like animations, so their names share the animation namespace
+    #[serde(default)]
+    pub blend_spaces: HashMap<String, BlendSpaceData>,
+
+    // optional state machine that drives the character's animations
+    #[serde(default)]
+    pub state_machine: Option<AnimationStateMachineData>,
+
+    // keeps the character's feet on the ground with two-bone leg IK
+    #[serde(default)]
+    pub foot_ik: Option<FootIkData>,
+
+    // bone chains (hair, tails, cloth flaps) simulated as springs over the animation
+    #[serde(default)]
+    pub spring_bones: Vec<SpringChainData>,
+
+    // named attachment points for props, see AttachToBone
+    #[serde(default)]
+    pub sockets: HashMap<String, SocketData>,
+
+    // named sets of material overrides (e.g. alternate skins), see SetMaterialVariant
+    #[serde(default)]
+    pub material_variants: HashMap<String, MaterialVariantData>,
+
+    // named morph target weight presets, see SetExpression
+    #[serde(default)]
+    pub expressions: HashMap<String, ExpressionData>,
+
+    // sounds played by animation markers (e.g. footsteps), keyed by marker name
+    #[serde(default)]
+    pub marker_sounds: HashMap<String, MarkerSoundData>,
+
+    // one-shot animations played by the gamepad's face buttons
+    #[serde(default)]
+    pub gamepad_animations: HashMap<FaceButton, String>,
+
+    // lower detail models swapped in as the camera moves away
+    #[serde(default)]
+    pub lods: Vec<ModelLodData>,
+
+    // applied to the spawned model so that models exported
+    // at different scales or orientations match without re-exporting
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    #[serde(default)]
+    pub translation: [f32; 3],
+    // XYZ euler angles in degrees
+    #[serde(default)]
+    pub rotation: [f32; 3],
+}
+
+fn default_transition_duration() -> f32 {
+    0.25
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+fn default_root_bone() -> String {
+    MIXAMO_ROOT_BONE.to_string()
+}
+
+// an animation entry, either a bare path or an object with playback settings
+#[derive(Debug, Clone, Deserialize, Reflect)]
+#[serde(from = "AnimationDataDef")]
+pub struct AnimationData {
+    pub path: String,
+    pub loop_mode: LoopMode,
+    pub speed: f32,
+    pub clip_index: usize,
+
+    // strip the root motion so that the animation plays in place
+    pub in_place: bool,
+
+    // limits the animation to one of the character's masks,
+    // masked animations are layered over the current animation
+    pub mask: Option<String>,
+
+    // triggers an AnimationMarker when playback crosses each of these
+    pub events: Vec<AnimationMarkerData>,
+
+    // the name of the node whose AnimationPlayer plays the animation,
+    // for models with more than one (e.g. a character and a weapon rig)
+    pub player: Option<String>,
+}
+
+impl AnimationData {
+    pub fn clip_path(&self) -> String {
+        format!("{}#Animation{}", self.path, self.clip_index)
+    }
+
+    pub fn apply(&self, animation: &mut ActiveAnimation) {
+        animation.set_repeat(self.loop_mode.repeat());
+        animation.set_speed(self.speed);
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AnimationDataDef {
+    Path(String),
+    Settings {
+        path: String,
+        #[serde(rename = "loop", default = "default_loop_mode")]
+        loop_mode: LoopModeDef,
+        #[serde(default = "default_speed")]
+        speed: f32,
+        #[serde(default)]
+        clip_index: usize,
+        #[serde(default)]
+        in_place: bool,
+        #[serde(default)]
+        mask: Option<String>,
+        #[serde(default)]
+        events: Vec<AnimationMarkerData>,
+        #[serde(default)]
+        player: Option<String>,
+    },
+}
+
+fn default_loop_mode() -> LoopModeDef {
+    LoopModeDef::Mode(LoopMode::Loop)
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+impl From<AnimationDataDef> for AnimationData {
+    fn from(def: AnimationDataDef) -> Self {
+        match def {
+            AnimationDataDef::Path(path) => Self {
+                path,
+                loop_mode: LoopMode::Loop,
+                speed: default_speed(),
+                clip_index: 0,
+                in_place: false,
+                mask: None,
+                events: Vec::new(),
+                player: None,
+            },
+            AnimationDataDef::Settings {
+                path,
+                loop_mode,
+                speed,
+                clip_index,
+                in_place,
+                mask,
+                events,
+                player,
+            } => Self {
+                path,
+                loop_mode: loop_mode.into(),
+                speed,
+                clip_index,
+                in_place,
+                mask,
+                events,
+                player,
+            },
+        }
+    }
+}
+
+// the character's own animations by name
+// a map would silently drop all but the last of a repeated name,
+// so repeated names are kept for validation to report
+#[derive(Debug, Clone, Default, Deref, Reflect)]
+pub struct AnimationPaths {
+    #[deref]
+    animations: HashMap<String, AnimationData>,
+    duplicates: Vec<String>,
+}
+
+impl AnimationPaths {
+    // names that appear more than once, the last one is used
+    pub fn duplicates(&self) -> &[String] {
+        &self.duplicates
+    }
+}
+
+impl<'de> Deserialize<'de> for AnimationPaths {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct AnimationPathsVisitor;
+
+        impl<'de> Visitor<'de> for AnimationPathsVisitor {
+            type Value = AnimationPaths;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of animation names to animations")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut animation_paths = AnimationPaths::default();
+                while let Some((name, animation_data)) =
+                    map.next_entry::<String, AnimationData>()?
+                {
+                    if animation_paths
+                        .animations
+                        .insert(name.clone(), animation_data)
+                        .is_some()
+                        && !animation_paths.duplicates.contains(&name)
+                    {
+                        animation_paths.duplicates.push(name);
+                    }
+                }
+                Ok(animation_paths)
+            }
+        }
+
+        deserializer.deserialize_map(AnimationPathsVisitor)
+    }
+}
+
+impl CharacterData {
+    pub fn model_scene_path(&self) -> String {
+        format!("{}#Scene0", self.model_path)
+    }
+
+    pub fn model_transform(&self) -> Transform {
+        let [x, y, z] = self.rotation.map(f32::to_radians);
+        Transform {
+            translation: Vec3::from(self.translation),
+            rotation: Quat::from_euler(EulerRot::XYZ, x, y, z),
+            scale: Vec3::splat(self.scale),
+        }
+    }
+
+    pub fn transition_duration(&self, from: Option<&str>, to: impl AsRef<str>) -> Duration {
+        let duration = from
+            .and_then(|from| self.transitions.get(from))
+            .and_then(|transitions| transitions.get(to.as_ref()))
+            .copied()
+            .unwrap_or(self.transition_duration);
+        crossfade_duration(duration)
+    }
+}
+
+// a crossfade duration (in seconds) from character data,
+// anything negative, NaN or too long for a Duration is no crossfade instead of a panic
+pub fn crossfade_duration(seconds: f32) -> Duration {
+    Duration::try_from_secs_f32(seconds.max(0.0)).unwrap_or_default()
+}
+
+#[derive(Reflect)]
+pub struct Character {
+    data: Handle<CharacterData>,
+
+    // not available until the character data is loaded
+    model: Option<Handle<Scene>>,
+    // replaces the data's model_path, set by SwapCharacterModel
+    model_path: Option<String>,
+    // lower detail models and the distance they're used from, sorted by distance
+    lod_models: Vec<(f32, Handle<Scene>)>,
+    // the data's scale / offset / rotation for the model
+    model_transform: Transform,
+
+    // all of the character's clips live in this one graph
+    graph: Handle<AnimationGraph>,
+    animations: HashMap<String, AnimationNodeIndex>,
+    // the loaded clips, before any retargeting
+    clips: HashMap<String, Handle<AnimationClip>>,
+    // the animation markers' particle effects, by path
+    marker_effects: HashMap<String, Handle<ParticleEffect>>,
+
+    // the character's own animations plus those from its animation sets
+    animation_data: HashMap<String, AnimationData>,
+    default_animation: Option<String>,
+
+    blend_spaces: HashMap<String, BlendSpaceNodes>,
+
+    retarget: RetargetSettings,
+
+    // set once the graph's clips have been swapped for the retargeted / in place clips
+    // and its masks have been set up
+    processed: bool,
+
+    // spawns requested before the character finished loading (and their start times)
+    pending_spawns: Vec<(Transform, f32)>,
+
+    // rendered by the CharacterThumbnailPlugin once the character is ready
+    thumbnail: Option<Handle<Image>>,
+}
+
+impl Character {
+    pub fn data(&self) -> &Handle<CharacterData> {
+        &self.data
+    }
+
+    pub fn graph(&self) -> &Handle<AnimationGraph> {
+        &self.graph
+    }
+
+    pub fn model(&self) -> Option<&Handle<Scene>> {
+        self.model.as_ref()
+    }
+
+    pub fn model_transform(&self) -> Transform {
+        self.model_transform
+    }
+
+    pub fn lod_models(&self) -> impl Iterator<Item = &Handle<Scene>> {
+        self.lod_models.iter().map(|(_, lod)| lod)
+    }
+
+    // the model swapped in with SwapCharacterModel, if any
+    pub fn model_path(&self) -> Option<&str> {
+        self.model_path.as_deref()
+    }
+
+    pub fn retarget(&self) -> &RetargetSettings {
+        &self.retarget
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.model.is_some()
+    }
+
+    // a small render of the model, e.g. for selection UIs
+    pub fn thumbnail(&self) -> Option<&Handle<Image>> {
+        self.thumbnail.as_ref()
+    }
+
+    pub(crate) fn set_thumbnail(&mut self, thumbnail: Handle<Image>) {
+        self.thumbnail = Some(thumbnail);
+    }
+
+    // (finished, total) counting the data, model, every animation clip,
+    // the bone map and processing the clips for the model
+    // failed assets count as finished so that a bad path doesn't block forever
+    pub fn load_progress(&self, asset_server: &AssetServer) -> (usize, usize) {
+        let (finished, total) = self.asset_load_progress(asset_server);
+        if !self.needs_processing() {
+            return (finished, total);
+        }
+        (finished + self.processed as usize, total + 1)
+    }
+
+    // whether the loaded clips need retargeting, converting to in place, masking or markers
+    fn needs_processing(&self) -> bool {
+        self.retarget.is_enabled()
+            || self.animation_data.values().any(|animation_data| {
+                animation_data.in_place
+                    || animation_data.mask.is_some()
+                    || !animation_data.events.is_empty()
+            })
+    }
+
+    fn asset_load_progress(&self, asset_server: &AssetServer) -> (usize, usize) {
+        let Some(model) = &self.model else {
+            // we don't know how many clips there are until the data is loaded
+            return (0, 1);
+        };
+
+        let assets = std::iter::once(model.id().untyped())
+            .chain(self.lod_models.iter().map(|(_, lod)| lod.id().untyped()))
+            .chain(self.clips.values().map(|clip| clip.id().untyped()))
+            .chain(
+                self.marker_effects
+                    .values()
+                    .map(|effect| effect.id().untyped()),
+            )
+            .chain(
+                self.retarget
+                    .bone_map
+                    .iter()
+                    .map(|bone_map| bone_map.id().untyped()),
+            )
+            .chain(
+                self.retarget
+                    .source_skeleton
+                    .iter()
+                    .map(|source_skeleton| source_skeleton.id().untyped()),
+            )
+            .collect::<Vec<_>>();
+        let finished = assets
+            .iter()
+            .filter(|id| is_finished_loading(asset_server, **id))
+            .count();
+        (1 + finished, 1 + assets.len())
+    }
+
+    pub fn is_ready(&self, asset_server: &AssetServer) -> bool {
+        let (finished, total) = self.load_progress(asset_server);
+        finished == total
+    }
+
+    pub fn animations(&self) -> impl Iterator<Item = &String> {
+        self.animation_data.keys()
+    }
+
+    // the loaded clips, before any retargeting
+    pub fn clips(&self) -> impl Iterator<Item = &Handle<AnimationClip>> {
+        self.clips.values()
+    }
+
+    pub fn animation(&self, name: impl AsRef<str>) -> Option<&AnimationData> {
+        self.animation_data.get(name.as_ref())
+    }
+
+    pub fn default_animation(&self) -> Option<&str> {
+        self.default_animation.as_deref()
+    }
+
+    pub fn marker_effect(&self, path: impl AsRef<str>) -> Option<&Handle<ParticleEffect>> {
+        self.marker_effects.get(path.as_ref())
+    }
+
+    pub fn blend_spaces(&self) -> impl Iterator<Item = (&String, &BlendSpaceNodes)> {
+        self.blend_spaces.iter()
+    }
+
+    pub fn blend_space(&self, name: impl AsRef<str>) -> Option<&BlendSpaceNodes> {
+        self.blend_spaces.get(name.as_ref())
+    }
+
+    // the animation's node plus any blend space points that use it
+    fn animation_nodes(&self, name: &str) -> Vec<AnimationNodeIndex> {
+        self.animations
+            .get(name)
+            .copied()
+            .into_iter()
+            .chain(self.blend_spaces.values().flat_map(|blend_space| {
+                blend_space
+                    .points
+                    .iter()
+                    .filter(|(_, animation, _)| animation == name)
+                    .map(|(_, _, node)| *node)
+            }))
+            .collect()
+    }
+
+    pub fn animation_index(&self, name: impl AsRef<str>) -> Option<AnimationNodeIndex> {
+        self.animations.get(name.as_ref()).copied()
+    }
+
+    pub fn animation_name(&self, animation_index: AnimationNodeIndex) -> Option<&str> {
+        self.animations
+            .iter()
+            .find(|(_, index)| **index == animation_index)
+            .map(|(name, _)| name.as_str())
+    }
+}
+
+fn is_finished_loading(asset_server: &AssetServer, id: UntypedAssetId) -> bool {
+    matches!(
+        asset_server.get_recursive_dependency_load_state(id),
+        Some(RecursiveDependencyLoadState::Loaded | RecursiveDependencyLoadState::Failed(_))
+    )
+}
+
+#[derive(Default, Resource, Reflect)]
+#[reflect(Resource)]
+pub struct Characters(HashMap<String, Character>);
+
+impl Characters {
+    pub fn get(&self, id: impl AsRef<str>) -> Option<&Character> {
+        self.0.get(id.as_ref())
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &String> {
+        self.0.keys()
+    }
+
+    pub(crate) fn get_mut(&mut self, id: impl AsRef<str>) -> Option<&mut Character> {
+        self.0.get_mut(id.as_ref())
+    }
+
+    // (finished, total) across every character
+    pub fn load_progress(&self, asset_server: &AssetServer) -> (usize, usize) {
+        self.0
+            .values()
+            .map(|character| character.load_progress(asset_server))
+            .fold(
+                (0, 0),
+                |(finished, total), (character_finished, character_total)| {
+                    (finished + character_finished, total + character_total)
+                },
+            )
+    }
+
+    // the character playing on an AnimationPlayer with this graph
+    pub fn find_by_graph(&self, graph: &AnimationGraphHandle) -> Option<(&String, &Character)> {
+        self.0
+            .iter()
+            .find(|(_, character)| character.graph == graph.0)
+    }
+
+    fn find_by_data_mut(
+        &mut self,
+        asset_id: AssetId<CharacterData>,
+    ) -> Option<(&String, &mut Character)> {
+        self.0
+            .iter_mut()
+            .find(|(_, character)| character.data.id() == asset_id)
+    }
+
+    // starts loading the character data, use SpawnCharacter to spawn the model
+    pub fn load(
+        &mut self,
+        id: impl Into<String>,
+        path: impl Into<String>,
+        asset_server: &AssetServer,
+        animation_graphs: &mut Assets<AnimationGraph>,
+    ) {
+        let id = id.into();
+        let path = path.into();
+
+        info!("Loading character '{}' from '{}' ...", id, path);
+        let data = asset_server.load::<CharacterData>(path);
+
+        // we have to hold the data handle until the asset is loaded
+        // or the asset system will free it before we get a chance to use it
+        self.0.insert(
+            id,
+            Character {
+                data,
+                model: None,
+                model_path: None,
+                lod_models: Vec::new(),
+                model_transform: Transform::IDENTITY,
+                // the graph is filled in once the character data is loaded
+                graph: animation_graphs.add(AnimationGraph::new()),
+                animations: HashMap::new(),
+                clips: HashMap::new(),
+                marker_effects: HashMap::new(),
+                animation_data: HashMap::new(),
+                default_animation: None,
+                blend_spaces: HashMap::new(),
+                retarget: RetargetSettings::default(),
+                processed: false,
+                pending_spawns: Vec::new(),
+                thumbnail: None,
+            },
+        );
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct CharacterModel(pub Handle<CharacterData>);
+
+// the model's AnimationPlayers, set once the model's scene has spawned
+// (and again whenever a model LOD swap respawns it)
+#[derive(Debug, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct CharacterAnimator {
+    // the first player in the model, animations play on this unless they name another
+    pub player: Entity,
+
+    // every player in the model by the name of its node
+    pub players: HashMap<String, Entity>,
+}
+
+impl CharacterAnimator {
+    // the player an animation plays on
+    pub fn player_for(&self, character: &Character, animation_name: impl AsRef<str>) -> Entity {
+        character
+            .animation(animation_name)
+            .and_then(|animation_data| animation_data.player.as_ref())
+            .and_then(|player_name| self.players.get(player_name))
+            .copied()
+            .unwrap_or(self.player)
+    }
+}
+
+// a character's data, model or animation failed to load
+#[derive(Event)]
+pub struct CharacterLoadError {
+    pub id: String,
+    pub path: String,
+    pub error: String,
+}
+
+// triggered when a character's model is spawned, before its scene has spawned
+#[derive(Debug, Clone, Event)]
+pub struct CharacterSpawned {
+    // the model, with the CharacterModel component
+    pub entity: Entity,
+    pub id: String,
+}
+
+// triggered once a spawned model's scene is ready and its animation has started
+// the model has its CharacterAnimator by now
+#[derive(Debug, Clone, Event)]
+pub struct CharacterReady {
+    pub entity: Entity,
+    pub id: String,
+}
+
+// triggered once a character's assets have loaded and its animations
+// have been processed for its model (if they need it), again after a reload or a model swap
+#[derive(Debug, Clone, Event)]
+pub struct CharacterLoaded {
+    pub id: String,
+}
+
+// loads a character that isn't in the manifest (or a scanned folder) from its data file
+// nothing happens if a character with the id is already loaded
+#[derive(Debug, Clone, Event)]
+pub struct LoadCharacter {
+    pub id: String,
+    pub path: String,
+}
+
+impl LoadCharacter {
+    pub fn new(id: impl Into<String>, path: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            path: path.into(),
+        }
+    }
+}
+
+// spawns the model for a loaded character,
+// or once it finishes loading if it hasn't yet
+#[derive(Event)]
+pub struct SpawnCharacter {
+    pub id: String,
+    pub transform: Transform,
+
+    // seconds into the default animation to start at,
+    // so that crowds of the same character don't animate in lockstep
+    pub start_time: f32,
+}
+
+impl SpawnCharacter {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            transform: Transform::default(),
+            start_time: 0.0,
+        }
+    }
+
+    pub fn with_transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    pub fn with_start_time(mut self, start_time: f32) -> Self {
+        self.start_time = start_time;
+        self
+    }
+}
+
+// despawns a character's models and drops its assets
+// (clips shared with other characters stay loaded)
+// the character has to be loaded again before it can be spawned
+#[derive(Debug, Clone, Event)]
+pub struct UnloadCharacter {
+    pub id: String,
+}
+
+impl UnloadCharacter {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self { id: id.into() }
+    }
+}
+
+// replaces a character's model with another that shares its skeleton
+// (e.g. a different Mixamo skin on the same rig), keeping its animation graph and clips
+// spawned models are respawned with the new model once it finishes loading
+#[derive(Debug, Clone, Event)]
+pub struct SwapCharacterModel {
+    pub id: String,
+    pub model_path: String,
+}
+
+impl SwapCharacterModel {
+    pub fn new(id: impl Into<String>, model_path: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            model_path: model_path.into(),
+        }
+    }
+}
+
+// where the default animation starts, for models spawned with a start time
+#[derive(Component)]
+struct DefaultAnimationStartTime(f32);
+
+fn spawn_character_model(
+    commands: &mut Commands,
+    id: impl Into<String>,
+    character: &Character,
+    transform: Transform,
+    start_time: f32,
+) {
+    let id = id.into();
+    let _span = info_span!("spawn_character_model", character = %id).entered();
+    info!("Spawning character '{}' ...", id);
+
+    let mut model = commands.spawn((
+        SceneRoot(character.model.clone().unwrap()),
+        transform * character.model_transform,
+        AppliedModelTransform(character.model_transform),
+        Name::new(id.clone()),
+        CharacterModel(character.data.clone()),
+        AnimationSequence::default(),
+    ));
+    let entity = model.id();
+
+    // start the default animation once the scene spawns
+    model.observe(start_default_animation);
+    model.observe(setup_foot_ik);
+    model.observe(setup_look_at);
+    model.observe(setup_spring_bones);
+    model.observe(setup_character_attachments);
+    model.observe(reapply_material_variant);
+    model.observe(setup_morph_targets);
+
+    if character.retarget.normalize_names {
+        model.observe(normalize_model_bone_names);
+    }
+
+    if !character.blend_spaces.is_empty() {
+        model.insert(BlendSpacePositions::default());
+    }
+
+    if !character.lod_models.is_empty() {
+        let levels = std::iter::once((0.0, character.model.clone().unwrap()))
+            .chain(character.lod_models.iter().cloned())
+            .collect();
+        model.insert(ModelLod::new(levels));
+    }
+
+    if start_time > 0.0 {
+        model.insert(DefaultAnimationStartTime(start_time));
+    }
+
+    commands.trigger(CharacterSpawned { entity, id });
+}
+
+fn on_spawn_character(
+    event: On<SpawnCharacter>,
+    mut commands: Commands,
+    mut characters: ResMut<Characters>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(character) = characters.0.get_mut(&event.id) else {
+        warn!("Cannot spawn unknown character '{}'", event.id);
+        return;
+    };
+
+    if character.is_ready(&asset_server) {
+        spawn_character_model(
+            &mut commands,
+            &event.id,
+            character,
+            event.transform,
+            event.start_time,
+        );
+    } else {
+        character
+            .pending_spawns
+            .push((event.transform, event.start_time));
+    }
+}
+
+// spawn anything that was waiting on its character to finish loading
+fn spawn_pending_characters(
+    mut commands: Commands,
+    mut characters: ResMut<Characters>,
+    asset_server: Res<AssetServer>,
+) {
+    for (id, character) in &mut characters.0 {
+        if character.pending_spawns.is_empty() || !character.is_ready(&asset_server) {
+            continue;
+        }
+
+        for (transform, start_time) in std::mem::take(&mut character.pending_spawns) {
+            spawn_character_model(&mut commands, id, character, transform, start_time);
+        }
+    }
+}
+
+// swaps the graph's clips for clips retargeted to the character's skeleton,
+// converted to in place and with their markers added, and sets up the graph's masks,
+// once the model, clips and bone map have all loaded
+#[allow(clippy::too_many_arguments)]
+fn process_character_animations(
+    mut commands: Commands,
+    mut characters: ResMut<Characters>,
+    mut retarget_cache: ResMut<RetargetCache>,
+    character_datum: Res<Assets<CharacterData>>,
+    scenes: Res<Assets<Scene>>,
+    bone_maps: Res<Assets<BoneMap>>,
+    mut gltfs: ResMut<Assets<Gltf>>,
+    mut animation_clips: ResMut<Assets<AnimationClip>>,
+    mut animation_graphs: ResMut<Assets<AnimationGraph>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (id, character) in &mut characters.0 {
+        if character.processed {
+            continue;
+        }
+        let Some(model) = &character.model else {
+            continue;
+        };
+        let (finished, total) = character.asset_load_progress(&asset_server);
+        if finished != total {
+            continue;
+        }
+
+        // the loaded clips play as they are
+        if !character.needs_processing() {
+            character.processed = true;
+            commands.trigger(CharacterLoaded { id: id.clone() });
+            continue;
+        }
+        let Some(character_data) = character_datum.get(&character.data) else {
+            continue;
+        };
+
+        let _span = info_span!(
+            "process_character_animations",
+            character = %id,
+            animations = character.clips.len()
+        )
+        .entered();
+        info!("Processing animations for character '{}' ...", id);
+
+        let root_target = scenes.get(model).and_then(|scene| {
+            root_bone_target(
+                scene,
+                &character_data.root_bone,
+                character.retarget.normalize_names,
+            )
+        });
+
+        let mask_groups = scenes
+            .get(model)
+            .map(|scene| {
+                BoneMaskGroups::from_scene(
+                    scene,
+                    &character_data.masks,
+                    character.retarget.normalize_names,
+                )
+            })
+            .unwrap_or_default();
+
+        let animation_graph = animation_graphs.get_mut(&character.graph).unwrap();
+        mask_groups.apply(animation_graph);
+
+        for (animation_name, clip) in &character.clips {
+            let _span = info_span!("process_animation", animation = %animation_name).entered();
+            let mut processed_clip = clip.clone();
+
+            if character.retarget.is_enabled() {
+                let Some(retargeted) = retarget_cache.retarget_clip(
+                    clip,
+                    model,
+                    &character.retarget,
+                    &scenes,
+                    &bone_maps,
+                    &mut animation_clips,
+                ) else {
+                    // failed assets are reported by on_character_asset_failed
+                    warn!(
+                        "Unable to retarget animation '{}' for character '{}'",
+                        animation_name, id
+                    );
+                    continue;
+                };
+                processed_clip = retargeted;
+            }
+
+            if character.animation_data[animation_name].in_place {
+                let in_place = root_target.and_then(|root_target| {
+                    retarget_cache.in_place_clip(&processed_clip, root_target, &mut animation_clips)
+                });
+                match in_place {
+                    Some(in_place) => processed_clip = in_place,
+                    None => warn!(
+                        "Unable to find root bone '{}' to play animation '{}' in place for character '{}'",
+                        character_data.root_bone, animation_name, id
+                    ),
+                }
+            }
+
+            let markers = &character.animation_data[animation_name].events;
+            if !markers.is_empty()
+                && let Some(source_clip) = animation_clips.get(&processed_clip)
+            {
+                let marked_clip = add_animation_markers(source_clip, id, animation_name, markers);
+                processed_clip = animation_clips.add(marked_clip);
+            }
+
+            let mask = character.animation_data[animation_name]
+                .mask
+                .as_ref()
+                .and_then(|mask_name| {
+                    let mask = mask_groups.mask(mask_name);
+                    if mask.is_none() {
+                        warn!(
+                            "Character '{}' has no mask '{}' for animation '{}'",
+                            id, mask_name, animation_name
+                        );
+                    }
+                    mask
+                });
+
+            for animation_index in character.animation_nodes(animation_name) {
+                let animation_node = animation_graph.get_mut(animation_index).unwrap();
+                animation_node.node_type = AnimationNodeType::Clip(processed_clip.clone());
+                if let Some(mask) = mask {
+                    animation_node.mask = mask;
+                }
+            }
+
+            if character.retarget.normalize_names {
+                rename_clip(
+                    &mut gltfs,
+                    &asset_server,
+                    &character.animation_data[animation_name].path,
+                    clip,
+                    animation_name,
+                );
+            }
+        }
+
+        character.processed = true;
+        commands.trigger(CharacterLoaded { id: id.clone() });
+    }
+}
+
+// renames the clip in its glTF so that it can be found by its animation key
+fn rename_clip(
+    gltfs: &mut Assets<Gltf>,
+    asset_server: &AssetServer,
+    path: &str,
+    clip: &Handle<AnimationClip>,
+    animation_name: &str,
+) {
+    let Some(gltf) = asset_server
+        .get_handle::<Gltf>(path.to_string())
+        .and_then(|gltf| gltfs.get_mut(&gltf))
+    else {
+        return;
+    };
+
+    gltf.named_animations
+        .retain(|name, named_clip| named_clip != clip || name.as_ref() != MIXAMO_CLIP_NAME);
+    gltf.named_animations
+        .insert(animation_name.into(), clip.clone());
+}
+
+// registers a character from a manifest or folder scan
+// its data may already be loaded by the other source, in which case we
+// have already missed its load event and need to load its assets here
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn load_character(
+    characters: &mut Characters,
+    id: impl Into<String>,
+    path: impl Into<String>,
+    character_datum: &Assets<CharacterData>,
+    animation_sets: &Assets<AnimationSet>,
+    animation_library: &mut AnimationLibrary,
+    asset_server: &AssetServer,
+    animation_graphs: &mut Assets<AnimationGraph>,
+) {
+    let id = id.into();
+    if characters.get(&id).is_some() {
+        return;
+    }
+
+    characters.load(id.clone(), path, asset_server, animation_graphs);
+
+    let character = characters.0.get_mut(&id).unwrap();
+    if let Some(character_data) = character_datum.get(&character.data) {
+        load_character_assets(
+            character,
+            character_data,
+            animation_sets,
+            animation_library,
+            asset_server,
+            animation_graphs,
+        );
+    }
+}
+
+// (re)loads the character's model and rebuilds its animation graph
+fn load_character_assets(
+    character: &mut Character,
+    character_data: &CharacterData,
+    animation_sets: &Assets<AnimationSet>,
+    animation_library: &mut AnimationLibrary,
+    asset_server: &AssetServer,
+    animation_graphs: &mut Assets<AnimationGraph>,
+) {
+    let _span = info_span!("load_character_assets", character = %character_data.id).entered();
+
+    // load model
+    let model_path = match &character.model_path {
+        Some(model_path) => format!("{model_path}#Scene0"),
+        None => character_data.model_scene_path(),
+    };
+    info!("Loading character model from '{}' ...", model_path);
+    character.model = Some(asset_server.load::<Scene>(model_path));
+    character.model_transform = character_data.model_transform();
+
+    // load model LODs
+    // (these are made for the data's model, so a swapped model doesn't use them)
+    let lods = if character.model_path.is_none() {
+        character_data.lods.as_slice()
+    } else {
+        &[]
+    };
+    character.lod_models = lods
+        .iter()
+        .map(|lod| {
+            let lod_path = lod.model_scene_path();
+            info!(
+                "Loading character model LOD from '{}' for {}m ...",
+                lod_path, lod.distance
+            );
+            (lod.distance, asset_server.load::<Scene>(lod_path))
+        })
+        .collect();
+    character
+        .lod_models
+        .sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+    // load bone map
+    character.retarget = RetargetSettings {
+        bone_map: character_data.bone_map_path.as_ref().map(|bone_map_path| {
+            info!("Loading character bone map from '{}' ...", bone_map_path);
+            asset_server.load::<BoneMap>(bone_map_path)
+        }),
+        source_skeleton: character_data
+            .source_skeleton_path
+            .as_ref()
+            .map(|source_skeleton_path| {
+                info!(
+                    "Loading character source skeleton from '{}' ...",
+                    source_skeleton_path
+                );
+                asset_server.load::<Scene>(format!("{source_skeleton_path}#Scene0"))
+            }),
+        normalize_names: character_data.normalize_names,
+    };
+    character.processed = false;
+
+    // load animations
+    // all of the clips go into the same graph so that we can blend between them
+    let animation_graph = animation_graphs.get_mut(&character.graph).unwrap();
+    *animation_graph = AnimationGraph::new();
+    character.animations.clear();
+    character.clips.clear();
+    character.animation_data = animation_library.resolve_animations(character_data, animation_sets);
+    let _graph_span = info_span!(
+        "build_animation_graph",
+        animations = character.animation_data.len()
+    )
+    .entered();
+
+    // a blend only weighs its inputs against each other, so with no weight of its own
+    // the base animations still drive every bone that nothing else animates,
+    // while masked animations next to them override the bones they're masked to
+    let base_index = animation_graph.add_blend(0.0, animation_graph.root);
+
+    character.default_animation = character_data
+        .default_animation
+        .clone()
+        .or_else(|| character.animation_data.keys().min().cloned());
+    for (animation_name, animation_data) in &character.animation_data {
+        let animation_path = animation_data.clip_path();
+        info!(
+            "Loading character animation '{}' from '{}' ...",
+            animation_name, animation_path
+        );
+        let animation_clip = animation_library.clip(animation_path, asset_server);
+
+        let parent = if animation_data.mask.is_some() {
+            animation_graph.root
+        } else {
+            base_index
+        };
+        let animation_index = animation_graph.add_clip(animation_clip.clone(), 1.0, parent);
+        character
+            .clips
+            .insert(animation_name.clone(), animation_clip);
+        character
+            .animations
+            .insert(animation_name.clone(), animation_index);
+    }
+
+    // load marker effects
+    character.marker_effects.clear();
+    for marker in character
+        .animation_data
+        .values()
+        .flat_map(|animation_data| &animation_data.events)
+    {
+        if let Some(effect) = &marker.effect
+            && !character.marker_effects.contains_key(&effect.path)
+        {
+            info!("Loading marker effect from '{}' ...", effect.path);
+            character
+                .marker_effects
+                .insert(effect.path.clone(), asset_server.load(&effect.path));
+        }
+    }
+
+    // blend space points get their own clip nodes so that their weights
+    // don't fight with the animation being played on its own
+    character.blend_spaces.clear();
+    for (blend_space_name, blend_space_data) in &character_data.blend_spaces {
+        if character.animations.contains_key(blend_space_name) {
+            warn!(
+                "Character '{}' blend space '{}' has the same name as an animation",
+                character_data.id, blend_space_name
+            );
+            continue;
+        }
+
+        let mut points = Vec::new();
+        for point in &blend_space_data.points {
+            let Some(animation_clip) = character.clips.get(&point.animation) else {
+                warn!(
+                    "Character '{}' blend space '{}' has no animation '{}'",
+                    character_data.id, blend_space_name, point.animation
+                );
+                continue;
+            };
+
+            let point_index = animation_graph.add_clip(animation_clip.clone(), 1.0, base_index);
+            points.push((point.position, point.animation.clone(), point_index));
+        }
+        points.sort_by(|(a, _, _), (b, _, _)| a.total_cmp(b));
+
+        let blend_space_index = animation_graph.add_blend(1.0, base_index);
+        character
+            .animations
+            .insert(blend_space_name.clone(), blend_space_index);
+        character.blend_spaces.insert(
+            blend_space_name.clone(),
+            BlendSpaceNodes {
+                node: blend_space_index,
+                points,
+            },
+        );
+    }
+}
+
+fn on_character_data_loaded(
+    event: On<AssetLoadedEvent<CharacterData>>,
+    character_datum: Res<Assets<CharacterData>>,
+    animation_sets: Res<Assets<AnimationSet>>,
+    mut animation_library: ResMut<AnimationLibrary>,
+    mut animation_graphs: ResMut<Assets<AnimationGraph>>,
+    mut characters: ResMut<Characters>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(character_data) = character_datum.get(event.asset_id) else {
+        return;
+    };
+
+    // data loaded by a folder scan isn't registered until the whole folder loads
+    let Some((_, character)) = characters.find_by_data_mut(event.asset_id) else {
+        return;
+    };
+
+    // reloads are handled by on_character_data_modified
+    if character.is_loaded() {
+        return;
+    }
+
+    let _span = info_span!("on_character_data_loaded", character = %character_data.id).entered();
+    info!(
+        "Loaded character data for '{}', loading assets ...",
+        character_data.id
+    );
+
+    load_character_assets(
+        character,
+        character_data,
+        &animation_sets,
+        &mut animation_library,
+        &asset_server,
+        &mut animation_graphs,
+    );
+}
+
+// hot reload, rebuild the character and respawn any of its models
+#[allow(clippy::too_many_arguments)]
+fn on_character_data_modified(
+    event: On<AssetModifiedEvent<CharacterData>>,
+    mut commands: Commands,
+    character_datum: Res<Assets<CharacterData>>,
+    animation_sets: Res<Assets<AnimationSet>>,
+    mut animation_library: ResMut<AnimationLibrary>,
+    mut animation_graphs: ResMut<Assets<AnimationGraph>>,
+    mut characters: ResMut<Characters>,
+    asset_server: Res<AssetServer>,
+    character_models: Query<(Entity, &CharacterModel, &Transform, &AppliedModelTransform)>,
+) {
+    let Some(character_data) = character_datum.get(event.asset_id) else {
+        return;
+    };
+    let Some((_, character)) = characters.find_by_data_mut(event.asset_id) else {
+        return;
+    };
+
+    // the initial load is handled by on_character_data_loaded
+    if !character.is_loaded() {
+        return;
+    }
+
+    info!(
+        "Reloaded character data for '{}', reloading assets ...",
+        character_data.id
+    );
+
+    load_character_assets(
+        character,
+        character_data,
+        &animation_sets,
+        &mut animation_library,
+        &asset_server,
+        &mut animation_graphs,
+    );
+
+    respawn_character_models(&mut commands, character, &character_models);
+}
+
+// a (re)loaded animation set changes the animations of every character using it
+#[allow(clippy::too_many_arguments)]
+fn on_animation_set_loaded(
+    event: On<AssetLoadedEvent<AnimationSet>>,
+    mut commands: Commands,
+    character_datum: Res<Assets<CharacterData>>,
+    animation_sets: Res<Assets<AnimationSet>>,
+    mut animation_library: ResMut<AnimationLibrary>,
+    mut animation_graphs: ResMut<Assets<AnimationGraph>>,
+    mut characters: ResMut<Characters>,
+    asset_server: Res<AssetServer>,
+    character_models: Query<(Entity, &CharacterModel, &Transform, &AppliedModelTransform)>,
+) {
+    let Some(set_id) = animation_library.find_set(event.asset_id).cloned() else {
+        return;
+    };
+    info!("Loaded animation set '{}'", set_id);
+
+    for character in characters.0.values_mut() {
+        // characters that haven't loaded yet will pick up the set when they do
+        if !character.is_loaded() {
+            continue;
+        }
+        let Some(character_data) = character_datum.get(&character.data) else {
+            continue;
+        };
+        if !character_data.animation_sets.contains(&set_id) {
+            continue;
+        }
+
+        info!(
+            "Reloading animations for character '{}' ...",
+            character_data.id
+        );
+
+        load_character_assets(
+            character,
+            character_data,
+            &animation_sets,
+            &mut animation_library,
+            &asset_server,
+            &mut animation_graphs,
+        );
+
+        respawn_character_models(&mut commands, character, &character_models);
+    }
+}
+
+// edited bone maps need their clips retargeted again
+fn on_bone_map_modified(
+    event: On<AssetModifiedEvent<BoneMap>>,
+    mut characters: ResMut<Characters>,
+    mut retarget_cache: ResMut<RetargetCache>,
+) {
+    retarget_cache.invalidate_bone_map(event.asset_id);
+
+    for character in characters.0.values_mut() {
+        if character
+            .retarget
+            .bone_map
+            .as_ref()
+            .is_some_and(|bone_map| bone_map.id() == event.asset_id)
+        {
+            character.processed = false;
+        }
+    }
+}
+
+// the clips are kept, they only need processing again for the new model
+fn on_swap_character_model(
+    event: On<SwapCharacterModel>,
+    mut commands: Commands,
+    mut characters: ResMut<Characters>,
+    asset_server: Res<AssetServer>,
+    character_models: Query<(Entity, &CharacterModel, &Transform, &AppliedModelTransform)>,
+) {
+    let Some(character) = characters.0.get_mut(&event.id) else {
+        warn!("Cannot swap model for unknown character '{}'", event.id);
+        return;
+    };
+
+    info!(
+        "Swapping model for character '{}' to '{}' ...",
+        event.id, event.model_path
+    );
+    character.model_path = Some(event.model_path.clone());
+    // rendered again for the new model
+    character.thumbnail = None;
+
+    // characters that haven't loaded yet will pick up the model when they do
+    if !character.is_loaded() {
+        return;
+    }
+
+    character.model = Some(asset_server.load::<Scene>(format!("{}#Scene0", event.model_path)));
+    character.lod_models.clear();
+    character.processed = false;
+
+    respawn_character_models(&mut commands, character, &character_models);
+}
+
+// how many frames an unloaded character's assets get to be freed before it's reported
+const UNLOAD_CHECK_FRAMES: u32 = 10;
+
+// the assets an unloaded character held, checked until they've been freed
+// anything still loaded after that has a handle held somewhere else
+#[derive(Default, Resource)]
+struct PendingUnloadChecks(Vec<UnloadCheck>);
+
+struct UnloadCheck {
+    id: String,
+    frames: u32,
+    data: AssetId<CharacterData>,
+    scenes: Vec<AssetId<Scene>>,
+    graph: AssetId<AnimationGraph>,
+    clips: Vec<AssetId<AnimationClip>>,
+}
+
+fn on_load_character(
+    event: On<LoadCharacter>,
+    character_datum: Res<Assets<CharacterData>>,
+    animation_sets: Res<Assets<AnimationSet>>,
+    mut animation_library: ResMut<AnimationLibrary>,
+    mut characters: ResMut<Characters>,
+    mut animation_graphs: ResMut<Assets<AnimationGraph>>,
+    asset_server: Res<AssetServer>,
+) {
+    load_character(
+        &mut characters,
+        event.id.clone(),
+        event.path.clone(),
+        &character_datum,
+        &animation_sets,
+        &mut animation_library,
+        &asset_server,
+        &mut animation_graphs,
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn on_unload_character(
+    event: On<UnloadCharacter>,
+    mut commands: Commands,
+    mut characters: ResMut<Characters>,
+    mut animation_library: ResMut<AnimationLibrary>,
+    mut retarget_cache: ResMut<RetargetCache>,
+    mut vertex_animations: ResMut<VertexAnimations>,
+    mut material_variant_cache: ResMut<MaterialVariantCache>,
+    mut pending_unload_checks: ResMut<PendingUnloadChecks>,
+    character_models: Query<(Entity, &CharacterModel)>,
+) {
+    let Some(character) = characters.0.remove(&event.id) else {
+        warn!("Cannot unload unknown character '{}'", event.id);
+        return;
+    };
+    info!("Unloading character '{}' ...", event.id);
+
+    for (entity, character_model) in &character_models {
+        if character_model.0 == character.data {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    // drop the cached clips and retargets that only this character used
+    let clips_in_use = characters
+        .0
+        .values()
+        .flat_map(|character| character.clips.values().map(Handle::id))
+        .collect::<HashSet<_>>();
+    let models_in_use = characters
+        .0
+        .values()
+        .filter_map(|character| character.model.as_ref().map(Handle::id))
+        .collect::<HashSet<_>>();
+    animation_library.release_clips(|clip| clips_in_use.contains(&clip));
+    retarget_cache.release(
+        |clip| clips_in_use.contains(&clip),
+        |model| models_in_use.contains(&model),
+    );
+    vertex_animations.release(&event.id);
+    material_variant_cache.release(&event.id);
+
+    pending_unload_checks.0.push(UnloadCheck {
+        id: event.id.clone(),
+        frames: 0,
+        data: character.data.id(),
+        scenes: character
+            .model
+            .iter()
+            .chain(character.lod_models.iter().map(|(_, lod)| lod))
+            .map(Handle::id)
+            .collect(),
+        graph: character.graph.id(),
+        clips: character
+            .clips
+            .values()
+            .map(Handle::id)
+            .filter(|clip| !clips_in_use.contains(clip))
+            .collect(),
+    });
+
+    // the character's handles are dropped here
+}
+
+fn verify_character_unloads(
+    mut pending_unload_checks: ResMut<PendingUnloadChecks>,
+    character_datum: Res<Assets<CharacterData>>,
+    scenes: Res<Assets<Scene>>,
+    animation_graphs: Res<Assets<AnimationGraph>>,
+    animation_clips: Res<Assets<AnimationClip>>,
+) {
+    pending_unload_checks.0.retain_mut(|check| {
+        let loaded = usize::from(character_datum.contains(check.data))
+            + check.scenes.iter().filter(|id| scenes.contains(**id)).count()
+            + usize::from(animation_graphs.contains(check.graph))
+            + check
+                .clips
+                .iter()
+                .filter(|id| animation_clips.contains(**id))
+                .count();
+        if loaded == 0 {
+            info!("Released every asset for character '{}'", check.id);
+            return false;
+        }
+
+        check.frames += 1;
+        if check.frames >= UNLOAD_CHECK_FRAMES {
+            warn!(
+                "Character '{}' still has {} assets loaded after unloading, something else is holding them",
+                check.id, loaded
+            );
+            return false;
+        }
+        true
+    });
+}
+
+// the character data's model transform that a model was spawned with,
+// taken back out of the model's transform to find where it was placed
+#[derive(Component)]
+pub(crate) struct AppliedModelTransform(pub(crate) Transform);
+
+// despawns the character's models, they're respawned once its new assets finish loading
+fn respawn_character_models(
+    commands: &mut Commands,
+    character: &mut Character,
+    character_models: &Query<(Entity, &CharacterModel, &Transform, &AppliedModelTransform)>,
+) {
+    for (entity, character_model, transform, applied) in character_models {
+        if character_model.0 == character.data {
+            commands.entity(entity).despawn();
+
+            // the model transform may have changed, so respawn from where it was placed
+            let placement =
+                Transform::from_matrix(transform.to_matrix() * applied.0.to_matrix().inverse());
+            character.pending_spawns.push((placement, 0.0));
+        }
+    }
+}
+
+fn on_character_data_failed(
+    event: On<AssetFailedEvent<CharacterData>>,
+    mut commands: Commands,
+    mut characters: ResMut<Characters>,
+) {
+    let Some((id, _)) = characters.find_by_data_mut(event.asset_id) else {
+        return;
+    };
+
+    error!(
+        "Failed to load character data for '{}' from '{}': {}",
+        id, event.path, event.error
+    );
+
+    commands.trigger(CharacterLoadError {
+        id: id.clone(),
+        path: event.path.to_string(),
+        error: event.error.to_string(),
+    });
+}
+
+// model and animation failures are matched to characters by path
+fn on_character_asset_failed<A>(
+    event: On<AssetFailedEvent<A>>,
+    mut commands: Commands,
+    character_datum: Res<Assets<CharacterData>>,
+    characters: Res<Characters>,
+) where
+    A: Asset,
+{
+    let path = event.path.path();
+
+    for (id, character) in &characters.0 {
+        let Some(character_data) = character_datum.get(&character.data) else {
+            continue;
+        };
+
+        let uses_path = Path::new(&character_data.model_path) == path
+            || character
+                .model_path
+                .as_ref()
+                .is_some_and(|model_path| Path::new(model_path) == path)
+            || character_data
+                .bone_map_path
+                .as_ref()
+                .is_some_and(|bone_map_path| Path::new(bone_map_path) == path)
+            || character_data
+                .source_skeleton_path
+                .as_ref()
+                .is_some_and(|source_skeleton_path| Path::new(source_skeleton_path) == path)
+            || character
+                .animation_data
+                .values()
+                .any(|animation| Path::new(&animation.path) == path)
+            || character
+                .marker_effects
+                .keys()
+                .any(|effect_path| Path::new(effect_path) == path);
+        if !uses_path {
+            continue;
+        }
+
+        error!(
+            "Failed to load asset for character '{}' from '{}': {}",
+            id, event.path, event.error
+        );
+
+        commands.trigger(CharacterLoadError {
+            id: id.clone(),
+            path: event.path.to_string(),
+            error: event.error.to_string(),
+        });
+    }
+}
+
+// crossfades from whatever is currently playing to the named animation
+pub fn play_animation<'p>(
+    player: &'p mut AnimationPlayer,
+    transitions: &mut AnimationTransitions,
+    character: &Character,
+    character_data: &CharacterData,
+    animation_name: impl AsRef<str>,
+) -> Option<&'p mut ActiveAnimation> {
+    let animation_name = animation_name.as_ref();
+
+    let current_animation = transitions
+        .get_main_animation()
+        .and_then(|index| character.animation_name(index));
+    let transition_duration = character_data.transition_duration(current_animation, animation_name);
+
+    play_animation_with_transition(
+        player,
+        transitions,
+        character,
+        animation_name,
+        transition_duration,
+    )
+}
+
+// crossfades to the named animation over the given duration,
+// applying the animation's loop and speed settings
+pub fn play_animation_with_transition<'p>(
+    player: &'p mut AnimationPlayer,
+    transitions: &mut AnimationTransitions,
+    character: &Character,
+    animation_name: impl AsRef<str>,
+    transition_duration: Duration,
+) -> Option<&'p mut ActiveAnimation> {
+    let animation_name = animation_name.as_ref();
+    let animation_index = character.animation_index(animation_name)?;
+
+    // update_blend_spaces drives the points from the blend space's weight
+    if character.blend_space(animation_name).is_some() {
+        let animation = transitions.play(player, animation_index, transition_duration);
+        animation.set_repeat(RepeatAnimation::Forever);
+        return Some(animation);
+    }
+
+    let animation_data = character.animation(animation_name)?;
+
+    let animation = if animation_data.mask.is_some() {
+        // masked animations play on top of the current animation, so leave the transitions alone
+        player.start(animation_index)
+    } else {
+        transitions.play(player, animation_index, transition_duration)
+    };
+    animation_data.apply(animation);
+    Some(animation)
+}
+
+// triggered once when a non-repeating animation finishes playing
+#[derive(Debug, Clone, Event)]
+pub struct AnimationFinished {
+    // the AnimationPlayer that played the animation
+    pub entity: Entity,
+    pub character: String,
+    pub animation: String,
+}
+
+// finished animations stay on the player until they're stopped or replaced,
+// so remember which were already finished last frame to only trigger once
+fn trigger_animation_finished(
+    mut commands: Commands,
+    characters: Res<Characters>,
+    animation_players: Query<(Entity, &AnimationPlayer, &AnimationGraphHandle)>,
+    mut previously_finished: Local<HashSet<(Entity, AnimationNodeIndex)>>,
+) {
+    let mut finished = HashSet::new();
+    for (entity, player, graph) in &animation_players {
+        let Some((id, character)) = characters.find_by_graph(graph) else {
+            continue;
+        };
+
+        for (animation_index, animation) in player.playing_animations() {
+            if !animation.is_finished() {
+                continue;
+            }
+            finished.insert((entity, *animation_index));
+
+            if previously_finished.contains(&(entity, *animation_index)) {
+                continue;
+            }
+            let Some(animation_name) = character.animation_name(*animation_index) else {
+                continue;
+            };
+
+            commands.trigger(AnimationFinished {
+                entity,
+                character: id.clone(),
+                animation: animation_name.to_string(),
+            });
+        }
+    }
+    *previously_finished = finished;
+}
+
+// finished one-shot masked animations would otherwise hold their last frame
+fn stop_finished_layered_animations(
+    characters: Res<Characters>,
+    mut animation_players: Query<(&mut AnimationPlayer, &AnimationGraphHandle)>,
+) {
+    for (mut player, graph) in &mut animation_players {
+        let Some((_, character)) = characters.find_by_graph(graph) else {
+            continue;
+        };
+
+        for (animation_name, animation_data) in &character.animation_data {
+            if animation_data.mask.is_none() {
+                continue;
+            }
+            let Some(animation_index) = character.animation_index(animation_name) else {
+                continue;
+            };
+
+            if player
+                .animation(animation_index)
+                .is_some_and(|animation| animation.is_finished())
+            {
+                player.stop(animation_index);
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn start_default_animation(
+    scene_ready: On<SceneInstanceReady>,
+    mut commands: Commands,
+    character_datum: Res<Assets<CharacterData>>,
+    characters: Res<Characters>,
+    character_models: Query<(&CharacterModel, Option<&DefaultAnimationStartTime>)>,
+    mut lod_animation_states: Query<&mut ModelLodAnimationState>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    mut animation_players: Query<&mut AnimationPlayer>,
+) {
+    let Ok((character_model, start_time)) = character_models.get(scene_ready.entity) else {
+        return;
+    };
+    let Some(character_data) = character_datum.get(&character_model.0) else {
+        warn!("Spawned character model has no character data");
+        return;
+    };
+    let Some(character) = characters.get(&character_data.id) else {
+        warn!("Spawned unknown character '{}'", character_data.id);
+        return;
+    };
+    let _span = info_span!("start_default_animation", character = %character_data.id).entered();
+
+    // find the AnimationPlayers for the character
+    // (there's usually one on the root node of the scene, multi-root exports have one per root)
+    // and cache them so that nothing else has to search the model for them
+    let players = children
+        .iter_descendants(scene_ready.entity)
+        .filter(|child| animation_players.contains(*child))
+        .collect::<Vec<_>>();
+    let Some(&main_player) = players.first() else {
+        warn!("Character '{}' has no AnimationPlayer", character_data.id);
+        return;
+    };
+    let animator = CharacterAnimator {
+        player: main_player,
+        players: players
+            .iter()
+            .filter_map(|player| Some((names.get(*player).ok()?.to_string(), *player)))
+            .collect(),
+    };
+
+    for (animation_name, animation_data) in &character.animation_data {
+        if let Some(player_name) = &animation_data.player
+            && !animator.players.contains_key(player_name)
+        {
+            warn!(
+                "Character '{}' animation '{}' plays on unknown player '{}'",
+                character_data.id, animation_name, player_name
+            );
+        }
+    }
+
+    for &player in &players {
+        commands.entity(player).insert((
+            AnimationGraphHandle(character.graph.clone()),
+            AnimationTransitions::new(),
+            AnimationLoopModes::default(),
+        ));
+    }
+
+    // a model LOD swap carries on from where the previous model was
+    if let Ok(mut state) = lod_animation_states.get_mut(scene_ready.entity) {
+        if let Ok(mut player) = animation_players.get_mut(main_player) {
+            *player = std::mem::take(&mut state.player);
+        }
+        commands.entity(main_player).insert((
+            state.transitions.take().unwrap_or_default(),
+            state.loop_modes.take().unwrap_or_default(),
+        ));
+        commands
+            .entity(scene_ready.entity)
+            .remove::<ModelLodAnimationState>()
+            .insert(animator);
+        return;
+    }
+
+    if character_data.state_machine.is_some() {
+        // the state machine will start its initial state
+        info!(
+            "Starting animation state machine for character '{}' ...",
+            character_data.id
+        );
+
+        commands
+            .entity(scene_ready.entity)
+            .insert(AnimationStateMachine::default());
+    } else if let Some(default_animation) = character.default_animation() {
+        info!(
+            "Running animation '{}' for character '{}' ...",
+            default_animation, character_data.id
+        );
+
+        let player = animator.player_for(character, default_animation);
+        let mut transitions = AnimationTransitions::new();
+        let animation = animation_players
+            .get_mut(player)
+            .ok()
+            .and_then(|mut player| {
+                let animation = play_animation(
+                    &mut player,
+                    &mut transitions,
+                    character,
+                    character_data,
+                    default_animation,
+                )?;
+                if let Some(start_time) = start_time {
+                    animation.seek_to(start_time.0);
+                }
+                Some(())
+            });
+        if animation.is_none() {
+            warn!(
+                "Character '{}' has no default animation '{}'",
+                character_data.id, default_animation
+            );
+        }
+        commands.entity(player).insert(transitions);
+    } else {
+        warn!("Character '{}' has no animations", character_data.id);
+    }
+
+    commands.entity(scene_ready.entity).insert(animator);
+
+    commands.trigger(CharacterReady {
+        entity: scene_ready.entity,
+        id: character_data.id.clone(),
+    });
+}
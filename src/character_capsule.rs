@@ -0,0 +1,253 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::character::*;
+use crate::ragdoll::*;
+
+// character capsules are only swept against the world, not each other
+pub(crate) const CHARACTER_LAYER: u32 = 0b100;
+
+// the capsule is kept this far off whatever it touches,
+// so that casts don't start out touching the ground
+const CAPSULE_SKIN: f32 = 0.02;
+
+// moves further than this in one frame are teleports (e.g. a respawn), not walking
+const MAX_CAPSULE_MOVE: f32 = 1.0;
+
+// slides along walls at most this many times a frame
+const MAX_CAPSULE_SLIDES: usize = 3;
+
+const CAPSULE_GRAVITY: f32 = -9.81;
+
+// a kinematic capsule that a character model is moved with,
+// so that the controller (and root motion) collides with the world and follows slopes
+// added to every character model, insert one before it spawns to change its size
+#[derive(Debug, Clone, Component)]
+pub struct CharacterCapsule {
+    pub radius: f32,
+    // including the end caps
+    pub height: f32,
+
+    // steeper ground is treated as a wall, in degrees
+    pub max_slope: f32,
+
+    // while grounded the capsule sticks to ground this far below it (e.g. walking down slopes)
+    pub snap_distance: f32,
+
+    // the collider, it isn't part of the model's hierarchy so that the model's scale doesn't apply
+    body: Option<Entity>,
+
+    // where the model's feet were last frame, None until the first update
+    position: Option<Vec3>,
+    vertical_speed: f32,
+    grounded: bool,
+}
+
+impl Default for CharacterCapsule {
+    fn default() -> Self {
+        Self {
+            radius: 0.3,
+            height: 1.8,
+            max_slope: 45.0,
+            snap_distance: 0.3,
+            body: None,
+            position: None,
+            vertical_speed: 0.0,
+            grounded: false,
+        }
+    }
+}
+
+impl CharacterCapsule {
+    pub fn new(radius: f32, height: f32) -> Self {
+        Self {
+            radius,
+            height,
+            ..default()
+        }
+    }
+
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    fn collider(&self) -> Collider {
+        Collider::capsule(self.radius, (self.height - self.radius * 2.0).max(0.0))
+    }
+
+    fn center(&self, feet: Vec3) -> Vec3 {
+        feet + Vec3::Y * (self.height * 0.5 + CAPSULE_SKIN)
+    }
+
+    fn is_walkable(&self, normal: Vec3) -> bool {
+        normal.angle_between(Vec3::Y) <= self.max_slope.to_radians()
+    }
+}
+
+pub(crate) fn add_character_capsule(event: On<Add, CharacterModel>, mut commands: Commands) {
+    commands
+        .entity(event.entity)
+        .insert_if_new(CharacterCapsule::default());
+}
+
+pub(crate) fn spawn_character_capsule_body(
+    event: On<Add, CharacterCapsule>,
+    mut commands: Commands,
+    mut capsules: Query<(&mut CharacterCapsule, &Transform)>,
+) {
+    let Ok((mut capsule, transform)) = capsules.get_mut(event.entity) else {
+        return;
+    };
+
+    let body = commands
+        .spawn((
+            RigidBody::Kinematic,
+            capsule.collider(),
+            CollisionLayers::new(CHARACTER_LAYER, WORLD_LAYER),
+            Transform::from_translation(capsule.center(transform.translation)),
+            Name::new("Character Capsule"),
+        ))
+        .id();
+    capsule.body = Some(body);
+}
+
+pub(crate) fn despawn_character_capsule_body(
+    event: On<Remove, CharacterCapsule>,
+    mut commands: Commands,
+    capsules: Query<&CharacterCapsule>,
+) {
+    if let Some(body) = capsules
+        .get(event.entity)
+        .ok()
+        .and_then(|capsule| capsule.body)
+    {
+        commands.entity(body).try_despawn();
+    }
+}
+
+// sweeps the capsule along the motion, sliding along whatever it hits
+fn slide_capsule(
+    spatial_query: &SpatialQuery,
+    filter: &SpatialQueryFilter,
+    capsule: &CharacterCapsule,
+    collider: &Collider,
+    mut feet: Vec3,
+    mut motion: Vec3,
+) -> Vec3 {
+    for _ in 0..MAX_CAPSULE_SLIDES {
+        let Ok((direction, distance)) = Dir3::new_and_length(motion) else {
+            break;
+        };
+
+        let Some(hit) = spatial_query.cast_shape(
+            collider,
+            capsule.center(feet),
+            Quat::IDENTITY,
+            direction,
+            &ShapeCastConfig::from_max_distance(distance + CAPSULE_SKIN),
+            filter,
+        ) else {
+            feet += motion;
+            break;
+        };
+
+        let travel = (hit.distance - CAPSULE_SKIN).max(0.0);
+        feet += direction * travel;
+
+        // walkable slopes are climbed, walls only slide sideways
+        let remaining = direction * (distance - travel);
+        let normal = if capsule.is_walkable(hit.normal1) {
+            hit.normal1
+        } else {
+            Vec3::new(hit.normal1.x, 0.0, hit.normal1.z).normalize_or_zero()
+        };
+        motion = remaining.reject_from_normalized(normal);
+    }
+    feet
+}
+
+// runs after the controllers, moving each model from where it was to where it's trying to go
+#[allow(clippy::type_complexity)]
+pub(crate) fn move_character_capsules(
+    time: Res<Time>,
+    spatial_query: SpatialQuery,
+    mut models: Query<(
+        &mut CharacterCapsule,
+        &mut Transform,
+        &AppliedModelTransform,
+        Has<Ragdoll>,
+    )>,
+    mut bodies: Query<&mut Transform, Without<CharacterCapsule>>,
+) {
+    let dt = time.delta_secs();
+    let filter = SpatialQueryFilter::from_mask(WORLD_LAYER);
+
+    for (mut capsule, mut transform, applied, ragdolling) in &mut models {
+        // the model's feet are where it was placed, before its model transform
+        let target =
+            Transform::from_matrix(transform.to_matrix() * applied.0.to_matrix().inverse())
+                .translation;
+        let collider = capsule.collider();
+
+        let feet = match capsule.position {
+            // ragdolls move the model themselves
+            Some(position) if !ragdolling && position.distance(target) <= MAX_CAPSULE_MOVE => {
+                let motion = target - position;
+                let feet = slide_capsule(
+                    &spatial_query,
+                    &filter,
+                    &capsule,
+                    &collider,
+                    position,
+                    Vec3::new(motion.x, 0.0, motion.z),
+                );
+
+                // fall, or stick to the ground
+                capsule.vertical_speed = if capsule.grounded {
+                    0.0
+                } else {
+                    capsule.vertical_speed + CAPSULE_GRAVITY * dt
+                };
+                let fall = (-capsule.vertical_speed * dt).max(0.0);
+                let reach = if capsule.grounded {
+                    capsule.snap_distance
+                } else {
+                    fall
+                };
+                let ground = spatial_query
+                    .cast_shape(
+                        &collider,
+                        capsule.center(feet),
+                        Quat::IDENTITY,
+                        Dir3::NEG_Y,
+                        &ShapeCastConfig::from_max_distance(reach + CAPSULE_SKIN),
+                        &filter,
+                    )
+                    .filter(|hit| capsule.is_walkable(hit.normal1));
+
+                match ground {
+                    Some(hit) => {
+                        capsule.grounded = true;
+                        capsule.vertical_speed = 0.0;
+                        feet - Vec3::Y * (hit.distance - CAPSULE_SKIN).max(0.0)
+                    }
+                    None => {
+                        capsule.grounded = false;
+                        feet - Vec3::Y * fall
+                    }
+                }
+            }
+            _ => {
+                capsule.vertical_speed = 0.0;
+                target
+            }
+        };
+
+        transform.translation += feet - target;
+        capsule.position = Some(feet);
+
+        if let Some(mut body) = capsule.body.and_then(|body| bodies.get_mut(body).ok()) {
+            body.translation = capsule.center(feet);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use clap::Parser;
+
+use crate::bench::*;
+use crate::settings::*;
+
+/// Bevy Mixamo character viewer
+#[derive(Debug, Clone, Parser, Resource)]
+#[command(version)]
+pub struct Cli {
+    /// Character file to load and spawn, instead of the first of the manifest's characters
+    #[arg(long, value_name = "PATH")]
+    pub character: Option<String>,
+
+    /// Animation to play on spawned characters, instead of their default animation
+    #[arg(long)]
+    pub animation: Option<String>,
+
+    /// Wait for vsync instead of rendering as fast as possible [default: the last run's setting]
+    #[arg(long)]
+    pub vsync: bool,
+
+    /// Window width in pixels [default: the last run's, or 1280]
+    #[arg(long)]
+    pub width: Option<u32>,
+
+    /// Window height in pixels [default: the last run's, or 720]
+    #[arg(long)]
+    pub height: Option<u32>,
+
+    /// How many copies of the first character to spawn, side by side
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    pub spawn_count: u32,
+
+    /// Step animations at a fixed 60 Hz, for reproducible captures
+    #[arg(long)]
+    pub fixed_animation: bool,
+
+    /// Load every character without a window, report any failures and exit
+    #[arg(long)]
+    pub headless_validate: bool,
+
+    /// Compare animated bone poses against golden/poses.json and exit
+    #[arg(long)]
+    pub golden_poses: bool,
+
+    /// Record the current bone poses to golden/poses.json and exit
+    #[arg(long, conflicts_with = "golden_poses")]
+    pub golden_poses_update: bool,
+
+    /// Benchmark a crowd, e.g. crowd=500, write a report and exit
+    #[arg(long, value_name = "MODE", value_parser = parse_bench_mode)]
+    pub bench: Option<u32>,
+
+    /// Seconds to measure the benchmark for
+    #[arg(long, value_name = "SECONDS", requires = "bench", value_parser = parse_bench_duration)]
+    pub bench_duration: Option<Duration>,
+
+    /// Where to write the benchmark report
+    #[arg(long, value_name = "PATH", requires = "bench")]
+    pub bench_output: Option<PathBuf>,
+}
+
+// crowd=<count>, the only bench mode so far
+fn parse_bench_mode(mode: &str) -> Result<u32, String> {
+    let count = mode
+        .strip_prefix("crowd=")
+        .ok_or_else(|| format!("unknown bench mode '{mode}', e.g. crowd=500"))?;
+    count
+        .parse::<u32>()
+        .map_err(|err| format!("invalid crowd size '{count}': {err}"))
+}
+
+fn parse_bench_duration(seconds: &str) -> Result<Duration, String> {
+    let duration = seconds
+        .parse::<f32>()
+        .map_err(|err| format!("invalid duration '{seconds}': {err}"))?;
+    Duration::try_from_secs_f32(duration)
+        .ok()
+        .filter(|duration| !duration.is_zero())
+        .ok_or_else(|| format!("'{seconds}' isn't a usable number of seconds, e.g. 10"))
+}
+
+impl Cli {
+    // command line options win over the saved settings
+    pub fn apply_to(&self, settings: &mut AppSettings) {
+        if let Some(width) = self.width {
+            settings.width = width;
+        }
+        if let Some(height) = self.height {
+            settings.height = height;
+        }
+        if self.vsync {
+            settings.vsync = true;
+        }
+    }
+
+    // None if the app wasn't started in bench mode
+    pub fn crowd_bench(&self) -> Option<CrowdBench> {
+        let mut bench = CrowdBench::new(self.bench?);
+        if let Some(duration) = self.bench_duration {
+            bench.duration = duration;
+        }
+        if let Some(output) = &self.bench_output {
+            bench.output = output.clone();
+        }
+        Some(bench)
+    }
+}
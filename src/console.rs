@@ -0,0 +1,317 @@
+use bevy::{
+    input::{
+        ButtonState, InputSystems,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+};
+use bevy_mixamo::*;
+
+use crate::SPAWN_SPACING;
+use crate::focus::*;
+use crate::playback::{MAX_SPEED, MIN_SPEED, PlaybackSettings};
+use crate::remote::*;
+use crate::scroll::*;
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_systems(Startup, setup_console)
+            // ahead of everything else that reads the keyboard
+            .add_systems(
+                PreUpdate,
+                handle_console_keys.after(InputSystems).before(FocusSystems),
+            )
+            .add_systems(Update, update_console)
+            .add_observer(run_console_command);
+    }
+}
+
+// what's been typed and printed, the console has the keyboard while it's open
+#[derive(Debug, Default, Resource)]
+pub struct Console {
+    pub open: bool,
+    pub input: String,
+    pub output: Vec<String>,
+
+    // submitted lines, up / down step back through them
+    history: Vec<String>,
+    history_index: Option<usize>,
+}
+
+impl Console {
+    pub fn print(&mut self, line: impl Into<String>) {
+        let line = line.into();
+        info!("Console: {}", line);
+        self.output.push(line);
+        if self.output.len() > MAX_CONSOLE_LINES {
+            let dropped = self.output.len() - MAX_CONSOLE_LINES;
+            self.output.drain(..dropped);
+        }
+    }
+}
+
+// a line submitted to the console, e.g. "play mutant run"
+#[derive(Debug, Clone, Event)]
+pub struct ConsoleCommand(pub String);
+
+// older output is dropped
+const MAX_CONSOLE_LINES: usize = 100;
+
+// most characters spawn one command can ask for
+const MAX_CONSOLE_SPAWN: u32 = 100;
+
+const CONSOLE_HELP: &[&str] = &[
+    "play <character> <animation>  play an animation on every model of the character",
+    "spawn <character> [count]     spawn models of a loaded character",
+    "speed <multiplier>            scale the speed of every animation",
+    "clear                         clear the console",
+    "help                          list the commands",
+];
+
+// over the rest of the UI, under dialogs, the focus ring and tooltips
+const CONSOLE_Z_INDEX: i32 = i32::MAX - 3;
+
+#[derive(Component)]
+struct ConsolePanel;
+
+#[derive(Component)]
+struct ConsoleOutput;
+
+#[derive(Component)]
+struct ConsoleInput;
+
+fn setup_console(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(0.0),
+            left: Val::Px(0.0),
+            width: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(5.0)),
+            border: UiRect::bottom(Val::Px(2.0)),
+            display: Display::None,
+            ..default()
+        },
+        BorderColor::all(Color::BLACK),
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.95)),
+        GlobalZIndex(CONSOLE_Z_INDEX),
+        Name::new("Console"),
+        ConsolePanel,
+        children![
+            (
+                Node {
+                    height: Val::Vh(30.0),
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                },
+                ScrollView::with_scrollbar(),
+                ConsoleOutput,
+            ),
+            (
+                Text::new("> "),
+                TextFont {
+                    font_size: 16.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                ConsoleInput,
+            ),
+        ],
+    ));
+}
+
+// ` (~) opens and closes it, escape closes it
+fn handle_console_keys(
+    mut commands: Commands,
+    mut console: ResMut<Console>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut keyboard_inputs: MessageReader<KeyboardInput>,
+) {
+    if keyboard.just_pressed(KeyCode::Backquote) {
+        console.open = !console.open;
+        keyboard.reset(KeyCode::Backquote);
+        // don't type the ` that opened it
+        keyboard_inputs.clear();
+        return;
+    }
+    if !console.open {
+        keyboard_inputs.clear();
+        return;
+    }
+
+    for input in keyboard_inputs.read() {
+        if input.state != ButtonState::Pressed {
+            continue;
+        }
+        match &input.logical_key {
+            Key::Character(character) => console.input.push_str(character),
+            Key::Space => console.input.push(' '),
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::Escape => console.open = false,
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input);
+                let line = line.trim();
+                console.history_index = None;
+                if !line.is_empty() {
+                    console.print(format!("> {line}"));
+                    console.history.push(line.to_string());
+                    commands.trigger(ConsoleCommand(line.to_string()));
+                }
+            }
+            Key::ArrowUp if !console.history.is_empty() => {
+                let index = console
+                    .history_index
+                    .map_or(console.history.len() - 1, |index| index.saturating_sub(1));
+                console.history_index = Some(index);
+                console.input = console.history[index].clone();
+            }
+            Key::ArrowDown => {
+                let index = console
+                    .history_index
+                    .map(|index| index + 1)
+                    .filter(|index| *index < console.history.len());
+                console.history_index = index;
+                console.input = index
+                    .map(|index| console.history[index].clone())
+                    .unwrap_or_default();
+            }
+            _ => (),
+        }
+    }
+
+    // the viewer's shortcuts (and the character controller) don't see what's typed
+    keyboard.reset_all();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_console_command(
+    command: On<ConsoleCommand>,
+    mut commands: Commands,
+    mut console: ResMut<Console>,
+    mut playback_settings: ResMut<PlaybackSettings>,
+    character_datum: Res<Assets<CharacterData>>,
+    characters: Res<Characters>,
+    character_models: CharacterAnimatorQuery,
+    mut animation_players: AnimationPlayerQuery,
+) {
+    let words = command.0.split_whitespace().collect::<Vec<_>>();
+    match words.as_slice() {
+        ["help"] => {
+            for line in CONSOLE_HELP {
+                console.print(*line);
+            }
+        }
+        ["clear"] => console.output.clear(),
+        // animation names can have spaces
+        ["play", character_id, animation @ ..] if !animation.is_empty() => {
+            let animation = animation.join(" ");
+            match play_character_animation(
+                character_id,
+                &animation,
+                None,
+                &character_datum,
+                &characters,
+                &character_models,
+                &mut animation_players,
+            ) {
+                Ok(played) => console.print(format!(
+                    "Playing '{animation}' on {played} models of '{character_id}'"
+                )),
+                Err(err) => console.print(err),
+            }
+        }
+        ["spawn", character_id, count @ ..] if count.len() <= 1 => {
+            let count = match count.first().map(|count| count.parse::<u32>()) {
+                None => 1,
+                Some(Ok(count)) if (1..=MAX_CONSOLE_SPAWN).contains(&count) => count,
+                _ => {
+                    console.print(format!("count must be 1 to {MAX_CONSOLE_SPAWN}"));
+                    return;
+                }
+            };
+            if characters.get(character_id).is_none() {
+                console.print(format!("unknown character '{character_id}'"));
+                return;
+            }
+
+            // side by side, a row behind the ones spawned at startup
+            let width = (count - 1) as f32 * SPAWN_SPACING;
+            for index in 0..count {
+                commands.trigger(SpawnCharacter::new(*character_id).with_transform(
+                    Transform::from_xyz(
+                        index as f32 * SPAWN_SPACING - width * 0.5,
+                        0.0,
+                        -SPAWN_SPACING,
+                    ),
+                ));
+            }
+            console.print(format!("Spawning {count} models of '{character_id}'"));
+        }
+        ["speed", speed] => match speed.parse::<f32>() {
+            // also keeps out NaN and inf
+            Ok(speed) if (MIN_SPEED..=MAX_SPEED).contains(&speed) => {
+                playback_settings.speed = speed;
+                console.print(format!("Playback speed {speed:.2}x"));
+            }
+            _ => console.print(format!(
+                "speed must be a number from {MIN_SPEED} to {MAX_SPEED}"
+            )),
+        },
+        _ => console.print(format!("unknown command '{}', try help", command.0)),
+    }
+}
+
+// shows the panel and the input, rebuilding the output rows when they change
+fn update_console(
+    mut commands: Commands,
+    mut shown: Local<Vec<String>>,
+    console: Res<Console>,
+    mut panel: Single<&mut Node, With<ConsolePanel>>,
+    output: Single<(Entity, &mut ScrollPosition), With<ConsoleOutput>>,
+    mut input: Single<&mut Text, With<ConsoleInput>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+
+    let display = if console.open {
+        Display::Flex
+    } else {
+        Display::None
+    };
+    if panel.display != display {
+        panel.display = display;
+    }
+
+    let caret = format!("> {}_", console.input);
+    if input.0 != caret {
+        input.0 = caret;
+    }
+
+    if *shown == console.output {
+        return;
+    }
+    shown.clone_from(&console.output);
+
+    let (output, mut scroll_position) = output.into_inner();
+    commands.entity(output).despawn_related::<Children>();
+    for line in &console.output {
+        commands.spawn((
+            Text::new(line.clone()),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            ChildOf(output),
+        ));
+    }
+    // always on the latest output
+    scroll_position.y = f32::MAX;
+}
@@ -0,0 +1,222 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::blend_space::*;
+use crate::character::*;
+use crate::sequence::*;
+
+// moves a character model with WASD or a gamepad's left stick,
+// its speed drives a locomotion blend space (e.g. idle -> walk -> run)
+// the blend space's animations should be in place so they don't fight the movement
+#[derive(Debug, Clone, Component)]
+pub struct CharacterController {
+    pub blend_space: String,
+
+    // units per second, shift (or a full stick) runs
+    pub walk_speed: f32,
+    pub run_speed: f32,
+
+    // how quickly the speed changes, in units per second per second
+    pub acceleration: f32,
+
+    // how quickly the model turns to face the movement, in radians per second
+    pub turn_speed: f32,
+
+    speed: f32,
+}
+
+impl Default for CharacterController {
+    fn default() -> Self {
+        Self {
+            blend_space: "locomotion".to_string(),
+            walk_speed: 1.5,
+            run_speed: 4.0,
+            acceleration: 8.0,
+            turn_speed: 10.0,
+            speed: 0.0,
+        }
+    }
+}
+
+impl CharacterController {
+    pub fn new(blend_space: impl Into<String>) -> Self {
+        Self {
+            blend_space: blend_space.into(),
+            ..default()
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+}
+
+// gamepad face buttons by position, so layouts with different labels map the same
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Reflect)]
+#[serde(rename_all = "snake_case")]
+pub enum FaceButton {
+    South,
+    East,
+    North,
+    West,
+}
+
+impl FaceButton {
+    pub const ALL: [Self; 4] = [Self::South, Self::East, Self::North, Self::West];
+
+    pub fn gamepad_button(&self) -> GamepadButton {
+        match self {
+            Self::South => GamepadButton::South,
+            Self::East => GamepadButton::East,
+            Self::North => GamepadButton::North,
+            Self::West => GamepadButton::West,
+        }
+    }
+}
+
+// the movement input in world space (x, z), up on the stick / W moves away from the camera
+fn movement_input(keyboard: &ButtonInput<KeyCode>, gamepads: &Query<&Gamepad>) -> (Vec2, bool) {
+    let mut input = Vec2::ZERO;
+    if keyboard.pressed(KeyCode::KeyW) {
+        input.y -= 1.0;
+    }
+    if keyboard.pressed(KeyCode::KeyS) {
+        input.y += 1.0;
+    }
+    if keyboard.pressed(KeyCode::KeyA) {
+        input.x -= 1.0;
+    }
+    if keyboard.pressed(KeyCode::KeyD) {
+        input.x += 1.0;
+    }
+    let running = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if input != Vec2::ZERO {
+        return (input.normalize(), running);
+    }
+
+    // the stick is analog, so it walks or runs depending on how far it's pushed
+    for gamepad in gamepads {
+        let stick = gamepad.left_stick();
+        if stick.length() > 0.1 {
+            return (Vec2::new(stick.x, -stick.y).clamp_length_max(1.0), true);
+        }
+    }
+    (Vec2::ZERO, false)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn update_character_controllers(
+    time: Res<Time>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    character_datum: Res<Assets<CharacterData>>,
+    characters: Res<Characters>,
+    mut controllers: Query<(
+        Option<&CharacterAnimator>,
+        &CharacterModel,
+        &mut CharacterController,
+        &mut Transform,
+        &mut BlendSpacePositions,
+    )>,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+) {
+    let (input, running) = movement_input(&keyboard, &gamepads);
+    let dt = time.delta_secs();
+
+    for (animator, character_model, mut controller, mut transform, mut positions) in
+        &mut controllers
+    {
+        let Some(character_data) = character_datum.get(&character_model.0) else {
+            continue;
+        };
+        let Some(character) = characters.get(&character_data.id) else {
+            continue;
+        };
+        let Some(blend_space_index) = character.animation_index(&controller.blend_space) else {
+            continue;
+        };
+
+        let max_speed = if running {
+            controller.run_speed
+        } else {
+            controller.walk_speed
+        };
+        let target_speed = input.length() * max_speed;
+        controller.speed = if controller.speed < target_speed {
+            (controller.speed + controller.acceleration * dt).min(target_speed)
+        } else {
+            (controller.speed - controller.acceleration * dt).max(target_speed)
+        };
+
+        // models face +Z
+        let direction = Vec3::new(input.x, 0.0, input.y);
+        if direction != Vec3::ZERO {
+            let facing = Transform::IDENTITY.looking_to(-direction, Vec3::Y).rotation;
+            let turn = (controller.turn_speed * dt).min(1.0);
+            transform.rotation = transform.rotation.slerp(facing, turn);
+        }
+        let forward = transform.rotation * Vec3::Z;
+        transform.translation += forward * controller.speed * dt;
+
+        positions.set(controller.blend_space.clone(), controller.speed);
+
+        // moving switches back to the blend space,
+        // standing still leaves whatever else was played alone
+        if input == Vec2::ZERO {
+            continue;
+        }
+        let Some(Ok((mut player, mut transitions))) = animator.map(|animator| {
+            animation_players.get_mut(animator.player_for(character, &controller.blend_space))
+        }) else {
+            continue;
+        };
+        if transitions.get_main_animation() != Some(blend_space_index) {
+            play_animation(
+                &mut player,
+                &mut transitions,
+                character,
+                character_data,
+                &controller.blend_space,
+            );
+        }
+    }
+}
+
+// plays a character's gamepad_animations once, then goes back to its default animation
+pub(crate) fn trigger_gamepad_animations(
+    gamepads: Query<&Gamepad>,
+    character_datum: Res<Assets<CharacterData>>,
+    characters: Res<Characters>,
+    mut sequences: Query<(&CharacterModel, &mut AnimationSequence)>,
+) {
+    for (character_model, mut sequence) in &mut sequences {
+        let Some(character_data) = character_datum.get(&character_model.0) else {
+            continue;
+        };
+        let Some(character) = characters.get(&character_data.id) else {
+            continue;
+        };
+
+        let Some(animation_name) = FaceButton::ALL
+            .iter()
+            .filter(|button| {
+                gamepads
+                    .iter()
+                    .any(|gamepad| gamepad.just_pressed(button.gamepad_button()))
+            })
+            .find_map(|button| character_data.gamepad_animations.get(button))
+        else {
+            continue;
+        };
+        if character.animation_index(animation_name).is_none() {
+            warn!("gamepad animation '{}' not found", animation_name);
+            continue;
+        }
+
+        let mut steps = vec![AnimationSequenceStep::new(animation_name).with_repeat(false)];
+        if let Some(default_animation) = character.default_animation() {
+            steps.push(AnimationSequenceStep::new(default_animation));
+        }
+        sequence.play_sequence(steps);
+    }
+}
@@ -0,0 +1,381 @@
+use bevy::{
+    prelude::*,
+    remote::{BrpResult, builtin_methods::parse_some},
+};
+use bevy_mixamo::*;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::dialog::*;
+use crate::dropdown::{HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
+use crate::focus::*;
+use crate::selection::*;
+
+pub struct CrowdPlugin;
+
+impl Plugin for CrowdPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingBakedCrowds>()
+            .add_systems(Startup, setup_crowd_panel)
+            .add_systems(Update, handle_crowd_buttons)
+            .add_observer(on_spawn_crowd)
+            .add_observer(on_vertex_animation_baked)
+            .add_observer(on_clear_crowd);
+    }
+}
+
+// remote method to spawn a crowd, takes SpawnCrowd as its params
+pub const SPAWN_CROWD_METHOD: &str = "mixamo/spawn_crowd";
+
+// spawns copies of the selected character in a grid behind it
+#[derive(Debug, Clone, Event, Deserialize)]
+pub struct SpawnCrowd {
+    pub columns: u32,
+    pub rows: u32,
+
+    #[serde(default = "default_crowd_spacing")]
+    pub spacing: f32,
+
+    // start each copy at a random point in its default animation
+    #[serde(default = "default_true")]
+    pub random_start: bool,
+
+    // spawns at most this many, leaving the last row partially filled
+    #[serde(default)]
+    pub count: Option<u32>,
+
+    // play a baked vertex animation on the GPU instead of spawning full characters
+    // the default animation is baked the first time
+    #[serde(default)]
+    pub baked: bool,
+}
+
+fn default_crowd_spacing() -> f32 {
+    1.5
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl SpawnCrowd {
+    pub fn new(columns: u32, rows: u32) -> Self {
+        Self {
+            columns,
+            rows,
+            spacing: default_crowd_spacing(),
+            random_start: true,
+            count: None,
+            baked: false,
+        }
+    }
+
+    pub fn baked(mut self) -> Self {
+        self.baked = true;
+        self
+    }
+
+    // a roughly square grid of exactly count characters
+    pub fn with_count(count: u32) -> Self {
+        let columns = (count as f32).sqrt().ceil() as u32;
+        let rows = count.div_ceil(columns.max(1));
+        Self {
+            count: Some(count),
+            ..Self::new(columns, rows)
+        }
+    }
+}
+
+// despawns every character except the selected one, and every baked crowd
+#[derive(Debug, Clone, Event)]
+pub struct ClearCrowd;
+
+// baked crowds waiting on their animation to finish baking
+#[derive(Debug, Default, Resource)]
+struct PendingBakedCrowds(Vec<SpawnCrowd>);
+
+#[derive(Component, Clone, Copy)]
+enum CrowdButton {
+    Spawn(u32, u32),
+    SpawnBaked(u32, u32),
+    Clear,
+}
+
+impl CrowdButton {
+    fn label(&self) -> String {
+        match self {
+            Self::Spawn(columns, rows) => format!("Spawn {columns}x{rows}"),
+            Self::SpawnBaked(columns, rows) => format!("Baked {columns}x{rows}"),
+            Self::Clear => "Clear crowd".to_string(),
+        }
+    }
+}
+
+pub fn process_spawn_crowd_request(
+    In(params): In<Option<Value>>,
+    mut commands: Commands,
+) -> BrpResult {
+    let spawn_crowd = parse_some::<SpawnCrowd>(params)?;
+    commands.trigger(spawn_crowd);
+    Ok(Value::Null)
+}
+
+fn setup_crowd_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                column_gap: Val::Px(10.0),
+                ..default()
+            },
+            Name::new("CrowdPanel"),
+        ))
+        .with_children(|parent| {
+            for button in [
+                CrowdButton::Spawn(5, 5),
+                CrowdButton::Spawn(10, 10),
+                CrowdButton::Spawn(20, 20),
+                CrowdButton::SpawnBaked(50, 50),
+                CrowdButton::Clear,
+            ] {
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(150.0),
+                            height: Val::Px(50.0),
+                            border: UiRect::all(Val::Px(2.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BorderColor::all(Color::BLACK),
+                        BackgroundColor(NORMAL_BUTTON),
+                        Focusable,
+                        button,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Text::new(button.label()),
+                            TextFont {
+                                font_size: 20.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                        ));
+                    });
+            }
+        });
+}
+
+fn handle_crowd_buttons(
+    mut commands: Commands,
+    mut buttons: Query<(&Interaction, &CrowdButton, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+
+                match *button {
+                    CrowdButton::Spawn(columns, rows) => {
+                        commands.trigger(SpawnCrowd::new(columns, rows));
+                    }
+                    CrowdButton::SpawnBaked(columns, rows) => {
+                        commands.trigger(SpawnCrowd::new(columns, rows).baked());
+                    }
+                    CrowdButton::Clear => {
+                        spawn_confirm_dialog(
+                            &mut commands,
+                            "Despawn every character except the selected one?",
+                            "Clear",
+                        )
+                        .insert(Name::new("ClearCrowdDialog"))
+                        .observe(
+                            |_: On<DialogConfirmed>, mut commands: Commands| {
+                                commands.trigger(ClearCrowd);
+                            },
+                        );
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+// the duration of the character's default animation clip
+fn default_animation_duration(
+    character: &Character,
+    animation_graphs: &Assets<AnimationGraph>,
+    animation_clips: &Assets<AnimationClip>,
+) -> Option<f32> {
+    let animation_index = character.animation_index(character.default_animation()?)?;
+    let AnimationNodeType::Clip(clip) = &animation_graphs
+        .get(character.graph())?
+        .get(animation_index)?
+        .node_type
+    else {
+        return None;
+    };
+    Some(animation_clips.get(clip)?.duration())
+}
+
+// a cheap hash (pcg) so that crowds don't need a random number generator
+fn random_fraction(seed: u32) -> f32 {
+    let state = seed.wrapping_mul(747796405).wrapping_add(2891336453);
+    let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+    let hash = (word >> 22) ^ word;
+    hash as f32 / u32::MAX as f32
+}
+
+// centered behind the origin, by index
+fn crowd_positions(event: &SpawnCrowd, origin: Vec3) -> impl Iterator<Item = (u32, Vec3)> + '_ {
+    let width = (event.columns.max(1) - 1) as f32 * event.spacing;
+    (0..event.rows)
+        .flat_map(move |row| (0..event.columns).map(move |column| (row, column)))
+        .map(move |(row, column)| {
+            let translation = origin
+                + Vec3::new(
+                    column as f32 * event.spacing - width * 0.5,
+                    0.0,
+                    -((row + 1) as f32) * event.spacing,
+                );
+            (row * event.columns + column, translation)
+        })
+        .take_while(|(index, _)| event.count.is_none_or(|count| *index < count))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn on_spawn_crowd(
+    event: On<SpawnCrowd>,
+    mut commands: Commands,
+    time: Res<Time>,
+    selected_character: Res<SelectedCharacter>,
+    character_datum: Res<Assets<CharacterData>>,
+    characters: Res<Characters>,
+    (vertex_animations, vertex_animation_assets, mut pending_baked_crowds): (
+        Res<VertexAnimations>,
+        Res<Assets<VertexAnimation>>,
+        ResMut<PendingBakedCrowds>,
+    ),
+    animation_graphs: Res<Assets<AnimationGraph>>,
+    animation_clips: Res<Assets<AnimationClip>>,
+    character_models: Query<(&CharacterModel, &Transform)>,
+) {
+    let Some((character_model, transform)) = selected_character
+        .0
+        .and_then(|entity| character_models.get(entity).ok())
+    else {
+        warn!("Select a character to spawn a crowd of");
+        return;
+    };
+    let Some(character_data) = character_datum.get(&character_model.0) else {
+        return;
+    };
+    let Some(character) = characters.get(&character_data.id) else {
+        return;
+    };
+
+    let seed = time.elapsed().as_millis() as u32;
+    let origin = transform.translation.with_y(0.0);
+
+    if event.baked {
+        let Some(default_animation) = character.default_animation() else {
+            warn!(
+                "Character '{}' has no default animation to bake",
+                character_data.id
+            );
+            return;
+        };
+        let Some(vertex_animation) = vertex_animations.get(&character_data.id, default_animation)
+        else {
+            // only bake once if the button is pressed again while baking
+            if pending_baked_crowds.0.is_empty() {
+                commands.trigger(BakeVertexAnimation::new(
+                    character_data.id.clone(),
+                    default_animation,
+                ));
+            }
+            pending_baked_crowds.0.push(event.clone());
+            return;
+        };
+
+        info!(
+            "Spawning a {}x{} baked crowd of character '{}' ...",
+            event.columns, event.rows, character_data.id
+        );
+
+        let duration = vertex_animation_assets
+            .get(vertex_animation)
+            .map(VertexAnimation::duration)
+            .filter(|_| event.random_start)
+            .unwrap_or_default();
+        commands.spawn_batch(
+            crowd_positions(&event, origin)
+                .map(|(index, translation)| {
+                    (
+                        VertexAnimationInstance {
+                            vertex_animation: vertex_animation.clone(),
+                            start_time: random_fraction(seed.wrapping_add(index)) * duration,
+                        },
+                        Transform::from_translation(translation) * character.model_transform(),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        );
+        return;
+    }
+
+    info!(
+        "Spawning a {}x{} crowd of character '{}' ...",
+        event.columns, event.rows, character_data.id
+    );
+
+    let duration = default_animation_duration(character, &animation_graphs, &animation_clips)
+        .filter(|_| event.random_start)
+        .unwrap_or_default();
+    for (index, translation) in crowd_positions(&event, origin) {
+        commands.trigger(
+            SpawnCharacter::new(character_data.id.clone())
+                .with_transform(Transform::from_translation(translation))
+                .with_start_time(random_fraction(seed.wrapping_add(index)) * duration),
+        );
+    }
+}
+
+// spawn the crowds that were waiting on the bake
+fn on_vertex_animation_baked(
+    _event: On<VertexAnimationBaked>,
+    mut commands: Commands,
+    mut pending_baked_crowds: ResMut<PendingBakedCrowds>,
+) {
+    for spawn_crowd in pending_baked_crowds.0.drain(..) {
+        commands.trigger(spawn_crowd);
+    }
+}
+
+fn on_clear_crowd(
+    _event: On<ClearCrowd>,
+    mut commands: Commands,
+    selected_character: Res<SelectedCharacter>,
+    character_models: Query<Entity, With<CharacterModel>>,
+    vertex_animation_instances: Query<Entity, With<VertexAnimationInstance>>,
+) {
+    for entity in &character_models {
+        if Some(entity) != selected_character.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    for entity in &vertex_animation_instances {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,94 @@
+use bevy::{
+    animation::{AnimatedBy, graph::AnimationGraph},
+    app::AnimationSystems,
+    diagnostic::{Diagnostic, DiagnosticPath, Diagnostics, RegisterDiagnostic},
+    platform::time::Instant,
+    prelude::*,
+};
+
+use crate::character::*;
+
+// publishes character counts, animation cost and loaded asset counts to the DiagnosticsStore
+pub struct CharacterDiagnosticsPlugin;
+
+impl CharacterDiagnosticsPlugin {
+    pub const CHARACTERS: DiagnosticPath = DiagnosticPath::const_new("mixamo/characters");
+    pub const ANIMATED_BONES: DiagnosticPath = DiagnosticPath::const_new("mixamo/animated_bones");
+
+    // in ms, from the start of the animation systems to the end of the post animation ones
+    pub const ANIMATION_TIME: DiagnosticPath = DiagnosticPath::const_new("mixamo/animation_time");
+
+    pub const SCENES: DiagnosticPath = DiagnosticPath::const_new("mixamo/assets/scenes");
+    pub const MESHES: DiagnosticPath = DiagnosticPath::const_new("mixamo/assets/meshes");
+    pub const IMAGES: DiagnosticPath = DiagnosticPath::const_new("mixamo/assets/images");
+    pub const ANIMATION_CLIPS: DiagnosticPath =
+        DiagnosticPath::const_new("mixamo/assets/animation_clips");
+    pub const ANIMATION_GRAPHS: DiagnosticPath =
+        DiagnosticPath::const_new("mixamo/assets/animation_graphs");
+}
+
+impl Plugin for CharacterDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(Self::CHARACTERS))
+            .register_diagnostic(Diagnostic::new(Self::ANIMATED_BONES))
+            .register_diagnostic(Diagnostic::new(Self::ANIMATION_TIME).with_suffix("ms"))
+            .register_diagnostic(Diagnostic::new(Self::SCENES))
+            .register_diagnostic(Diagnostic::new(Self::MESHES))
+            .register_diagnostic(Diagnostic::new(Self::IMAGES))
+            .register_diagnostic(Diagnostic::new(Self::ANIMATION_CLIPS))
+            .register_diagnostic(Diagnostic::new(Self::ANIMATION_GRAPHS))
+            .init_resource::<AnimationTimer>()
+            .add_systems(Update, measure_character_diagnostics)
+            .add_systems(
+                PostUpdate,
+                (
+                    start_animation_timer.before(AnimationSystems),
+                    measure_animation_time.after(PostAnimationSystems),
+                ),
+            );
+    }
+}
+
+// wall clock time, so anything running in parallel with the animation systems is counted too
+#[derive(Default, Resource)]
+struct AnimationTimer(Option<Instant>);
+
+fn start_animation_timer(mut timer: ResMut<AnimationTimer>) {
+    timer.0 = Some(Instant::now());
+}
+
+fn measure_animation_time(mut diagnostics: Diagnostics, mut timer: ResMut<AnimationTimer>) {
+    if let Some(start) = timer.0.take() {
+        diagnostics.add_measurement(&CharacterDiagnosticsPlugin::ANIMATION_TIME, || {
+            start.elapsed().as_secs_f64() * 1000.0
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn measure_character_diagnostics(
+    mut diagnostics: Diagnostics,
+    character_models: Query<(), With<CharacterModel>>,
+    animated_bones: Query<(), With<AnimatedBy>>,
+    scenes: Res<Assets<Scene>>,
+    meshes: Res<Assets<Mesh>>,
+    images: Res<Assets<Image>>,
+    animation_clips: Res<Assets<AnimationClip>>,
+    animation_graphs: Res<Assets<AnimationGraph>>,
+) {
+    diagnostics.add_measurement(&CharacterDiagnosticsPlugin::CHARACTERS, || {
+        character_models.iter().count() as f64
+    });
+    diagnostics.add_measurement(&CharacterDiagnosticsPlugin::ANIMATED_BONES, || {
+        animated_bones.iter().count() as f64
+    });
+    diagnostics.add_measurement(&CharacterDiagnosticsPlugin::SCENES, || scenes.len() as f64);
+    diagnostics.add_measurement(&CharacterDiagnosticsPlugin::MESHES, || meshes.len() as f64);
+    diagnostics.add_measurement(&CharacterDiagnosticsPlugin::IMAGES, || images.len() as f64);
+    diagnostics.add_measurement(&CharacterDiagnosticsPlugin::ANIMATION_CLIPS, || {
+        animation_clips.len() as f64
+    });
+    diagnostics.add_measurement(&CharacterDiagnosticsPlugin::ANIMATION_GRAPHS, || {
+        animation_graphs.len() as f64
+    });
+}
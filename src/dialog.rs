@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+
+use crate::dropdown::{HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
+use crate::focus::*;
+
+pub struct ConfirmDialogPlugin;
+
+impl Plugin for ConfirmDialogPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ConfirmDialog>()
+            .add_systems(Update, (handle_dialog_buttons, handle_dialog_keys));
+    }
+}
+
+// a message with confirm and cancel buttons over a backdrop that blocks the rest of the UI,
+// despawned once either is chosen, observe DialogConfirmed on it to act on the answer
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ConfirmDialog;
+
+#[derive(Component, Clone, Copy)]
+struct DialogButton {
+    dialog: Entity,
+    confirm: bool,
+}
+
+// both target the ConfirmDialog entity
+#[derive(EntityEvent)]
+pub struct DialogConfirmed {
+    pub entity: Entity,
+}
+
+#[derive(EntityEvent)]
+pub struct DialogCancelled {
+    pub entity: Entity,
+}
+
+const BACKDROP_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+const DIALOG_BACKGROUND: Color = Color::srgb(0.1, 0.1, 0.1);
+
+// just under the focus ring and tooltips
+const DIALOG_Z_INDEX: i32 = i32::MAX - 2;
+
+pub fn spawn_confirm_dialog<'a>(
+    commands: &'a mut Commands,
+    message: impl Into<String>,
+    confirm_label: impl Into<String>,
+) -> EntityCommands<'a> {
+    let mut entity_commands = commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        BackgroundColor(BACKDROP_COLOR),
+        GlobalZIndex(DIALOG_Z_INDEX),
+        // the backdrop takes the clicks meant for what's under it
+        Interaction::default(),
+        // tab / the d-pad only move between its buttons
+        FocusTrap,
+        ConfirmDialog,
+    ));
+    let dialog = entity_commands.id();
+
+    entity_commands.with_children(|parent| {
+        parent
+            .spawn((
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(20.0),
+                    padding: UiRect::all(Val::Px(20.0)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                BorderColor::all(Color::BLACK),
+                BackgroundColor(DIALOG_BACKGROUND),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(message.into()),
+                    TextFont {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                ));
+
+                parent
+                    .spawn(Node {
+                        column_gap: Val::Px(10.0),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        for (label, confirm) in
+                            [(confirm_label.into(), true), ("Cancel".into(), false)]
+                        {
+                            parent
+                                .spawn((
+                                    Button,
+                                    Node {
+                                        width: Val::Px(150.0),
+                                        height: Val::Px(50.0),
+                                        border: UiRect::all(Val::Px(2.0)),
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        ..default()
+                                    },
+                                    BorderColor::all(Color::BLACK),
+                                    BackgroundColor(NORMAL_BUTTON),
+                                    Focusable,
+                                    DialogButton { dialog, confirm },
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn((
+                                        Text::new(label),
+                                        TextFont {
+                                            font_size: 20.0,
+                                            ..default()
+                                        },
+                                        TextColor(Color::WHITE),
+                                    ));
+                                });
+                        }
+                    });
+            });
+    });
+
+    entity_commands
+}
+
+// the event is triggered before the dialog is despawned, so its own observers still see it
+fn close_dialog(commands: &mut Commands, dialog: Entity, confirm: bool) {
+    if confirm {
+        commands.trigger(DialogConfirmed { entity: dialog });
+    } else {
+        commands.trigger(DialogCancelled { entity: dialog });
+    }
+    commands.entity(dialog).despawn();
+}
+
+fn handle_dialog_buttons(
+    mut commands: Commands,
+    mut buttons: Query<(&Interaction, &DialogButton, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+                close_dialog(&mut commands, button.dialog, button.confirm);
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+// enter confirms and escape cancels the open dialog
+fn handle_dialog_keys(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    dialogs: Query<Entity, With<ConfirmDialog>>,
+) {
+    let confirm = if keyboard.just_pressed(KeyCode::Enter) {
+        true
+    } else if keyboard.just_pressed(KeyCode::Escape) {
+        false
+    } else {
+        return;
+    };
+
+    // there's only ever one open at a time in practice
+    if let Some(dialog) = dialogs.iter().next() {
+        close_dialog(&mut commands, dialog, confirm);
+    }
+}
@@ -0,0 +1,108 @@
+use bevy::{asset::LoadedFolder, prelude::*};
+
+use crate::animation_library::*;
+use crate::asset_event_bridge::*;
+use crate::character::*;
+
+// character files in a scanned folder must use one of these extensions
+pub const CHARACTER_EXTENSIONS: &[&str] = &[".character.json", ".character.ron"];
+
+// a folder scanned for character files, as an alternative to keeping a manifest in sync
+// (folder loading isn't supported on the web)
+#[derive(Resource)]
+pub struct CharacterFolderHandle {
+    handle: Handle<LoadedFolder>,
+
+    // set once the folder's characters have started loading
+    applied: bool,
+}
+
+impl CharacterFolderHandle {
+    pub fn load(path: impl Into<String>, asset_server: &AssetServer) -> Self {
+        let path = path.into();
+
+        info!("Scanning '{}' for characters ...", path);
+        Self {
+            handle: asset_server.load_folder(path),
+            applied: false,
+        }
+    }
+
+    pub fn handle(&self) -> &Handle<LoadedFolder> {
+        &self.handle
+    }
+
+    // failed scans count as finished so that a bad path doesn't block forever
+    pub fn is_finished_loading(&self, asset_server: &AssetServer) -> bool {
+        self.applied || asset_server.load_state(&self.handle).is_failed()
+    }
+}
+
+// the id a character file is loaded as when nothing names it, its file name without the extension
+pub fn character_id_from_path(path: &str) -> String {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    CHARACTER_EXTENSIONS
+        .iter()
+        .find_map(|extension| file_name.strip_suffix(extension))
+        .unwrap_or(file_name)
+        .to_string()
+}
+
+fn is_character_path(path: &str) -> bool {
+    CHARACTER_EXTENSIONS
+        .iter()
+        .any(|extension| path.ends_with(extension))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn on_character_folder_loaded(
+    event: On<AssetLoadedEvent<LoadedFolder>>,
+    folders: Res<Assets<LoadedFolder>>,
+    folder_handle: Option<ResMut<CharacterFolderHandle>>,
+    character_datum: Res<Assets<CharacterData>>,
+    animation_sets: Res<Assets<AnimationSet>>,
+    mut animation_library: ResMut<AnimationLibrary>,
+    mut characters: ResMut<Characters>,
+    mut animation_graphs: ResMut<Assets<AnimationGraph>>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(mut folder_handle) = folder_handle else {
+        return;
+    };
+    if folder_handle.handle.id() != event.asset_id {
+        return;
+    }
+
+    let folder = folders.get(event.asset_id).unwrap();
+    for handle in &folder.handles {
+        let Some(path) = handle.path() else {
+            continue;
+        };
+        let path = path.to_string();
+        if !is_character_path(&path) {
+            continue;
+        }
+
+        let Ok(data) = handle.clone().try_typed::<CharacterData>() else {
+            continue;
+        };
+        let Some(character_data) = character_datum.get(&data) else {
+            continue;
+        };
+
+        info!("Found character '{}' at '{}'", character_data.id, path);
+
+        load_character(
+            &mut characters,
+            character_data.id.clone(),
+            path,
+            &character_datum,
+            &animation_sets,
+            &mut animation_library,
+            &asset_server,
+            &mut animation_graphs,
+        );
+    }
+
+    folder_handle.applied = true;
+}
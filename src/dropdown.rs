@@ -1,42 +1,353 @@
-use bevy::prelude::*;
+use std::marker::PhantomData;
+
+use bevy::{
+    input::{
+        ButtonState, InputSystems,
+        keyboard::{Key, KeyboardInput},
+    },
+    prelude::*,
+    ui::UiSystems,
+};
+
+use crate::focus::*;
+use crate::scroll::*;
 
 pub struct DropdownPlugin;
 
 impl Plugin for DropdownPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, handle_dropdown_interactions);
+        app.register_type::<Dropdown>()
+            .register_type::<DropdownButton>()
+            .register_type::<DropdownList>()
+            .register_type::<DropdownState>()
+            .register_type::<DropdownFilter>()
+            .register_type::<DropdownTheme>()
+            .add_observer(spawn_dropdown_filter_box)
+            .add_systems(
+                Update,
+                (handle_dropdown_buttons, close_dropdowns_on_outside_click),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    apply_dropdown_theme,
+                    apply_dropdown_state,
+                    apply_dropdown_filter,
+                )
+                    .chain()
+                    .before(UiSystems::Prepare),
+            );
     }
 }
 
-#[derive(Component)]
+// builds the items of dropdowns with T values and handles clicks on them,
+// added by add_dropdown_values::<T>()
+pub struct DropdownValuesPlugin<T>(PhantomData<T>)
+where
+    T: Clone + Send + Sync + 'static;
+
+impl<T> Default for DropdownValuesPlugin<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T> Plugin for DropdownValuesPlugin<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            handle_dropdown_keys::<T>
+                .after(InputSystems)
+                .before(FocusSystems),
+        )
+        .add_systems(
+            Update,
+            (rebuild_dropdown_items::<T>, handle_dropdown_items::<T>),
+        );
+    }
+}
+
+pub trait DropdownAppExt {
+    fn add_dropdown_values<T>(&mut self) -> &mut Self
+    where
+        T: Clone + Send + Sync + 'static;
+}
+
+impl DropdownAppExt for App {
+    fn add_dropdown_values<T>(&mut self) -> &mut Self
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        if !self.is_plugin_added::<DropdownValuesPlugin<T>>() {
+            self.add_plugins(DropdownValuesPlugin::<T>::default());
+        }
+        self
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Dropdown;
 
+// the selected item's index and whether the list is shown,
+// set it to change the dropdown without going through its buttons (that doesn't trigger DropdownChanged)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Component, Reflect)]
+#[reflect(Component)]
+pub struct DropdownState {
+    pub selected: Option<usize>,
+    pub open: bool,
+
+    // the item enter selects, moved by the arrow keys, typing and hovering
+    pub highlighted: Option<usize>,
+}
+
+// how long type-ahead waits for the next letter before starting over
+const TYPE_AHEAD_TIMEOUT: f32 = 1.0;
+
+// what's been typed into the open list so far
+#[derive(Component, Default)]
+struct DropdownTypeAhead {
+    prefix: String,
+    last_typed: f32,
+}
+
+// the button text while nothing is selected
 #[derive(Component)]
-pub struct DropdownButton;
+struct DropdownPlaceholder(String);
 
+// the button size, the list is as wide
 #[derive(Component)]
+struct DropdownSize(Vec2);
+
+// insert it to add a search box above the list, typing then filters the items
+// (case insensitively) instead of jumping to them
+#[derive(Debug, Default, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct DropdownFilter(pub String);
+
+impl DropdownFilter {
+    pub fn matches(&self, label: &str) -> bool {
+        label.to_lowercase().contains(&self.0.to_lowercase())
+    }
+}
+
+#[derive(Component)]
+struct DropdownFilterBox;
+
+const FILTER_PLACEHOLDER: &str = "Type to filter";
+
+// the list scrolls past this many items
+const MAX_VISIBLE_ITEMS: f32 = 8.0;
+
+fn list_max_height(theme: &DropdownTheme) -> Val {
+    Val::Px(theme.item_height * MAX_VISIBLE_ITEMS + theme.border_width * 2.0)
+}
+
+// how a dropdown looks, insert one over the default to restyle it
+#[derive(Debug, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct DropdownTheme {
+    pub button: Color,
+    pub button_hovered: Color,
+    pub button_pressed: Color,
+    pub list_background: Color,
+    pub item_selected: Color,
+
+    pub border: Color,
+    pub border_width: f32,
+
+    pub text: Color,
+    // the search box's text until something's typed
+    pub placeholder_text: Color,
+    pub font_size: f32,
+    pub item_font_size: f32,
+
+    // of the items and the search box, the button's height is given when it's spawned
+    pub item_height: f32,
+}
+
+impl Default for DropdownTheme {
+    fn default() -> Self {
+        Self {
+            button: NORMAL_BUTTON,
+            button_hovered: HOVERED_BUTTON,
+            button_pressed: PRESSED_BUTTON,
+            list_background: Color::srgb(0.1, 0.1, 0.1),
+            item_selected: SELECTED_ITEM,
+            border: Color::BLACK,
+            border_width: 2.0,
+            text: Color::WHITE,
+            placeholder_text: Color::srgb(0.5, 0.5, 0.5),
+            font_size: 20.0,
+            item_font_size: 18.0,
+            item_height: 40.0,
+        }
+    }
+}
+
+// an item's label and value, and an optional icon shown left of the label
+#[derive(Debug, Clone)]
+pub struct DropdownOption<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    pub label: String,
+    pub value: T,
+    pub icon: Option<Handle<Image>>,
+}
+
+impl<T> DropdownOption<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    pub fn new(label: impl Into<String>, value: T) -> Self {
+        Self {
+            label: label.into(),
+            value,
+            icon: None,
+        }
+    }
+}
+
+impl<T> From<(String, T)> for DropdownOption<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    fn from((label, value): (String, T)) -> Self {
+        Self::new(label, value)
+    }
+}
+
+// the list's items, in order,
+// replace them to rebuild the list (the selection is kept if its index is still in range)
+#[derive(Component)]
+pub struct DropdownOptions<T>(pub Vec<DropdownOption<T>>)
+where
+    T: Clone + Send + Sync + 'static;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct DropdownButton;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct DropdownList;
 
+// the value of an option in the list
 #[derive(Component)]
-pub struct DropdownItem(String);
+pub struct DropdownItem<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    pub value: T,
+}
 
-#[derive(Event)]
-pub struct DropdownChanged {
-    //pub entity: Entity,
-    pub selected_item: String,
+// targets the Dropdown entity, with the selected item's value
+#[derive(EntityEvent)]
+pub struct DropdownChanged<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    pub entity: Entity,
+    pub value: T,
 }
 
-const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
-const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
-const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+pub const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
+pub const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
+pub const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
+pub const SELECTED_ITEM: Color = Color::srgb(0.2, 0.3, 0.2);
+
+fn item_color(theme: &DropdownTheme, state: &DropdownState, index: usize) -> Color {
+    if state.highlighted == Some(index) {
+        theme.button_hovered
+    } else if state.selected == Some(index) {
+        theme.item_selected
+    } else {
+        Color::NONE
+    }
+}
+
+type DropdownButtonInteractionQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Interaction,
+        &'static mut BackgroundColor,
+        &'static ChildOf,
+    ),
+    (Changed<Interaction>, With<DropdownButton>),
+>;
+
+type DropdownButtonStateQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static DropdownTheme,
+        &'static mut DropdownState,
+        Option<&'static mut DropdownFilter>,
+    ),
+>;
+
+type DropdownItemInteractionQuery<'w, 's, T> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Interaction,
+        &'static mut BackgroundColor,
+        &'static DropdownItem<T>,
+        &'static ChildOf,
+    ),
+    (Changed<Interaction>, Without<DropdownButton>),
+>;
 
-pub fn spawn_dropdown<'a>(
+type DropdownOptionsChangedQuery<'w, 's, T> = Query<
+    'w,
+    's,
+    (
+        &'static DropdownOptions<T>,
+        &'static DropdownTheme,
+        &'static Children,
+        &'static mut DropdownState,
+        Option<&'static mut DropdownFilter>,
+    ),
+    Changed<DropdownOptions<T>>,
+>;
+
+type DropdownKeysQuery<'w, 's, T> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static DropdownOptions<T>,
+        &'static mut DropdownState,
+        &'static mut DropdownTypeAhead,
+        Option<&'static mut DropdownFilter>,
+        &'static DropdownTheme,
+        &'static Children,
+    ),
+>;
+
+// options are DropdownOptions or (label, value) pairs,
+// the app needs add_dropdown_values::<T>() to build their items
+pub fn spawn_dropdown<'a, T>(
     commands: &'a mut Commands,
     position: Vec2,
     size: Vec2,
     label: impl Into<String>,
-    options: impl AsRef<[String]>,
-) -> EntityCommands<'a> {
+    options: impl IntoIterator<Item = impl Into<DropdownOption<T>>>,
+) -> EntityCommands<'a>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let label = label.into();
+    let theme = DropdownTheme::default();
     let mut entity_commands = commands.spawn((
         Node {
             position_type: PositionType::Absolute,
@@ -47,6 +358,12 @@ pub fn spawn_dropdown<'a>(
             ..default()
         },
         Dropdown,
+        DropdownState::default(),
+        DropdownPlaceholder(label.clone()),
+        DropdownSize(size),
+        DropdownTypeAhead::default(),
+        DropdownOptions(options.into_iter().map(Into::into).collect()),
+        theme.clone(),
     ));
 
     entity_commands.with_children(|parent| {
@@ -57,145 +374,656 @@ pub fn spawn_dropdown<'a>(
                 Node {
                     width: Val::Px(size.x),
                     height: Val::Px(size.y),
-                    border: UiRect::all(Val::Px(2.0)),
+                    border: UiRect::all(Val::Px(theme.border_width)),
                     justify_content: JustifyContent::Center,
                     align_items: AlignItems::Center,
                     ..default()
                 },
-                BorderColor::all(Color::BLACK),
-                BackgroundColor(NORMAL_BUTTON),
+                BorderColor::all(theme.border),
+                BackgroundColor(theme.button),
+                Focusable,
                 DropdownButton,
             ))
             .with_children(|parent| {
                 parent.spawn((
-                    Text::new(label.into()),
+                    Text::new(label),
                     TextFont {
-                        font_size: 20.0,
+                        font_size: theme.font_size,
                         ..default()
                     },
-                    TextColor(Color::WHITE),
+                    TextColor(theme.text),
                 ));
             });
 
         // list
-        parent
-            .spawn((
-                Node {
-                    display: Display::None,
-                    flex_direction: FlexDirection::Column,
-                    width: Val::Px(size.x),
-                    border: UiRect::all(Val::Px(2.0)),
-                    margin: UiRect::top(Val::Px(5.0)),
-                    ..default()
-                },
-                BorderColor::all(Color::BLACK),
-                BackgroundColor(Color::srgb(0.1, 0.1, 0.1)),
-                DropdownList,
-            ))
-            .with_children(|parent| {
-                for option in options.as_ref() {
-                    parent
-                        .spawn((
-                            Button,
-                            Node {
-                                height: Val::Px(size.y - 10.0),
-                                justify_content: JustifyContent::Center,
-                                align_items: AlignItems::Center,
-                                ..default()
-                            },
-                            BackgroundColor(Color::NONE),
-                            DropdownItem(option.to_string()),
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn((
-                                Text::new(option),
-                                TextFont {
-                                    font_size: 18.0,
-                                    ..default()
-                                },
-                                TextColor(Color::WHITE),
-                            ));
-                        });
-                }
-            });
+        parent.spawn((
+            Node {
+                display: Display::None,
+                flex_direction: FlexDirection::Column,
+                width: Val::Px(size.x),
+                max_height: list_max_height(&theme),
+                border: UiRect::all(Val::Px(theme.border_width)),
+                margin: UiRect::top(Val::Px(5.0)),
+                ..default()
+            },
+            BorderColor::all(theme.border),
+            BackgroundColor(theme.list_background),
+            // so clicks on the list's border count as inside it
+            Interaction::default(),
+            ScrollView::with_scrollbar(),
+            DropdownList,
+        ));
     });
 
     entity_commands
 }
 
-fn handle_dropdown_interactions(
-    mut commands: Commands,
-    dropdown_children_query: Query<&Children, With<Dropdown>>,
-    mut dropdown_list_query: Query<&mut Node, With<DropdownList>>,
-    mut button_query: Query<
-        (&Interaction, &mut BackgroundColor),
-        (Changed<Interaction>, With<DropdownButton>),
-    >,
-    button_children_query: Query<&Children, With<DropdownButton>>,
-    mut item_query: Query<
-        (&Interaction, &mut BackgroundColor, &DropdownItem),
-        (Changed<Interaction>, Without<DropdownButton>),
-    >,
-    mut text_query: Query<&mut Text>,
+fn spawn_dropdown_item<T>(
+    parent: &mut ChildSpawnerCommands,
+    theme: &DropdownTheme,
+    option: &DropdownOption<T>,
+) where
+    T: Clone + Send + Sync + 'static,
+{
+    parent
+        .spawn((
+            Button,
+            Node {
+                height: Val::Px(theme.item_height),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(8.0),
+                ..default()
+            },
+            BackgroundColor(Color::NONE),
+            DropdownItem {
+                value: option.value.clone(),
+            },
+        ))
+        .with_children(|parent| {
+            if let Some(icon) = &option.icon {
+                // square, scaling with the theme's item height
+                parent.spawn((
+                    Node {
+                        height: Val::Percent(80.0),
+                        aspect_ratio: Some(1.0),
+                        ..default()
+                    },
+                    ImageNode::new(icon.clone()),
+                ));
+            }
+
+            parent.spawn((
+                Text::new(option.label.clone()),
+                TextFont {
+                    font_size: theme.item_font_size,
+                    ..default()
+                },
+                TextColor(theme.text),
+            ));
+        });
+}
+
+fn handle_dropdown_buttons(
+    mut dropdown_state_query: DropdownButtonStateQuery,
+    mut button_query: DropdownButtonInteractionQuery,
 ) {
     // main button click
-    for (interaction, mut color) in &mut button_query {
+    for (interaction, mut color, parent) in &mut button_query {
+        let Ok((theme, mut state, filter)) = dropdown_state_query.get_mut(parent.parent()) else {
+            continue;
+        };
         match *interaction {
             Interaction::Pressed => {
-                *color = PRESSED_BUTTON.into();
-                // Toggle list visibility (Toggle ALL dropdowns for simplicity in this example)
-                for dropdown_children in &dropdown_children_query {
-                    for child in dropdown_children {
-                        if let Ok(mut list_node) = dropdown_list_query.get_mut(*child) {
-                            list_node.display = match list_node.display {
-                                Display::None => Display::Flex,
-                                _ => Display::None,
-                            };
-                        }
+                *color = theme.button_pressed.into();
+                // Toggle this dropdown's list
+                {
+                    state.open = !state.open;
+                    state.highlighted = state.selected;
+
+                    // start each search from scratch
+                    if state.open
+                        && let Some(mut filter) = filter
+                        && !filter.0.is_empty()
+                    {
+                        filter.0.clear();
                     }
                 }
             }
             Interaction::Hovered => {
-                *color = HOVERED_BUTTON.into();
+                *color = theme.button_hovered.into();
             }
             Interaction::None => {
-                *color = NORMAL_BUTTON.into();
+                *color = theme.button.into();
             }
         }
     }
+}
+
+fn handle_dropdown_items<T>(
+    mut commands: Commands,
+    mut dropdown_state_query: Query<(&DropdownTheme, &mut DropdownState)>,
+    dropdown_list_query: Query<(&ChildOf, &Children), With<DropdownList>>,
+    mut item_query: DropdownItemInteractionQuery<T>,
+) where
+    T: Clone + Send + Sync + 'static,
+{
+    for (entity, interaction, mut color, item, parent) in &mut item_query {
+        // item -> list -> dropdown
+        let Ok((list_parent, list_children)) = dropdown_list_query.get(parent.parent()) else {
+            continue;
+        };
+        let dropdown = list_parent.parent();
+        let index = list_children.iter().position(|child| child == entity);
+        let Ok((theme, mut state)) = dropdown_state_query.get_mut(dropdown) else {
+            continue;
+        };
 
-    // Handle item clicks
-    for (interaction, mut color, item) in &mut item_query {
         match *interaction {
             Interaction::Pressed => {
-                *color = PRESSED_BUTTON.into();
+                *color = theme.button_pressed.into();
                 commands.trigger(DropdownChanged {
-                    selected_item: item.0.clone(),
+                    entity: dropdown,
+                    value: item.value.clone(),
                 });
 
-                // Close list and update button text (Update ALL dropdowns for simplicity)
-                for dropdown_children in &dropdown_children_query {
-                    for child in dropdown_children {
-                        // Close list
-                        if let Ok(mut list_node) = dropdown_list_query.get_mut(*child) {
-                            list_node.display = Display::None;
-                        }
-                        // Update button text
-                        if let Ok(text_children) = button_children_query.get(*child) {
-                            for text_child in text_children {
-                                if let Ok(mut text) = text_query.get_mut(*text_child) {
-                                    **text = item.0.clone();
-                                }
-                            }
+                // Close this dropdown's list, apply_dropdown_state updates its button text
+                state.selected = index;
+                state.open = false;
+            }
+            Interaction::Hovered => {
+                *color = theme.button_hovered.into();
+                // so the arrow keys carry on from here
+                state.highlighted = index;
+            }
+            Interaction::None => {
+                *color = index
+                    .map_or(Color::NONE, |index| item_color(theme, &state, index))
+                    .into();
+            }
+        }
+    }
+}
+
+// the next option starting with what's been typed,
+// the same letter over and over steps through the options starting with it
+fn type_ahead_index(
+    options: &[DropdownOption<impl Clone + Send + Sync + 'static>],
+    highlighted: Option<usize>,
+    typed: &str,
+) -> Option<usize> {
+    let mut letters = typed.chars();
+    let first = letters.next()?;
+    let (prefix, start) = if letters.all(|letter| letter == first) {
+        (first.to_string(), highlighted.map_or(0, |index| index + 1))
+    } else {
+        // the highlighted option is kept while it still matches
+        (typed.to_string(), highlighted.unwrap_or(0))
+    };
+
+    (0..options.len())
+        .map(|offset| (start + offset) % options.len())
+        .find(|index| options[*index].label.to_lowercase().starts_with(&prefix))
+}
+
+// an open dropdown has the keyboard and the gamepads: up / down (or the d-pad) move the highlight,
+// enter (or A) selects it, escape (or B) closes the list and typing filters it (if it has a DropdownFilter)
+// or jumps to the next option starting with what's been typed
+fn handle_dropdown_keys<T>(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut keyboard_inputs: MessageReader<KeyboardInput>,
+    mut gamepads: Query<&mut Gamepad>,
+    mut dropdown_query: DropdownKeysQuery<T>,
+    mut dropdown_list_query: Query<(&ComputedNode, &mut ScrollPosition), With<DropdownList>>,
+) where
+    T: Clone + Send + Sync + 'static,
+{
+    let Some((dropdown, options, mut state, mut type_ahead, mut filter, theme, dropdown_children)) =
+        dropdown_query
+            .iter_mut()
+            .find(|(_, _, state, _, _, _, _)| state.open)
+    else {
+        // don't use letters from before it was opened
+        keyboard_inputs.clear();
+        return;
+    };
+    let options = &options.0;
+
+    if take_input(
+        &mut keyboard,
+        &mut gamepads,
+        Some(KeyCode::Escape),
+        Some(GamepadButton::East),
+    ) {
+        state.open = false;
+        return;
+    }
+    let previous = state.highlighted;
+
+    let now = time.elapsed_secs();
+    for input in keyboard_inputs.read() {
+        if input.state != ButtonState::Pressed {
+            continue;
+        }
+        match (&input.logical_key, filter.as_mut()) {
+            (Key::Character(character), Some(filter)) => filter.0.push_str(character),
+            (Key::Space, Some(filter)) => filter.0.push(' '),
+            (Key::Backspace, Some(filter)) => {
+                filter.0.pop();
+            }
+            (Key::Character(character), None) => {
+                if now - type_ahead.last_typed > TYPE_AHEAD_TIMEOUT {
+                    type_ahead.prefix.clear();
+                }
+                type_ahead.prefix.push_str(&character.to_lowercase());
+                type_ahead.last_typed = now;
+
+                if let Some(index) =
+                    type_ahead_index(options, state.highlighted, &type_ahead.prefix)
+                {
+                    state.highlighted = Some(index);
+                }
+            }
+            _ => continue,
+        }
+        keyboard.reset(input.key_code);
+    }
+
+    // the arrow keys and enter only see the items left by the filter
+    let visible = options
+        .iter()
+        .enumerate()
+        .filter(|(_, option)| {
+            filter
+                .as_ref()
+                .is_none_or(|filter| filter.matches(&option.label))
+        })
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let mut position = state
+        .highlighted
+        .and_then(|highlighted| visible.iter().position(|index| *index == highlighted));
+    if position.is_none() && filter.as_ref().is_some_and(|filter| !filter.0.is_empty()) {
+        // the first match, so enter picks it straight away
+        position = (!visible.is_empty()).then_some(0);
+    }
+
+    let last = visible.len().saturating_sub(1);
+    if take_input(
+        &mut keyboard,
+        &mut gamepads,
+        Some(KeyCode::ArrowDown),
+        Some(GamepadButton::DPadDown),
+    ) && !visible.is_empty()
+    {
+        position = Some(position.map_or(0, |position| (position + 1).min(last)));
+    }
+    if take_input(
+        &mut keyboard,
+        &mut gamepads,
+        Some(KeyCode::ArrowUp),
+        Some(GamepadButton::DPadUp),
+    ) && !visible.is_empty()
+    {
+        position = Some(position.map_or(0, |position| position.saturating_sub(1)));
+    }
+    let highlighted = position.map(|position| visible[position]);
+    if state.highlighted != highlighted {
+        state.highlighted = highlighted;
+    }
+
+    // scroll the list just enough to show an item the keys moved to,
+    // the items the filter hides take up no space
+    if highlighted != previous
+        && let Some(position) = position
+        && let Some(list) = dropdown_children
+            .iter()
+            .find(|child| dropdown_list_query.contains(*child))
+        && let Ok((computed, mut scroll_position)) = dropdown_list_query.get_mut(list)
+    {
+        let top = position as f32 * theme.item_height;
+        let view_height =
+            computed.size().y * computed.inverse_scale_factor() - theme.border_width * 2.0;
+        let scroll = scroll_position
+            .y
+            .max(top + theme.item_height - view_height)
+            .min(top);
+        if scroll_position.y != scroll {
+            scroll_position.y = scroll;
+        }
+    }
+
+    if take_input(
+        &mut keyboard,
+        &mut gamepads,
+        Some(KeyCode::Enter),
+        Some(GamepadButton::South),
+    ) | take_input(
+        &mut keyboard,
+        &mut gamepads,
+        Some(KeyCode::NumpadEnter),
+        None,
+    ) && let Some(index) = highlighted
+    {
+        state.selected = Some(index);
+        state.open = false;
+        commands.trigger(DropdownChanged {
+            entity: dropdown,
+            value: options[index].value.clone(),
+        });
+    }
+}
+
+// a click anywhere but on an open dropdown's button or list closes it
+fn close_dropdowns_on_outside_click(
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut dropdown_query: Query<(Entity, &mut DropdownState)>,
+    children_query: Query<&Children>,
+    interaction_query: Query<&Interaction>,
+) {
+    if mouse.get_just_pressed().next().is_none() {
+        return;
+    }
+
+    for (dropdown, mut state) in &mut dropdown_query {
+        if !state.open {
+            continue;
+        }
+
+        let inside = children_query.iter_descendants(dropdown).any(|entity| {
+            interaction_query
+                .get(entity)
+                .is_ok_and(|interaction| *interaction != Interaction::None)
+        });
+        if !inside {
+            state.open = false;
+        }
+    }
+}
+
+// between the button and the list, shown while the list is
+fn spawn_dropdown_filter_box(
+    event: On<Add, DropdownFilter>,
+    mut commands: Commands,
+    dropdown_query: Query<(&DropdownSize, &DropdownTheme)>,
+) {
+    let Ok((size, theme)) = dropdown_query.get(event.entity) else {
+        return;
+    };
+
+    let filter_box = commands
+        .spawn((
+            Node {
+                display: Display::None,
+                width: Val::Px(size.0.x),
+                height: Val::Px(theme.item_height),
+                border: UiRect::all(Val::Px(theme.border_width)),
+                margin: UiRect::top(Val::Px(5.0)),
+                padding: UiRect::horizontal(Val::Px(8.0)),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BorderColor::all(theme.border),
+            BackgroundColor(theme.list_background),
+            // so clicking it doesn't close the list
+            Interaction::default(),
+            DropdownFilterBox,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(FILTER_PLACEHOLDER),
+                TextFont {
+                    font_size: theme.item_font_size,
+                    ..default()
+                },
+                TextColor(theme.placeholder_text),
+            ));
+        })
+        .id();
+    commands
+        .entity(event.entity)
+        .insert_children(1, &[filter_box]);
+}
+
+// (re)spawns the list's items from DropdownOptions, when the dropdown is spawned and whenever they change
+fn rebuild_dropdown_items<T>(
+    mut commands: Commands,
+    mut dropdown_query: DropdownOptionsChangedQuery<T>,
+    dropdown_list_query: Query<Entity, With<DropdownList>>,
+) where
+    T: Clone + Send + Sync + 'static,
+{
+    for (options, theme, dropdown_children, mut state, filter) in &mut dropdown_query {
+        for child in dropdown_children {
+            if let Ok(list) = dropdown_list_query.get(*child) {
+                commands
+                    .entity(list)
+                    .despawn_children()
+                    .with_children(|parent| {
+                        for option in &options.0 {
+                            spawn_dropdown_item(parent, theme, option);
                         }
+                    });
+            }
+        }
+
+        if state.selected.is_some_and(|index| index >= options.0.len()) {
+            state.selected = None;
+        }
+        // relabel, highlight and filter the new items
+        state.set_changed();
+        if let Some(mut filter) = filter {
+            filter.set_changed();
+        }
+    }
+}
+
+type DropdownPartQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Node,
+        &'static mut BorderColor,
+        &'static mut BackgroundColor,
+        Has<DropdownButton>,
+        Has<DropdownList>,
+    ),
+    Or<(
+        With<DropdownButton>,
+        With<DropdownList>,
+        With<DropdownFilterBox>,
+    )>,
+>;
+
+type DropdownItemNodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    &'static mut Node,
+    (
+        With<Button>,
+        Without<DropdownButton>,
+        Without<DropdownList>,
+        Without<DropdownFilterBox>,
+    ),
+>;
+
+type DropdownThemeChangedQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static DropdownTheme,
+        &'static Children,
+        &'static mut DropdownState,
+        Option<&'static mut DropdownFilter>,
+    ),
+    Changed<DropdownTheme>,
+>;
+
+// restyles the button, search box, list and items when the theme is replaced
+fn apply_dropdown_theme(
+    mut dropdown_query: DropdownThemeChangedQuery,
+    mut part_query: DropdownPartQuery,
+    mut item_query: DropdownItemNodeQuery,
+    children_query: Query<&Children>,
+    mut text_query: Query<(&mut TextFont, &mut TextColor)>,
+) {
+    for (theme, dropdown_children, mut state, filter) in &mut dropdown_query {
+        for child in dropdown_children {
+            let Ok((mut node, mut border_color, mut background, is_button, is_list)) =
+                part_query.get_mut(*child)
+            else {
+                continue;
+            };
+            node.border = UiRect::all(Val::Px(theme.border_width));
+            *border_color = BorderColor::all(theme.border);
+
+            if is_button {
+                *background = theme.button.into();
+            } else {
+                *background = theme.list_background.into();
+                if !is_list {
+                    // the search box
+                    node.height = Val::Px(theme.item_height);
+                }
+            }
+
+            if is_list {
+                node.max_height = list_max_height(theme);
+                for item in children_query.get(*child).into_iter().flatten() {
+                    if let Ok(mut item_node) = item_query.get_mut(*item) {
+                        item_node.height = Val::Px(theme.item_height);
                     }
                 }
             }
-            Interaction::Hovered => {
-                *color = HOVERED_BUTTON.into();
+
+            let font_size = if is_button {
+                theme.font_size
+            } else {
+                theme.item_font_size
+            };
+            for descendant in children_query.iter_descendants(*child) {
+                if let Ok((mut font, mut color)) = text_query.get_mut(descendant) {
+                    font.font_size = font_size;
+                    *color = theme.text.into();
+                }
             }
-            Interaction::None => {
-                *color = Color::NONE.into();
+        }
+
+        // recolor the items and the search box's text
+        state.set_changed();
+        if let Some(mut filter) = filter {
+            filter.set_changed();
+        }
+    }
+}
+
+// shows or hides the list, highlights the selected item and puts its text on the button,
+// whether the state was changed by a click or by some other system
+fn apply_dropdown_state(
+    dropdown_query: Query<
+        (
+            &DropdownState,
+            &DropdownTheme,
+            &DropdownPlaceholder,
+            &Children,
+        ),
+        Changed<DropdownState>,
+    >,
+    mut dropdown_list_query: Query<(&mut Node, &Children), With<DropdownList>>,
+    mut filter_box_query: Query<&mut Node, (With<DropdownFilterBox>, Without<DropdownList>)>,
+    button_children_query: Query<&Children, With<DropdownButton>>,
+    mut item_query: Query<(&Interaction, &mut BackgroundColor, &Children), Without<DropdownButton>>,
+    mut text_query: Query<&mut Text>,
+) {
+    for (state, theme, placeholder, dropdown_children) in &dropdown_query {
+        let mut label = placeholder.0.clone();
+        let display = if state.open {
+            Display::Flex
+        } else {
+            Display::None
+        };
+        for child in dropdown_children {
+            if let Ok(mut filter_box_node) = filter_box_query.get_mut(*child) {
+                filter_box_node.display = display;
+            }
+
+            let Ok((mut list_node, items)) = dropdown_list_query.get_mut(*child) else {
+                continue;
+            };
+            list_node.display = display;
+
+            for (index, item) in items.iter().enumerate() {
+                let Ok((interaction, mut color, item_children)) = item_query.get_mut(item) else {
+                    continue;
+                };
+                if state.selected == Some(index)
+                    && let Some(text) = item_children
+                        .iter()
+                        .find_map(|child| text_query.get(child).ok())
+                {
+                    label = text.0.clone();
+                }
+                if *interaction == Interaction::None {
+                    *color = item_color(theme, state, index).into();
+                }
+            }
+        }
+
+        // Update button text
+        for child in dropdown_children {
+            if let Ok(text_children) = button_children_query.get(*child) {
+                for text_child in text_children {
+                    if let Ok(mut text) = text_query.get_mut(*text_child) {
+                        **text = label.clone();
+                    }
+                }
+            }
+        }
+    }
+}
+
+// hides the items that don't match the filter and shows what's been typed in the search box
+fn apply_dropdown_filter(
+    dropdown_query: Query<(&DropdownFilter, &DropdownTheme, &Children), Changed<DropdownFilter>>,
+    dropdown_list_query: Query<&Children, With<DropdownList>>,
+    filter_box_query: Query<&Children, With<DropdownFilterBox>>,
+    mut item_query: Query<(&mut Node, &Children)>,
+    mut text_query: Query<(&mut Text, &mut TextColor)>,
+) {
+    for (filter, theme, dropdown_children) in &dropdown_query {
+        for child in dropdown_children {
+            if let Ok(text_children) = filter_box_query.get(*child) {
+                for text_child in text_children {
+                    if let Ok((mut text, mut color)) = text_query.get_mut(*text_child) {
+                        if filter.0.is_empty() {
+                            **text = FILTER_PLACEHOLDER.to_string();
+                            *color = theme.placeholder_text.into();
+                        } else {
+                            **text = filter.0.clone();
+                            *color = theme.text.into();
+                        }
+                    }
+                }
+            }
+
+            let Ok(items) = dropdown_list_query.get(*child) else {
+                continue;
+            };
+            for item in items {
+                let Ok((mut item_node, item_children)) = item_query.get_mut(*item) else {
+                    continue;
+                };
+                let matches = item_children
+                    .iter()
+                    .find_map(|child| text_query.get(child).ok())
+                    .is_none_or(|(text, _)| filter.matches(&text.0));
+                item_node.display = if matches {
+                    Display::Flex
+                } else {
+                    Display::None
+                };
             }
         }
     }
@@ -0,0 +1,300 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{
+    core_pipeline::Skybox,
+    light::NotShadowCaster,
+    prelude::*,
+    render::render_resource::{TextureViewDescriptor, TextureViewDimension},
+};
+use bevy_common_assets::json::JsonAssetPlugin;
+use bevy_mixamo::asset_event_bridge::*;
+use serde::Deserialize;
+
+pub struct EnvironmentPlugin;
+
+impl Plugin for EnvironmentPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(JsonAssetPlugin::<EnvironmentData>::new(&[
+            "environment.json",
+        ]))
+        .bridge_asset_events::<EnvironmentData>()
+        .init_resource::<PendingCubemaps>()
+        .add_systems(Startup, load_environment)
+        .add_systems(Update, (draw_ground_grid, reinterpret_cubemaps))
+        .add_observer(on_environment_loaded)
+        .add_observer(on_environment_modified);
+    }
+}
+
+// what the characters are displayed in
+#[derive(Debug, Deserialize, Asset, TypePath)]
+pub struct EnvironmentData {
+    // width and depth of the ground plane, centered on the origin
+    #[serde(default = "default_ground_size")]
+    pub ground_size: f32,
+
+    // linear RGB
+    #[serde(default = "default_ground_color")]
+    pub ground_color: [f32; 3],
+
+    // draws a grid over the ground, one line per grid_spacing units
+    #[serde(default = "default_true")]
+    pub grid: bool,
+    #[serde(default = "default_grid_spacing")]
+    pub grid_spacing: f32,
+
+    // drawn behind everything, either a cubemap (.ktx2)
+    // or an image with its six faces stacked vertically (e.g. .png or .hdr)
+    #[serde(default)]
+    pub skybox_path: Option<String>,
+    #[serde(default = "default_skybox_brightness")]
+    pub skybox_brightness: f32,
+
+    // image based lighting for the character's PBR materials
+    #[serde(default)]
+    pub environment_map: Option<EnvironmentMapData>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EnvironmentMapData {
+    // prefiltered diffuse and specular cubemaps (.ktx2),
+    // without them the skybox is filtered on the GPU instead (its size must be a power of two)
+    #[serde(default)]
+    pub diffuse_path: Option<String>,
+    #[serde(default)]
+    pub specular_path: Option<String>,
+
+    #[serde(default = "default_environment_map_intensity")]
+    pub intensity: f32,
+}
+
+fn default_ground_size() -> f32 {
+    20.0
+}
+
+fn default_ground_color() -> [f32; 3] {
+    [0.3, 0.3, 0.3]
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_grid_spacing() -> f32 {
+    1.0
+}
+
+fn default_skybox_brightness() -> f32 {
+    1000.0
+}
+
+fn default_environment_map_intensity() -> f32 {
+    1000.0
+}
+
+#[derive(Resource)]
+struct EnvironmentHandle(Handle<EnvironmentData>);
+
+#[derive(Component)]
+struct Ground;
+
+// the grid is drawn with gizmos every frame rather than spawned
+#[derive(Component)]
+struct GroundGrid {
+    size: f32,
+    spacing: f32,
+}
+
+// stacked images that need to be turned into cubemaps once they load
+#[derive(Default, Resource)]
+struct PendingCubemaps(Vec<Handle<Image>>);
+
+const GRID_COLOR: Color = Color::srgba(0.8, 0.8, 0.8, 0.25);
+
+fn load_environment(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let path = "environment.json";
+
+    info!("Loading environment from '{}' ...", path);
+    commands.insert_resource(EnvironmentHandle(
+        asset_server.load::<EnvironmentData>(path),
+    ));
+}
+
+fn on_environment_loaded(
+    event: On<AssetLoadedEvent<EnvironmentData>>,
+    mut commands: Commands,
+    environment_handle: Res<EnvironmentHandle>,
+) {
+    if event.asset_id != environment_handle.0.id() {
+        return;
+    }
+    info!("Loaded environment");
+
+    commands.run_system_cached(spawn_environment);
+}
+
+// edits to environment.json are picked up while the app is running
+fn on_environment_modified(
+    event: On<AssetModifiedEvent<EnvironmentData>>,
+    mut commands: Commands,
+    environment_handle: Res<EnvironmentHandle>,
+) {
+    if event.asset_id != environment_handle.0.id() {
+        return;
+    }
+    info!("Reloading environment ...");
+
+    commands.run_system_cached(spawn_environment);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_environment(
+    mut commands: Commands,
+    environment_handle: Res<EnvironmentHandle>,
+    environments: Res<Assets<EnvironmentData>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut pending_cubemaps: ResMut<PendingCubemaps>,
+    asset_server: Res<AssetServer>,
+    grounds: Query<Entity, With<Ground>>,
+    cameras: Query<Entity, With<Camera3d>>,
+) {
+    let Some(environment) = environments.get(&environment_handle.0) else {
+        return;
+    };
+
+    for entity in &grounds {
+        commands.entity(entity).despawn();
+    }
+
+    let [r, g, b] = environment.ground_color;
+    let mut ground = commands.spawn((
+        Mesh3d(
+            meshes.add(
+                Plane3d::default()
+                    .mesh()
+                    .size(environment.ground_size, environment.ground_size),
+            ),
+        ),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::linear_rgb(r, g, b),
+            perceptual_roughness: 1.0,
+            ..default()
+        })),
+        // receives the character's shadows without casting its own
+        NotShadowCaster,
+        Name::new("Ground"),
+        Ground,
+    ));
+    if environment.grid && environment.grid_spacing > 0.0 {
+        ground.insert(GroundGrid {
+            size: environment.ground_size,
+            spacing: environment.grid_spacing,
+        });
+    }
+
+    let skybox = environment.skybox_path.as_ref().map(|skybox_path| {
+        let image = asset_server.load::<Image>(skybox_path);
+        pending_cubemaps.0.push(image.clone());
+        image
+    });
+
+    for camera in &cameras {
+        let mut camera = commands.entity(camera);
+        camera.remove::<(Skybox, EnvironmentMapLight, GeneratedEnvironmentMapLight)>();
+
+        if let Some(skybox) = &skybox {
+            camera.insert(Skybox {
+                image: skybox.clone(),
+                brightness: environment.skybox_brightness,
+                ..default()
+            });
+        }
+
+        let Some(environment_map) = &environment.environment_map else {
+            continue;
+        };
+        match (
+            &environment_map.diffuse_path,
+            &environment_map.specular_path,
+            &skybox,
+        ) {
+            (Some(diffuse_path), Some(specular_path), _) => {
+                camera.insert(EnvironmentMapLight {
+                    diffuse_map: asset_server.load(diffuse_path),
+                    specular_map: asset_server.load(specular_path),
+                    intensity: environment_map.intensity,
+                    ..default()
+                });
+            }
+            (None, None, Some(skybox)) => {
+                camera.insert(GeneratedEnvironmentMapLight {
+                    environment_map: skybox.clone(),
+                    intensity: environment_map.intensity,
+                    ..default()
+                });
+            }
+            _ => {
+                warn!(
+                    "environment_map needs both diffuse_path and specular_path, or neither and a skybox_path"
+                );
+            }
+        }
+    }
+}
+
+// stacked images load as regular 2D textures,
+// reinterpret them as cubemaps so that they can be used for the skybox and lighting
+fn reinterpret_cubemaps(
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut pending_cubemaps: ResMut<PendingCubemaps>,
+) {
+    pending_cubemaps.0.retain(|handle| {
+        if asset_server.load_state(handle).is_failed() {
+            return false;
+        }
+        if !asset_server.is_loaded(handle) {
+            return true;
+        }
+        let Some(image) = images.get_mut(handle) else {
+            return true;
+        };
+
+        // already a cubemap (e.g. .ktx2)
+        if image.texture_descriptor.array_layer_count() != 1 {
+            return false;
+        }
+
+        if let Err(err) = image.reinterpret_stacked_2d_as_array(6) {
+            warn!(
+                "Skybox image '{}' isn't a cubemap: {}",
+                handle.path().map(ToString::to_string).unwrap_or_default(),
+                err
+            );
+            return false;
+        }
+        image.texture_view_descriptor = Some(TextureViewDescriptor {
+            dimension: Some(TextureViewDimension::Cube),
+            ..default()
+        });
+        false
+    });
+}
+
+fn draw_ground_grid(mut gizmos: Gizmos, grids: Query<(&GroundGrid, &GlobalTransform)>) {
+    for (grid, transform) in &grids {
+        let cells = (grid.size / grid.spacing).floor() as u32;
+
+        // the grid is drawn in the XY plane, lay it flat and lift it off the ground to avoid z-fighting
+        gizmos.grid(
+            Isometry3d::new(
+                transform.translation() + Vec3::Y * 0.001,
+                Quat::from_rotation_x(FRAC_PI_2),
+            ),
+            UVec2::splat(cells),
+            Vec2::splat(grid.spacing),
+            GRID_COLOR,
+        );
+    }
+}
@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use bevy::{
+    animation::{advance_animations, transition::advance_transitions},
+    ecs::schedule::ScheduleCleanupPolicy,
+    prelude::*,
+};
+
+use crate::animation_lod::*;
+
+// advances animations (and their transitions) in FixedUpdate by a locked timestep
+// instead of by each frame's delta, so that a clip always steps through exactly the same poses
+// however fast or unevenly the app runs (e.g. for golden pose tests and reproducible captures)
+// poses are still sampled every frame, from the last fixed step
+#[derive(Debug, Clone)]
+pub struct FixedAnimationPlugin {
+    pub timestep: Duration,
+}
+
+impl Default for FixedAnimationPlugin {
+    fn default() -> Self {
+        Self::from_hz(60.0)
+    }
+}
+
+impl FixedAnimationPlugin {
+    pub fn from_hz(hz: f64) -> Self {
+        Self {
+            timestep: Duration::from_secs_f64(1.0 / hz),
+        }
+    }
+}
+
+impl Plugin for FixedAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Time::<Fixed>::from_duration(self.timestep))
+            .add_systems(
+                FixedUpdate,
+                (advance_transitions, advance_animations).chain(),
+            );
+    }
+
+    // the AnimationPlugin has to have added its systems before they can be taken out
+    fn finish(&self, app: &mut App) {
+        for result in [
+            app.remove_systems_in_set(
+                PostUpdate,
+                advance_transitions,
+                ScheduleCleanupPolicy::RemoveSystemsOnly,
+            ),
+            app.remove_systems_in_set(
+                PostUpdate,
+                advance_animations,
+                ScheduleCleanupPolicy::RemoveSystemsOnly,
+            ),
+        ] {
+            if let Err(err) = result {
+                warn!("Failed to move animation updates to FixedUpdate: {}", err);
+            }
+        }
+
+        // lod skips sampling by frame count, which isn't deterministic
+        if let Some(mut settings) = app.world_mut().get_resource_mut::<AnimationLodSettings>() {
+            settings.enabled = false;
+        }
+    }
+}
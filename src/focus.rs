@@ -0,0 +1,286 @@
+use bevy::{
+    prelude::*,
+    ui::{FocusPolicy, UiGlobalTransform, UiSystems},
+};
+
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Focusable>()
+            .register_type::<FocusTrap>()
+            .init_resource::<UiFocus>()
+            .add_systems(Startup, setup_focus_ring)
+            // after the mouse has set this frame's interactions, so a press isn't undone
+            .add_systems(
+                PreUpdate,
+                (release_focus_press, navigate_focus, activate_focus)
+                    .chain()
+                    .in_set(FocusSystems)
+                    .after(UiSystems::Focus),
+            )
+            .add_systems(PostUpdate, place_focus_ring.before(UiSystems::Layout));
+    }
+}
+
+// widgets that want the keys or the d-pad first (e.g. an open dropdown) run before this
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct FocusSystems;
+
+// tab / shift tab and the d-pad move the focus between these (in reading order for tab),
+// enter / A presses the focused one as if it had been clicked
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component)]
+#[require(Interaction)]
+pub struct Focusable;
+
+// while one exists the focus stays among its descendants, e.g. in a modal dialog
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component)]
+pub struct FocusTrap;
+
+// the focused widget, cleared by clicking with the mouse
+#[derive(Debug, Default, Resource)]
+pub struct UiFocus {
+    pub entity: Option<Entity>,
+
+    // pressed by enter / A last frame, released this one
+    pressed: Option<Entity>,
+}
+
+#[derive(Component)]
+struct FocusRing;
+
+const FOCUS_RING_COLOR: Color = Color::srgb(1.0, 0.8, 0.2);
+const FOCUS_RING_WIDTH: f32 = 2.0;
+
+// over dialogs, under tooltips
+const FOCUS_RING_Z_INDEX: i32 = i32::MAX - 1;
+
+// a key or gamepad button press that hasn't been used by anything yet,
+// taking it so that later systems (e.g. the viewer's shortcuts) don't see it too
+pub fn take_input(
+    keyboard: &mut ButtonInput<KeyCode>,
+    gamepads: &mut Query<&mut Gamepad>,
+    key: Option<KeyCode>,
+    button: Option<GamepadButton>,
+) -> bool {
+    let mut pressed = false;
+    if let Some(key) = key
+        && keyboard.just_pressed(key)
+    {
+        keyboard.reset(key);
+        pressed = true;
+    }
+    if let Some(button) = button {
+        for mut gamepad in gamepads {
+            if gamepad.just_pressed(button) {
+                gamepad.digital_mut().reset(button);
+                pressed = true;
+            }
+        }
+    }
+    pressed
+}
+
+fn setup_focus_ring(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            display: Display::None,
+            border: UiRect::all(Val::Px(FOCUS_RING_WIDTH)),
+            ..default()
+        },
+        BorderColor::all(FOCUS_RING_COLOR),
+        GlobalZIndex(FOCUS_RING_Z_INDEX),
+        FocusPolicy::Pass,
+        Name::new("FocusRing"),
+        FocusRing,
+    ));
+}
+
+// logical pixels, from the last layout
+fn focus_rect(computed: &ComputedNode, transform: &UiGlobalTransform) -> Rect {
+    let scale = computed.inverse_scale_factor();
+    Rect::from_center_size(transform.translation * scale, computed.size() * scale)
+}
+
+fn is_descendant(entity: Entity, ancestor: Entity, parents: &Query<&ChildOf>) -> bool {
+    parents
+        .iter_ancestors(entity)
+        .any(|parent| parent == ancestor)
+}
+
+fn release_focus_press(mut focus: ResMut<UiFocus>, mut interactions: Query<&mut Interaction>) {
+    let Some(pressed) = focus.pressed.take() else {
+        return;
+    };
+    // the mouse may have taken over since
+    if let Ok(mut interaction) = interactions.get_mut(pressed)
+        && *interaction == Interaction::Pressed
+    {
+        *interaction = Interaction::None;
+    }
+}
+
+fn navigate_focus(
+    mut focus: ResMut<UiFocus>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut gamepads: Query<&mut Gamepad>,
+    focusables: Query<
+        (
+            Entity,
+            &ComputedNode,
+            &UiGlobalTransform,
+            &InheritedVisibility,
+        ),
+        With<Focusable>,
+    >,
+    traps: Query<Entity, With<FocusTrap>>,
+    parents: Query<&ChildOf>,
+) {
+    // everything that can be focused right now, in reading order
+    let trap = traps.iter().last();
+    let mut candidates = focusables
+        .iter()
+        .filter(|(entity, computed, _, visibility)| {
+            !computed.is_empty()
+                && visibility.get()
+                && trap.is_none_or(|trap| is_descendant(*entity, trap, &parents))
+        })
+        .map(|(entity, computed, transform, _)| (entity, focus_rect(computed, transform)))
+        .collect::<Vec<_>>();
+    candidates.sort_by(|(_, a), (_, b)| {
+        a.min
+            .y
+            .total_cmp(&b.min.y)
+            .then(a.min.x.total_cmp(&b.min.x))
+    });
+
+    // a hidden or despawned widget, or one outside an open dialog, loses the focus
+    let mut focused = focus.entity.and_then(|entity| {
+        candidates
+            .iter()
+            .position(|(candidate, _)| *candidate == entity)
+    });
+    if mouse.get_just_pressed().next().is_some() {
+        focused = None;
+    }
+
+    let shift = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if take_input(&mut keyboard, &mut gamepads, Some(KeyCode::Tab), None) && !candidates.is_empty()
+    {
+        let last = candidates.len() - 1;
+        focused = Some(match (focused, shift) {
+            (None, false) => 0,
+            (None, true) => last,
+            (Some(index), false) => {
+                if index == last {
+                    0
+                } else {
+                    index + 1
+                }
+            }
+            (Some(index), true) => index.checked_sub(1).unwrap_or(last),
+        });
+    }
+
+    for (button, direction) in [
+        (GamepadButton::DPadUp, Vec2::NEG_Y),
+        (GamepadButton::DPadDown, Vec2::Y),
+        (GamepadButton::DPadLeft, Vec2::NEG_X),
+        (GamepadButton::DPadRight, Vec2::X),
+    ] {
+        if !take_input(&mut keyboard, &mut gamepads, None, Some(button)) || candidates.is_empty() {
+            continue;
+        }
+        let Some(index) = focused else {
+            focused = Some(0);
+            continue;
+        };
+
+        // the closest widget that way, preferring ones straight ahead
+        let from = candidates[index].1.center();
+        focused = candidates
+            .iter()
+            .enumerate()
+            .filter_map(|(candidate, (_, rect))| {
+                let offset = rect.center() - from;
+                let along = offset.dot(direction);
+                let across = offset.perp_dot(direction).abs();
+                (along > 0.0).then_some((candidate, along + across * 2.0))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(candidate, _)| candidate)
+            .or(focused);
+    }
+
+    let entity = focused.map(|index| candidates[index].0);
+    if focus.entity != entity {
+        focus.entity = entity;
+    }
+}
+
+fn activate_focus(
+    mut focus: ResMut<UiFocus>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut gamepads: Query<&mut Gamepad>,
+    mut interactions: Query<&mut Interaction>,
+) {
+    let Some(entity) = focus.entity else {
+        return;
+    };
+    let activated = take_input(
+        &mut keyboard,
+        &mut gamepads,
+        Some(KeyCode::Enter),
+        Some(GamepadButton::South),
+    ) | take_input(
+        &mut keyboard,
+        &mut gamepads,
+        Some(KeyCode::NumpadEnter),
+        None,
+    );
+    if !activated {
+        return;
+    }
+
+    // widgets react to their button being pressed, the same as for a click
+    if let Ok(mut interaction) = interactions.get_mut(entity) {
+        *interaction = Interaction::Pressed;
+        focus.pressed = Some(entity);
+    }
+}
+
+// outlines the focused widget, from its last layout like the scrollbar thumbs
+fn place_focus_ring(
+    focus: Res<UiFocus>,
+    focusables: Query<(&ComputedNode, &UiGlobalTransform), With<Focusable>>,
+    mut ring: Single<&mut Node, With<FocusRing>>,
+) {
+    let rect = focus
+        .entity
+        .and_then(|entity| focusables.get(entity).ok())
+        .filter(|(computed, _)| !computed.is_empty())
+        .map(|(computed, transform)| focus_rect(computed, transform).inflate(FOCUS_RING_WIDTH));
+    let Some(rect) = rect else {
+        if ring.display != Display::None {
+            ring.display = Display::None;
+        }
+        return;
+    };
+
+    let node = Node {
+        position_type: PositionType::Absolute,
+        left: Val::Px(rect.min.x),
+        top: Val::Px(rect.min.y),
+        width: Val::Px(rect.width()),
+        height: Val::Px(rect.height()),
+        border: UiRect::all(Val::Px(FOCUS_RING_WIDTH)),
+        ..default()
+    };
+    if **ring != node {
+        **ring = node;
+    }
+}
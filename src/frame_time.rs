@@ -0,0 +1,232 @@
+use bevy::{
+    diagnostic::{DiagnosticPath, DiagnosticsStore, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_mixamo::*;
+
+// the FPS counter and a scrolling graph of recent frame times, to spot stutters,
+// next to the CharacterDiagnosticsPlugin's stats
+pub struct FrameTimeGraphPlugin;
+
+impl Plugin for FrameTimeGraphPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FrameTimeDiagnosticsPlugin {
+            max_history_length: FRAME_TIME_HISTORY,
+            ..default()
+        })
+        .add_systems(Startup, setup_frame_time_graph)
+        .add_systems(
+            Update,
+            (
+                update_fps_text,
+                update_frame_time_graph,
+                update_diagnostics_text,
+            ),
+        );
+    }
+}
+
+// frames shown in the graph, one pixel wide each
+pub const FRAME_TIME_HISTORY: usize = 240;
+
+// 60 Hz
+const REFERENCE_FRAME_TIME_MS: f64 = 1000.0 / 60.0;
+
+// frame times above this are clipped to the top of the graph
+const GRAPH_MAX_MS: f64 = REFERENCE_FRAME_TIME_MS * 2.0;
+
+const GRAPH_HEIGHT: f32 = 60.0;
+
+const FAST_FRAME: Color = Color::srgb(0.2, 0.8, 0.2);
+const SLOW_FRAME: Color = Color::srgb(0.9, 0.2, 0.2);
+
+#[derive(Component)]
+struct FpsText;
+
+#[derive(Component)]
+struct FrameTimeStatsText;
+
+#[derive(Component)]
+struct DiagnosticsText;
+
+// the index into the graph, the newest frame is the last bar
+#[derive(Component)]
+struct FrameTimeBar(usize);
+
+fn graph_height(frame_time_ms: f64) -> f32 {
+    (frame_time_ms.min(GRAPH_MAX_MS) / GRAPH_MAX_MS) as f32 * GRAPH_HEIGHT
+}
+
+fn setup_frame_time_graph(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                column_gap: Val::Px(10.0),
+                ..default()
+            },
+            Name::new("FrameTimeGraph"),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::from("FPS: 0.0"),
+                TextColor(Color::WHITE),
+                // wide enough that the graph doesn't move as the text changes
+                Node {
+                    width: Val::Px(140.0),
+                    ..default()
+                },
+                FpsText,
+            ));
+
+            parent
+                .spawn(Node {
+                    flex_direction: FlexDirection::Column,
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            Node {
+                                width: Val::Px(FRAME_TIME_HISTORY as f32),
+                                height: Val::Px(GRAPH_HEIGHT),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.7)),
+                        ))
+                        .with_children(|parent| {
+                            for index in 0..FRAME_TIME_HISTORY {
+                                parent.spawn((
+                                    Node {
+                                        position_type: PositionType::Absolute,
+                                        left: Val::Px(index as f32),
+                                        bottom: Val::Px(0.0),
+                                        width: Val::Px(1.0),
+                                        height: Val::Px(0.0),
+                                        ..default()
+                                    },
+                                    BackgroundColor(FAST_FRAME),
+                                    FrameTimeBar(index),
+                                ));
+                            }
+
+                            // 16.6 ms
+                            parent.spawn((
+                                Node {
+                                    position_type: PositionType::Absolute,
+                                    left: Val::Px(0.0),
+                                    bottom: Val::Px(graph_height(REFERENCE_FRAME_TIME_MS)),
+                                    width: Val::Percent(100.0),
+                                    height: Val::Px(1.0),
+                                    ..default()
+                                },
+                                BackgroundColor(Color::srgba(1.0, 1.0, 0.0, 0.8)),
+                            ));
+                        });
+
+                    parent.spawn((
+                        Text::new(""),
+                        TextFont {
+                            font_size: 14.0,
+                            ..default()
+                        },
+                        TextColor(Color::WHITE),
+                        FrameTimeStatsText,
+                    ));
+                });
+
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                DiagnosticsText,
+            ));
+        });
+}
+
+fn update_fps_text(diagnostics: Res<DiagnosticsStore>, mut query: Query<&mut Text, With<FpsText>>) {
+    for mut text in &mut query {
+        if let Some(fps) = diagnostics.get(&FrameTimeDiagnosticsPlugin::FPS)
+            && let Some(value) = fps.smoothed()
+        {
+            **text = format!("FPS: {value:.2}");
+        }
+    }
+}
+
+fn update_frame_time_graph(
+    diagnostics: Res<DiagnosticsStore>,
+    mut bars: Query<(&FrameTimeBar, &mut Node, &mut BackgroundColor)>,
+    mut stats_texts: Query<&mut Text, With<FrameTimeStatsText>>,
+) {
+    let Some(frame_time) = diagnostics.get(&FrameTimeDiagnosticsPlugin::FRAME_TIME) else {
+        return;
+    };
+
+    // right aligned until the history fills up
+    let frame_times = frame_time.values().copied().collect::<Vec<_>>();
+    let offset = FRAME_TIME_HISTORY.saturating_sub(frame_times.len());
+    for (bar, mut node, mut color) in &mut bars {
+        let frame_time_ms = bar
+            .0
+            .checked_sub(offset)
+            .and_then(|index| frame_times.get(index))
+            .copied()
+            .unwrap_or_default();
+        node.height = Val::Px(graph_height(frame_time_ms));
+        *color = if frame_time_ms > REFERENCE_FRAME_TIME_MS {
+            SLOW_FRAME.into()
+        } else {
+            FAST_FRAME.into()
+        };
+    }
+
+    let (Some(min), Some(max), Some(average)) = (
+        frame_times.iter().copied().reduce(f64::min),
+        frame_times.iter().copied().reduce(f64::max),
+        frame_time.average(),
+    ) else {
+        return;
+    };
+    for mut text in &mut stats_texts {
+        **text = format!("min {min:.1} / avg {average:.1} / max {max:.1} ms");
+    }
+}
+
+fn update_diagnostics_text(
+    diagnostics: Res<DiagnosticsStore>,
+    mut texts: Query<&mut Text, With<DiagnosticsText>>,
+) {
+    let value = |path: &DiagnosticPath| {
+        diagnostics
+            .get(path)
+            .and_then(|diagnostic| diagnostic.value())
+            .unwrap_or_default()
+    };
+    let animation_time = diagnostics
+        .get(&CharacterDiagnosticsPlugin::ANIMATION_TIME)
+        .and_then(|diagnostic| diagnostic.smoothed())
+        .unwrap_or_default();
+
+    for mut text in &mut texts {
+        **text = format!(
+            "characters: {} / bones: {}\n\
+             animation: {:.2} ms\n\
+             scenes: {} / meshes: {} / images: {}\n\
+             clips: {} / graphs: {}",
+            value(&CharacterDiagnosticsPlugin::CHARACTERS),
+            value(&CharacterDiagnosticsPlugin::ANIMATED_BONES),
+            animation_time,
+            value(&CharacterDiagnosticsPlugin::SCENES),
+            value(&CharacterDiagnosticsPlugin::MESHES),
+            value(&CharacterDiagnosticsPlugin::IMAGES),
+            value(&CharacterDiagnosticsPlugin::ANIMATION_CLIPS),
+            value(&CharacterDiagnosticsPlugin::ANIMATION_GRAPHS),
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use bevy::{camera::primitives::Aabb, prelude::*, scene::SceneInstanceReady};
+use bevy_mixamo::*;
+
+pub struct FramingPlugin;
+
+impl Plugin for FramingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(on_character_model_ready).add_systems(
+            Update,
+            (frame_character_models, update_orbit_cameras).chain(),
+        );
+    }
+}
+
+// extra room around the model
+const FRAMING_MARGIN: f32 = 1.1;
+
+// radians per second at full stick
+const ORBIT_SPEED: f32 = 2.0;
+
+// keeps the camera from flipping over the top
+const MAX_ELEVATION: f32 = 1.4;
+
+// a camera that circles a focus point, the gamepad's right stick orbits it
+#[derive(Debug, Component)]
+pub struct OrbitCamera {
+    pub focus: Vec3,
+    pub distance: f32,
+    // radians around the Y axis, 0 looks down -Z
+    pub yaw: f32,
+    // radians above the focus
+    pub elevation: f32,
+}
+
+impl OrbitCamera {
+    pub fn looking_from(translation: Vec3, focus: Vec3) -> Self {
+        let offset = translation - focus;
+        let distance = offset.length();
+        Self {
+            focus,
+            distance,
+            yaw: offset.x.atan2(offset.z),
+            elevation: (offset.y / distance.max(f32::EPSILON)).asin(),
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        let offset = Vec3::new(
+            self.elevation.cos() * self.yaw.sin(),
+            self.elevation.sin(),
+            self.elevation.cos() * self.yaw.cos(),
+        ) * self.distance;
+        Transform::from_translation(self.focus + offset).looking_at(self.focus, Vec3::Y)
+    }
+}
+
+// while present, spawned characters don't move the camera (e.g. it was restored from settings)
+// removed once a character has spawned
+#[derive(Resource)]
+pub struct KeepCamera;
+
+// waiting for the model's bounds so the camera can be framed to it
+#[derive(Component)]
+struct FrameCamera;
+
+fn on_character_model_ready(
+    scene_ready: On<SceneInstanceReady>,
+    mut commands: Commands,
+    character_models: Query<(), With<CharacterModel>>,
+) {
+    if character_models.contains(scene_ready.entity) {
+        commands.entity(scene_ready.entity).insert(FrameCamera);
+    }
+}
+
+// moves the camera towards or away from the model so that the whole model fits in view
+// mesh bounds and global transforms aren't available until after the scene has spawned
+fn frame_character_models(
+    mut commands: Commands,
+    character_models: Query<Entity, (With<CharacterModel>, With<FrameCamera>)>,
+    children: Query<&Children>,
+    bounds: Query<(&Aabb, &GlobalTransform)>,
+    camera: Single<(&mut OrbitCamera, &Projection)>,
+    keep_camera: Option<Res<KeepCamera>>,
+) {
+    let (mut orbit_camera, projection) = camera.into_inner();
+
+    for entity in &character_models {
+        if keep_camera.is_some() {
+            commands.entity(entity).remove::<FrameCamera>();
+            commands.remove_resource::<KeepCamera>();
+            continue;
+        }
+
+        let mut min = Vec3::splat(f32::MAX);
+        let mut max = Vec3::splat(f32::MIN);
+        for child in children.iter_descendants(entity) {
+            let Ok((aabb, transform)) = bounds.get(child) else {
+                continue;
+            };
+
+            let (center, half_extents) = (Vec3::from(aabb.center), Vec3::from(aabb.half_extents));
+            for x in [-1.0, 1.0] {
+                for y in [-1.0, 1.0] {
+                    for z in [-1.0, 1.0] {
+                        let corner =
+                            transform.transform_point(center + half_extents * Vec3::new(x, y, z));
+                        min = min.min(corner);
+                        max = max.max(corner);
+                    }
+                }
+            }
+        }
+
+        // bounds are calculated once the meshes load
+        if min.cmpgt(max).any() {
+            continue;
+        }
+        commands.entity(entity).remove::<FrameCamera>();
+
+        let center = (min + max) * 0.5;
+        let radius = (max - min).length() * 0.5 * FRAMING_MARGIN;
+
+        // fit the bounding sphere into the narrower field of view
+        let fov = match projection {
+            Projection::Perspective(perspective) => {
+                let horizontal =
+                    2.0 * ((perspective.fov * 0.5).tan() * perspective.aspect_ratio).atan();
+                perspective.fov.min(horizontal)
+            }
+            _ => continue,
+        };
+        orbit_camera.focus = center;
+        orbit_camera.distance = radius / (fov * 0.5).sin();
+    }
+}
+
+fn update_orbit_cameras(
+    time: Res<Time>,
+    gamepads: Query<&Gamepad>,
+    mut cameras: Query<(&mut OrbitCamera, &mut Transform)>,
+) {
+    let stick = gamepads
+        .iter()
+        .map(Gamepad::right_stick)
+        .find(|stick| stick.length() > 0.1)
+        .unwrap_or_default();
+
+    for (mut orbit_camera, mut transform) in &mut cameras {
+        if stick != Vec2::ZERO {
+            let step = ORBIT_SPEED * time.delta_secs();
+            orbit_camera.yaw -= stick.x * step;
+            orbit_camera.elevation =
+                (orbit_camera.elevation - stick.y * step).clamp(-MAX_ELEVATION, MAX_ELEVATION);
+        }
+
+        if orbit_camera.is_changed() {
+            *transform = orbit_camera.transform();
+        }
+    }
+}
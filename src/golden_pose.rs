@@ -0,0 +1,371 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use bevy::{
+    app::ScheduleRunnerPlugin, prelude::*, time::TimeUpdateStrategy, transform::TransformSystems,
+};
+use bevy_mixamo::*;
+use serde::{Deserialize, Serialize};
+
+use crate::headless::*;
+use crate::loading::*;
+
+// the checked-in expected poses, relative to where the viewer is run from
+pub const GOLDEN_POSES_PATH: &str = "golden/poses.json";
+
+// gives up on assets (or models) that never finish loading
+const GOLDEN_POSES_TIMEOUT_SECONDS: f32 = 60.0;
+
+// plays each case's animation on its character for a fixed number of animation steps,
+// then compares the sampled bones against the checked-in poses and exits,
+// with a non-zero code if any bone drifted (e.g. a retargeting or graph building regression)
+// run with --golden-poses, or --golden-poses-update to record the current poses as the expected ones
+pub struct GoldenPosePlugin {
+    pub update: bool,
+}
+
+impl Plugin for GoldenPosePlugin {
+    fn build(&self, app: &mut App) {
+        let fixed_animation = FixedAnimationPlugin::default();
+
+        // every frame is exactly one animation step, however long it takes
+        app.add_plugins(headless_plugins())
+            .add_plugins(ScheduleRunnerPlugin::run_loop(Duration::ZERO))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(fixed_animation.timestep))
+            .add_plugins(CharacterPlugin)
+            .add_plugins(fixed_animation)
+            .insert_resource(GoldenPoseRun::new(self.update))
+            .add_systems(Startup, load_all_characters)
+            // after propagation so that the sampled bones have this frame's pose
+            .add_systems(
+                PostUpdate,
+                run_golden_poses.after(TransformSystems::Propagate),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoldenPoses {
+    // how far a bone can be from its expected pose, in meters and radians
+    pub tolerance: f32,
+    pub cases: Vec<GoldenPoseCase>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoldenPoseCase {
+    pub character: String,
+    pub animation: String,
+
+    // FixedAnimationPlugin steps to play the animation for before sampling
+    pub steps: u32,
+
+    pub bones: Vec<String>,
+
+    // relative to the model, written by --golden-poses-update
+    #[serde(default)]
+    pub expected: BTreeMap<String, GoldenBonePose>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct GoldenBonePose {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+}
+
+impl From<Transform> for GoldenBonePose {
+    fn from(transform: Transform) -> Self {
+        Self {
+            translation: transform.translation.to_array(),
+            rotation: transform.rotation.to_array(),
+        }
+    }
+}
+
+impl GoldenBonePose {
+    // None if the pose is within tolerance
+    fn compare(&self, actual: &GoldenBonePose, tolerance: f32) -> Option<String> {
+        let distance = Vec3::from(self.translation).distance(Vec3::from(actual.translation));
+        let angle = Quat::from_array(self.rotation)
+            .normalize()
+            .angle_between(Quat::from_array(actual.rotation).normalize());
+        (distance > tolerance || angle > tolerance)
+            .then(|| format!("is {distance:.5}m and {angle:.5} radians off"))
+    }
+}
+
+#[derive(Debug, Default)]
+enum GoldenPoseCaseState {
+    #[default]
+    Spawn,
+    Spawning,
+    Playing {
+        model: Entity,
+        steps: u32,
+    },
+}
+
+#[derive(Resource)]
+struct GoldenPoseRun {
+    update: bool,
+
+    // None until the poses are read, once the characters have loaded
+    poses: Option<GoldenPoses>,
+    case: usize,
+    state: GoldenPoseCaseState,
+
+    failures: Vec<String>,
+    timeout: Timer,
+}
+
+impl GoldenPoseRun {
+    fn new(update: bool) -> Self {
+        Self {
+            update,
+            poses: None,
+            case: 0,
+            state: GoldenPoseCaseState::default(),
+            failures: Vec::new(),
+            timeout: Timer::from_seconds(GOLDEN_POSES_TIMEOUT_SECONDS, TimerMode::Once),
+        }
+    }
+}
+
+fn read_golden_poses() -> Result<GoldenPoses, String> {
+    let json = std::fs::read_to_string(GOLDEN_POSES_PATH)
+        .map_err(|err| format!("Failed to read '{}': {}", GOLDEN_POSES_PATH, err))?;
+    serde_json::from_str(&json)
+        .map_err(|err| format!("Failed to parse '{}': {}", GOLDEN_POSES_PATH, err))
+}
+
+fn write_golden_poses(poses: &GoldenPoses) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(poses).map_err(|err| err.to_string())?;
+    std::fs::write(GOLDEN_POSES_PATH, json + "\n")
+        .map_err(|err| format!("Failed to write '{}': {}", GOLDEN_POSES_PATH, err))
+}
+
+// each bone's transform relative to the model
+fn sample_bones(
+    model: Entity,
+    bones: &[String],
+    children: &Query<&Children>,
+    names: &Query<&Name>,
+    transforms: &Query<&GlobalTransform>,
+) -> BTreeMap<String, Option<GoldenBonePose>> {
+    let Ok(model_transform) = transforms.get(model) else {
+        return BTreeMap::new();
+    };
+
+    bones
+        .iter()
+        .map(|bone| {
+            let pose = find_bone(model, bone, children, names)
+                .and_then(|bone| transforms.get(bone).ok())
+                .map(|transform| transform.reparented_to(model_transform).into());
+            (bone.clone(), pose)
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_golden_poses(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    manifest: Res<CharacterManifestHandle>,
+    folder: Res<CharacterFolderHandle>,
+    characters: Res<Characters>,
+    animation_library: Res<AnimationLibrary>,
+    asset_server: Res<AssetServer>,
+    character_models: Query<(Entity, &CharacterAnimator), With<CharacterModel>>,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    transforms: Query<&GlobalTransform>,
+    mut run: ResMut<GoldenPoseRun>,
+    mut app_exit: MessageWriter<AppExit>,
+) {
+    if run.timeout.tick(time.delta()).is_finished() {
+        error!(
+            "Timed out after {}s on golden pose case {}",
+            GOLDEN_POSES_TIMEOUT_SECONDS, run.case
+        );
+        app_exit.write(AppExit::error());
+        return;
+    }
+
+    if run.poses.is_none() {
+        let (finished, total) = load_progress(
+            Some(&manifest),
+            Some(&folder),
+            &characters,
+            &animation_library,
+            &asset_server,
+        );
+        if finished < total {
+            return;
+        }
+
+        match read_golden_poses() {
+            Ok(poses) => {
+                info!("Running {} golden pose cases ...", poses.cases.len());
+                run.poses = Some(poses);
+                run.timeout.reset();
+            }
+            Err(err) => {
+                error!("{}", err);
+                app_exit.write(AppExit::error());
+                return;
+            }
+        }
+    }
+
+    let run = &mut *run;
+    let Some(poses) = run.poses.as_mut() else {
+        return;
+    };
+
+    let Some(case) = poses.cases.get_mut(run.case) else {
+        if !run.failures.is_empty() {
+            for failure in &run.failures {
+                error!("{}", failure);
+            }
+            error!(
+                "{} golden pose cases with {} failures",
+                poses.cases.len(),
+                run.failures.len()
+            );
+            app_exit.write(AppExit::error());
+        } else if run.update {
+            if let Err(err) = write_golden_poses(poses) {
+                error!("{}", err);
+                app_exit.write(AppExit::error());
+                return;
+            }
+            info!(
+                "Recorded {} golden pose cases to '{}'",
+                poses.cases.len(),
+                GOLDEN_POSES_PATH
+            );
+            app_exit.write(AppExit::Success);
+        } else {
+            info!("Matched {} golden pose cases", poses.cases.len());
+            app_exit.write(AppExit::Success);
+        }
+        return;
+    };
+    let case_name = format!("'{}' '{}'", case.character, case.animation);
+
+    let mut next_case = false;
+    match run.state {
+        GoldenPoseCaseState::Spawn => {
+            if characters.get(&case.character).is_none() {
+                run.failures
+                    .push(format!("{}: unknown character", case_name));
+                next_case = true;
+            } else {
+                commands.trigger(SpawnCharacter::new(case.character.clone()));
+                run.state = GoldenPoseCaseState::Spawning;
+            }
+        }
+        GoldenPoseCaseState::Spawning => {
+            // only one case's model is spawned at a time
+            let Ok((model, animator)) = character_models.single() else {
+                return;
+            };
+            let Some(character) = characters.get(&case.character) else {
+                return;
+            };
+
+            // no crossfade and from the start, whatever the default animation left behind
+            let animation = animation_players
+                .get_mut(animator.player_for(character, &case.animation))
+                .ok()
+                .and_then(|(mut player, mut transitions)| {
+                    play_animation_with_transition(
+                        &mut player,
+                        &mut transitions,
+                        character,
+                        &case.animation,
+                        Duration::ZERO,
+                    )
+                    .map(|animation| {
+                        animation.replay();
+                    })
+                });
+            if animation.is_none() {
+                run.failures
+                    .push(format!("{}: unknown animation", case_name));
+                commands.entity(model).despawn();
+                next_case = true;
+            }
+
+            run.state = GoldenPoseCaseState::Playing { model, steps: 0 };
+        }
+        GoldenPoseCaseState::Playing {
+            model,
+            ref mut steps,
+        } => {
+            // one animation step a frame
+            *steps += 1;
+            if *steps < case.steps.max(1) {
+                return;
+            }
+
+            let sampled = sample_bones(model, &case.bones, &children, &names, &transforms);
+            for bone in &case.bones {
+                let Some(pose) = sampled.get(bone).copied().flatten() else {
+                    run.failures
+                        .push(format!("{}: no bone '{}'", case_name, bone));
+                    continue;
+                };
+
+                if run.update {
+                    case.expected.insert(bone.clone(), pose);
+                    continue;
+                }
+
+                match case.expected.get(bone) {
+                    Some(expected) => {
+                        if let Some(difference) = expected.compare(&pose, poses.tolerance) {
+                            run.failures
+                                .push(format!("{}: bone '{}' {}", case_name, bone, difference));
+                        }
+                    }
+                    None => run.failures.push(format!(
+                        "{}: no expected pose for bone '{}', run with --golden-poses-update",
+                        case_name, bone
+                    )),
+                }
+            }
+
+            info!(
+                "Sampled {} bones of {} after {} steps",
+                case.bones.len(),
+                case_name,
+                steps
+            );
+
+            commands.entity(model).despawn();
+            next_case = true;
+        }
+    }
+
+    if next_case {
+        run.case += 1;
+        run.state = GoldenPoseCaseState::Spawn;
+        run.timeout.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // cargo test runs from the package root, where the assets and golden poses are
+    #[test]
+    fn golden_poses_match() {
+        let app_exit = App::new()
+            .add_plugins(GoldenPosePlugin { update: false })
+            .run();
+        assert_eq!(app_exit, AppExit::Success);
+    }
+}
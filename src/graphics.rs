@@ -0,0 +1,373 @@
+use bevy::{
+    camera::{RenderTarget, visibility::RenderLayers},
+    image::{BevyDefault, ToExtents},
+    light::DirectionalLightShadowMap,
+    prelude::*,
+    render::{render_resource::TextureFormat, renderer::RenderAdapter},
+    ui::{UiSystems, widget::ViewportNode},
+    window::{PresentMode, PrimaryWindow, WindowRef},
+};
+
+use crate::dropdown::{HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
+use crate::focus::*;
+use crate::tooltip::*;
+
+pub struct GraphicsSettingsPlugin;
+
+impl Plugin for GraphicsSettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GraphicsSettings>()
+            .add_systems(Startup, setup_graphics_panel)
+            .add_systems(
+                Update,
+                (
+                    handle_vsync_key,
+                    handle_graphics_buttons,
+                    apply_graphics_settings.run_if(resource_changed::<GraphicsSettings>),
+                    update_graphics_labels,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                PostUpdate,
+                scale_viewport_render_target.after(UiSystems::PostLayout),
+            );
+    }
+}
+
+// shadows are turned on and off with the lighting panel
+#[derive(Resource)]
+pub struct GraphicsSettings {
+    pub msaa: Msaa,
+
+    // width and height of each shadow cascade, a power of two
+    pub shadow_map_size: usize,
+
+    // fraction of the window size the 3d camera renders at
+    pub resolution_scale: f32,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            msaa: Msaa::default(),
+            shadow_map_size: DirectionalLightShadowMap::default().size,
+            resolution_scale: 1.0,
+        }
+    }
+}
+
+const MSAA_LEVELS: [Msaa; 4] = [Msaa::Off, Msaa::Sample2, Msaa::Sample4, Msaa::Sample8];
+
+const SHADOW_MAP_SIZES: [usize; 4] = [512, 1024, 2048, 4096];
+
+const RESOLUTION_SCALES: [f32; 4] = [1.0, 0.75, 0.5, 0.25];
+
+// the value after current, wrapping around
+fn next<T: Copy + PartialEq>(values: &[T], current: T) -> T {
+    let index = values.iter().position(|value| *value == current);
+    values[index.map_or(0, |index| (index + 1) % values.len())]
+}
+
+// 2x and 8x aren't supported by every adapter, so those are skipped if they'd fail
+fn next_msaa(current: Msaa, adapter: Option<&RenderAdapter>) -> Msaa {
+    let mut msaa = next(&MSAA_LEVELS, current);
+    if let Some(adapter) = adapter {
+        let flags = adapter
+            .get_texture_format_features(TextureFormat::bevy_default())
+            .flags;
+        while msaa != Msaa::Off && !flags.sample_count_supported(msaa.samples()) {
+            msaa = next(&MSAA_LEVELS, msaa);
+        }
+    }
+    msaa
+}
+
+pub fn is_vsync(present_mode: PresentMode) -> bool {
+    matches!(
+        present_mode,
+        PresentMode::AutoVsync | PresentMode::Fifo | PresentMode::FifoRelaxed
+    )
+}
+
+// switches between AutoVsync and AutoNoVsync, to compare how smooth animations are under each
+fn toggle_vsync(window: &mut Window) {
+    window.present_mode = if is_vsync(window.present_mode) {
+        PresentMode::AutoNoVsync
+    } else {
+        PresentMode::AutoVsync
+    };
+    info!("Present mode {:?}", window.present_mode);
+}
+
+// holds the graphics buttons, it's placed in the settings panel
+#[derive(Component)]
+pub struct GraphicsPanel;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum GraphicsButton {
+    Vsync,
+    Msaa,
+    ShadowMap,
+    ResolutionScale,
+}
+
+impl GraphicsButton {
+    fn label(&self, settings: &GraphicsSettings, window: &Window) -> String {
+        match self {
+            Self::Vsync if is_vsync(window.present_mode) => "Vsync: on".to_string(),
+            Self::Vsync => "Vsync: off".to_string(),
+            Self::Msaa if settings.msaa == Msaa::Off => "MSAA: off".to_string(),
+            Self::Msaa => format!("MSAA: {}x", settings.msaa.samples()),
+            Self::ShadowMap => format!("Shadow map: {}", settings.shadow_map_size),
+            Self::ResolutionScale => {
+                format!("Resolution: {:.0}%", settings.resolution_scale * 100.0)
+            }
+        }
+    }
+
+    fn tooltip(&self) -> &'static str {
+        match self {
+            Self::Vsync => "Toggle vsync (V)",
+            Self::Msaa => "Cycle the MSAA sample count",
+            Self::ShadowMap => "Cycle the shadow map resolution",
+            Self::ResolutionScale => "Cycle the 3d render resolution",
+        }
+    }
+}
+
+// the window camera that displays the 3d camera's image when the resolution is scaled
+#[derive(Component)]
+struct ResolutionScaleCamera;
+
+#[derive(Component)]
+pub struct ResolutionScaleViewport;
+
+type GraphicsButtonInteractionQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Interaction,
+        &'static GraphicsButton,
+        &'static mut BackgroundColor,
+    ),
+    Changed<Interaction>,
+>;
+
+fn setup_graphics_panel(
+    mut commands: Commands,
+    settings: Res<GraphicsSettings>,
+    window: Single<&Window, With<PrimaryWindow>>,
+) {
+    let panel = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            Name::new("GraphicsPanel"),
+            GraphicsPanel,
+        ))
+        .id();
+
+    for button in [
+        GraphicsButton::Vsync,
+        GraphicsButton::ResolutionScale,
+        GraphicsButton::Msaa,
+        GraphicsButton::ShadowMap,
+    ] {
+        commands
+            .spawn((
+                Button,
+                Node {
+                    width: Val::Px(150.0),
+                    height: Val::Px(50.0),
+                    border: UiRect::all(Val::Px(2.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BorderColor::all(Color::BLACK),
+                BackgroundColor(NORMAL_BUTTON),
+                Name::new("GraphicsButton"),
+                Tooltip::new(button.tooltip()),
+                Focusable,
+                button,
+                ChildOf(panel),
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(button.label(&settings, &window)),
+                    TextFont {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                ));
+            });
+    }
+}
+
+// V toggles vsync
+fn handle_vsync_key(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut window: Single<&mut Window, With<PrimaryWindow>>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyV) {
+        toggle_vsync(&mut window);
+    }
+}
+
+fn handle_graphics_buttons(
+    mut settings: ResMut<GraphicsSettings>,
+    adapter: Option<Res<RenderAdapter>>,
+    mut window: Single<&mut Window, With<PrimaryWindow>>,
+    mut buttons: GraphicsButtonInteractionQuery,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+                match button {
+                    GraphicsButton::Vsync => toggle_vsync(&mut window),
+                    GraphicsButton::Msaa => {
+                        settings.msaa = next_msaa(settings.msaa, adapter.as_deref());
+                    }
+                    GraphicsButton::ShadowMap => {
+                        settings.shadow_map_size =
+                            next(&SHADOW_MAP_SIZES, settings.shadow_map_size);
+                    }
+                    GraphicsButton::ResolutionScale => {
+                        settings.resolution_scale =
+                            next(&RESOLUTION_SCALES, settings.resolution_scale);
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_graphics_settings(
+    mut commands: Commands,
+    settings: Res<GraphicsSettings>,
+    mut images: ResMut<Assets<Image>>,
+    camera: Single<Entity, With<Camera3d>>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    resolution_scale_cameras: Query<Entity, With<ResolutionScaleCamera>>,
+    viewports: Query<Entity, With<ResolutionScaleViewport>>,
+) {
+    commands.entity(*camera).insert(settings.msaa);
+    commands.insert_resource(DirectionalLightShadowMap {
+        size: settings.shadow_map_size,
+    });
+
+    if settings.resolution_scale >= 1.0 {
+        // render straight to the window again
+        for entity in resolution_scale_cameras.iter().chain(&viewports) {
+            commands.entity(entity).despawn();
+        }
+        commands
+            .entity(*camera)
+            .insert(RenderTarget::Window(WindowRef::Primary));
+        return;
+    }
+
+    if !viewports.is_empty() {
+        // already rendering to an image, scale_viewport_render_target resizes it
+        return;
+    }
+
+    // the 3d camera renders to an image that's stretched over the window,
+    // the ViewportNode also forwards pointer input to it so picking characters still works
+    let size = (window.resolution.physical_size().as_vec2() * settings.resolution_scale)
+        .as_uvec2()
+        .max(UVec2::ONE);
+    let image = images.add(Image::new_target_texture(
+        size.x,
+        size.y,
+        TextureFormat::bevy_default(),
+        None,
+    ));
+    commands
+        .entity(*camera)
+        .insert(RenderTarget::Image(image.into()));
+
+    // on its own render layer so mesh picking ignores it
+    commands.spawn((
+        Camera2d,
+        Camera {
+            order: 1,
+            ..default()
+        },
+        Msaa::Off,
+        RenderLayers::layer(1),
+        Name::new("ResolutionScaleCamera"),
+        ResolutionScaleCamera,
+    ));
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        ViewportNode::new(*camera),
+        GlobalZIndex(-1),
+        Name::new("ResolutionScaleViewport"),
+        ResolutionScaleViewport,
+    ));
+}
+
+// ViewportNode sizes its image to the node, this shrinks it back down after it does
+fn scale_viewport_render_target(
+    settings: Res<GraphicsSettings>,
+    viewports: Query<(&ViewportNode, &ComputedNode), With<ResolutionScaleViewport>>,
+    render_targets: Query<&RenderTarget>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (viewport, computed_node) in &viewports {
+        let Some(image) = render_targets
+            .get(viewport.camera)
+            .ok()
+            .and_then(RenderTarget::as_image)
+        else {
+            continue;
+        };
+
+        let size = (computed_node.size() * settings.resolution_scale)
+            .as_uvec2()
+            .max(UVec2::ONE);
+        if images.get(image).is_some_and(|image| image.size() != size)
+            && let Some(image) = images.get_mut(image)
+        {
+            image.resize(size.to_extents());
+        }
+    }
+}
+
+fn update_graphics_labels(
+    settings: Res<GraphicsSettings>,
+    window: Single<Ref<Window>, With<PrimaryWindow>>,
+    buttons: Query<(&GraphicsButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    if !settings.is_changed() && !window.is_changed() {
+        return;
+    }
+
+    for (button, children) in &buttons {
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                **text = button.label(&settings, &window);
+            }
+        }
+    }
+}
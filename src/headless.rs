@@ -0,0 +1,163 @@
+use std::time::Duration;
+
+use bevy::{
+    app::{PluginGroupBuilder, ScheduleRunnerPlugin},
+    prelude::*,
+    render::{
+        RenderPlugin,
+        settings::{RenderCreation, WgpuSettings},
+    },
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+use bevy_mixamo::*;
+
+use crate::loading::*;
+
+// loads every character without a window or renderer, reports any failures and exits,
+// with a non-zero code if anything failed, so that broken character assets fail CI
+// run with --headless-validate
+pub struct HeadlessValidatePlugin;
+
+impl Plugin for HeadlessValidatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(headless_plugins())
+            .add_plugins(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+                1.0 / 60.0,
+            )))
+            .add_plugins(CharacterPlugin)
+            .init_resource::<HeadlessValidation>()
+            .add_systems(Startup, load_all_characters)
+            .add_systems(Update, check_headless_validation)
+            .add_observer(record_character_load_error)
+            .add_observer(record_character_validation_error);
+    }
+}
+
+// DefaultPlugins without a window or renderer, add a ScheduleRunnerPlugin to drive the app
+pub fn headless_plugins() -> PluginGroupBuilder {
+    DefaultPlugins
+        .set(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            ..default()
+        })
+        .set(RenderPlugin {
+            render_creation: RenderCreation::Automatic(WgpuSettings {
+                backends: None,
+                ..default()
+            }),
+            ..default()
+        })
+        .disable::<WinitPlugin>()
+}
+
+// gives up on assets that never finish loading
+const HEADLESS_VALIDATE_TIMEOUT_SECONDS: f32 = 60.0;
+
+#[derive(Resource)]
+struct HeadlessValidation {
+    errors: Vec<String>,
+    timeout: Timer,
+}
+
+impl Default for HeadlessValidation {
+    fn default() -> Self {
+        Self {
+            errors: Vec::new(),
+            timeout: Timer::from_seconds(HEADLESS_VALIDATE_TIMEOUT_SECONDS, TimerMode::Once),
+        }
+    }
+}
+
+pub fn load_all_characters(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CharacterManifestHandle::load(
+        "characters/manifest.json",
+        &asset_server,
+    ));
+    commands.insert_resource(CharacterFolderHandle::load("characters", &asset_server));
+}
+
+fn record_character_load_error(
+    event: On<CharacterLoadError>,
+    mut validation: ResMut<HeadlessValidation>,
+) {
+    validation.errors.push(format!(
+        "Character '{}' failed to load '{}': {}",
+        event.id, event.path, event.error
+    ));
+}
+
+fn record_character_validation_error(
+    event: On<CharacterValidationError>,
+    mut validation: ResMut<HeadlessValidation>,
+) {
+    validation.errors.push(format!(
+        "Character '{}' ('{}'): {}",
+        event.id, event.path, event.error
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn check_headless_validation(
+    time: Res<Time>,
+    manifest: Res<CharacterManifestHandle>,
+    folder: Res<CharacterFolderHandle>,
+    characters: Res<Characters>,
+    animation_library: Res<AnimationLibrary>,
+    asset_server: Res<AssetServer>,
+    mut validation: ResMut<HeadlessValidation>,
+    mut app_exit: MessageWriter<AppExit>,
+) {
+    let (finished, total) = load_progress(
+        Some(&manifest),
+        Some(&folder),
+        &characters,
+        &animation_library,
+        &asset_server,
+    );
+
+    if finished < total {
+        if validation.timeout.tick(time.delta()).is_finished() {
+            error!(
+                "Timed out after {}s with {} / {} assets loaded",
+                HEADLESS_VALIDATE_TIMEOUT_SECONDS, finished, total
+            );
+            app_exit.write(AppExit::error());
+        }
+        return;
+    }
+
+    // failed manifests and folder scans don't trigger a CharacterLoadError
+    if asset_server.load_state(manifest.handle()).is_failed() {
+        validation
+            .errors
+            .push("Failed to load the character manifest".to_string());
+    }
+    if asset_server.load_state(folder.handle()).is_failed() {
+        validation
+            .errors
+            .push("Failed to scan the characters folder".to_string());
+    }
+
+    let mut ids = characters.ids().collect::<Vec<_>>();
+    ids.sort();
+    for id in &ids {
+        info!("Loaded character '{}'", id);
+    }
+
+    if validation.errors.is_empty() {
+        info!("Validated {} characters", ids.len());
+        app_exit.write(AppExit::Success);
+    } else {
+        for error in &validation.errors {
+            error!("{}", error);
+        }
+        error!(
+            "Validated {} characters with {} errors",
+            ids.len(),
+            validation.errors.len()
+        );
+        app_exit.write(AppExit::error());
+    }
+}
@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, scene::SceneInstanceReady};
+use serde::Deserialize;
+
+use crate::character::*;
+use crate::retarget::*;
+
+// foot placement settings, loaded as part of the character data
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct FootIkData {
+    #[serde(default = "default_ik_legs")]
+    pub legs: Vec<IkLegData>,
+
+    // height of the ankle above the sole,
+    // feet are kept at least this far above the ground
+    #[serde(default)]
+    pub foot_height: f32,
+}
+
+// a hip -> knee -> ankle chain, each bone must be the parent of the next
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct IkLegData {
+    pub upper: String,
+    pub lower: String,
+    pub foot: String,
+}
+
+fn default_ik_legs() -> Vec<IkLegData> {
+    ["Left", "Right"]
+        .into_iter()
+        .map(|side| IkLegData {
+            upper: format!("{MIXAMO_BONE_PREFIX}{side}UpLeg"),
+            lower: format!("{MIXAMO_BONE_PREFIX}{side}Leg"),
+            foot: format!("{MIXAMO_BONE_PREFIX}{side}Foot"),
+        })
+        .collect()
+}
+
+// added to the character model when its data enables foot IK
+// the animations assume flat ground at the model's height,
+// the feet are moved by however far the ground actually is from that
+#[derive(Debug, Component, Reflect)]
+#[reflect(Component)]
+pub struct FootIk {
+    pub enabled: bool,
+
+    // world space height of the ground plane
+    pub ground_height: f32,
+
+    // per-foot ground heights (e.g. from a raycast under each foot),
+    // keyed by foot bone name, feet without one use the ground plane
+    pub foot_ground_heights: HashMap<String, f32>,
+}
+
+impl Default for FootIk {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ground_height: 0.0,
+            foot_ground_heights: HashMap::new(),
+        }
+    }
+}
+
+impl FootIk {
+    pub fn ground_height(&self, foot: impl AsRef<str>) -> f32 {
+        self.foot_ground_heights
+            .get(foot.as_ref())
+            .copied()
+            .unwrap_or(self.ground_height)
+    }
+}
+
+#[derive(Debug)]
+struct IkLeg {
+    foot_name: String,
+    upper: Entity,
+    lower: Entity,
+    foot: Entity,
+}
+
+#[derive(Debug, Component)]
+pub(crate) struct FootIkLegs {
+    legs: Vec<IkLeg>,
+    foot_height: f32,
+}
+
+// finds the model's leg bones once the scene spawns
+pub(crate) fn setup_foot_ik(
+    scene_ready: On<SceneInstanceReady>,
+    mut commands: Commands,
+    character_datum: Res<Assets<CharacterData>>,
+    character_models: Query<&CharacterModel>,
+    children: Query<&Children>,
+    parents: Query<&ChildOf>,
+    names: Query<&Name>,
+) {
+    let Ok(character_model) = character_models.get(scene_ready.entity) else {
+        return;
+    };
+    let Some(character_data) = character_datum.get(&character_model.0) else {
+        return;
+    };
+    let Some(foot_ik_data) = &character_data.foot_ik else {
+        return;
+    };
+
+    let find_bone = |bone: &str| find_bone(scene_ready.entity, bone, &children, &names);
+    let is_parent = |parent: Entity, child: Entity| {
+        parents
+            .get(child)
+            .is_ok_and(|child_of| child_of.parent() == parent)
+    };
+
+    let mut legs = Vec::new();
+    for leg in &foot_ik_data.legs {
+        let (Some(upper), Some(lower), Some(foot)) = (
+            find_bone(&leg.upper),
+            find_bone(&leg.lower),
+            find_bone(&leg.foot),
+        ) else {
+            warn!(
+                "Character '{}' is missing foot IK bones '{}', '{}', '{}'",
+                character_data.id, leg.upper, leg.lower, leg.foot
+            );
+            continue;
+        };
+
+        if !is_parent(upper, lower) || !is_parent(lower, foot) {
+            warn!(
+                "Character '{}' foot IK bones '{}', '{}', '{}' aren't a chain",
+                character_data.id, leg.upper, leg.lower, leg.foot
+            );
+            continue;
+        }
+
+        legs.push(IkLeg {
+            foot_name: leg.foot.clone(),
+            upper,
+            lower,
+            foot,
+        });
+    }
+
+    commands.entity(scene_ready.entity).insert((
+        FootIk::default(),
+        FootIkLegs {
+            legs,
+            foot_height: foot_ik_data.foot_height,
+        },
+    ));
+}
+
+// transforms haven't propagated yet this frame, so build the global transform from the local ones
+pub(crate) fn compute_global_transform(
+    entity: Entity,
+    parents: &Query<&ChildOf>,
+    transforms: &Query<&mut Transform>,
+) -> GlobalTransform {
+    let local = transforms
+        .get(entity)
+        .map(|transform| GlobalTransform::from(*transform))
+        .unwrap_or_default();
+    match parents.get(entity) {
+        Ok(child_of) => compute_global_transform(child_of.parent(), parents, transforms) * local,
+        Err(_) => local,
+    }
+}
+
+// analytic two-bone IK, rotates the chain a -> b -> c so that c reaches the target
+// (or as close as it can) returning the local rotations to apply to a and b
+// a_rotation and b_rotation are the bones' global rotations
+pub fn solve_two_bone_ik(
+    a: Vec3,
+    b: Vec3,
+    c: Vec3,
+    target: Vec3,
+    a_rotation: Quat,
+    b_rotation: Quat,
+) -> (Quat, Quat) {
+    const EPSILON: f32 = 0.001;
+
+    let angle = |from: Vec3, to: Vec3| {
+        from.normalize_or_zero()
+            .dot(to.normalize_or_zero())
+            .clamp(-1.0, 1.0)
+            .acos()
+    };
+
+    let ab_length = a.distance(b);
+    let bc_length = b.distance(c);
+    let at_length = a
+        .distance(target)
+        .clamp(EPSILON, ab_length + bc_length - EPSILON);
+
+    // current angles
+    let ac_ab = angle(c - a, b - a);
+    let ba_bc = angle(a - b, c - b);
+
+    // angles that put c at the target distance from a (law of cosines)
+    let ac_ab_target = ((bc_length * bc_length - ab_length * ab_length - at_length * at_length)
+        / (-2.0 * ab_length * at_length))
+        .clamp(-1.0, 1.0)
+        .acos();
+    let ba_bc_target = ((at_length * at_length - ab_length * ab_length - bc_length * bc_length)
+        / (-2.0 * ab_length * bc_length))
+        .clamp(-1.0, 1.0)
+        .acos();
+
+    // bend around the plane of the chain
+    let bend_axis = (c - a).cross(b - a).normalize_or_zero();
+    if bend_axis == Vec3::ZERO {
+        // a straight chain has no plane to bend in
+        return (Quat::IDENTITY, Quat::IDENTITY);
+    }
+    let a_bend = Quat::from_axis_angle(bend_axis, ac_ab_target - ac_ab);
+    let b_bend = Quat::from_axis_angle(bend_axis, ba_bc_target - ba_bc);
+
+    // then swing the bent chain towards the target
+    let bent_c = a + a_bend * (b - a) + a_bend * b_bend * (c - b);
+    let swing = Quat::from_rotation_arc(
+        (bent_c - a).normalize_or_zero(),
+        (target - a).normalize_or_zero(),
+    );
+
+    // global rotations to local ones
+    (
+        a_rotation.inverse() * swing * a_bend * a_rotation,
+        b_rotation.inverse() * b_bend * b_rotation,
+    )
+}
+
+pub(crate) fn solve_foot_ik(
+    models: Query<(Entity, &FootIk, &FootIkLegs)>,
+    parents: Query<&ChildOf>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for (entity, foot_ik, legs) in &models {
+        if !foot_ik.enabled {
+            continue;
+        }
+
+        let model_height = compute_global_transform(entity, &parents, &transforms)
+            .translation()
+            .y;
+
+        for leg in &legs.legs {
+            let (Ok(upper), Ok(lower), Ok(foot)) = (
+                transforms.get(leg.upper).copied(),
+                transforms.get(leg.lower).copied(),
+                transforms.get(leg.foot).copied(),
+            ) else {
+                continue;
+            };
+
+            let parent_global = match parents.get(leg.upper) {
+                Ok(child_of) => compute_global_transform(child_of.parent(), &parents, &transforms),
+                Err(_) => GlobalTransform::IDENTITY,
+            };
+            let upper_global = parent_global * upper;
+            let lower_global = upper_global * lower;
+            let foot_global = lower_global * foot;
+
+            let ground_height = foot_ik.ground_height(&leg.foot_name);
+            let ankle = foot_global.translation();
+            let mut target = ankle + Vec3::Y * (ground_height - model_height);
+            target.y = target.y.max(ground_height + legs.foot_height);
+            if target.distance_squared(ankle) < f32::EPSILON {
+                continue;
+            }
+
+            let (upper_rotation, lower_rotation) = solve_two_bone_ik(
+                upper_global.translation(),
+                lower_global.translation(),
+                ankle,
+                target,
+                upper_global.rotation(),
+                lower_global.rotation(),
+            );
+
+            let upper = upper.with_rotation(upper.rotation * upper_rotation);
+            let lower = lower.with_rotation(lower.rotation * lower_rotation);
+
+            // keep the foot's world orientation so it doesn't tilt with the leg
+            let lower_rotation = (parent_global * upper * lower).rotation();
+            let foot_rotation = lower_rotation.inverse() * foot_global.rotation();
+
+            transforms.get_mut(leg.upper).unwrap().rotation = upper.rotation;
+            transforms.get_mut(leg.lower).unwrap().rotation = lower.rotation;
+            transforms.get_mut(leg.foot).unwrap().rotation = foot_rotation;
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use bevy::{input::common_conditions::input_toggle_active, prelude::*};
+use bevy_inspector_egui::{bevy_egui::EguiPlugin, quick::WorldInspectorPlugin};
+
+// an egui inspector for entities (e.g. bone transforms), assets and resources
+// F1 shows / hides it
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<EguiPlugin>() {
+            app.add_plugins(EguiPlugin::default());
+        }
+        app.add_plugins(
+            WorldInspectorPlugin::new().run_if(input_toggle_active(false, KeyCode::F1)),
+        );
+    }
+}
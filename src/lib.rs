@@ -0,0 +1,68 @@
+mod animation_library;
+mod animation_lod;
+mod animation_marker;
+pub mod asset_event_bridge;
+mod attachment;
+mod blend_space;
+mod bone_mask;
+mod character;
+#[cfg(feature = "physics")]
+mod character_capsule;
+mod controller;
+mod diagnostics;
+mod discovery;
+mod fixed_animation;
+mod ik;
+mod look_at;
+mod loop_mode;
+mod manifest;
+mod marker_audio;
+mod material_variant;
+mod memory;
+mod model_lod;
+mod morph_target;
+mod particle;
+#[cfg(feature = "physics")]
+mod ragdoll;
+mod retarget;
+mod root_motion;
+mod sequence;
+mod spring_bone;
+mod state_machine;
+mod thumbnail;
+mod validation;
+mod vertex_animation;
+
+pub use animation_library::*;
+pub use animation_lod::*;
+pub use animation_marker::*;
+pub use attachment::*;
+pub use blend_space::*;
+pub use bone_mask::*;
+pub use character::*;
+#[cfg(feature = "physics")]
+pub use character_capsule::*;
+pub use controller::*;
+pub use diagnostics::*;
+pub use discovery::*;
+pub use fixed_animation::*;
+pub use ik::*;
+pub use look_at::*;
+pub use loop_mode::*;
+pub use manifest::*;
+pub use marker_audio::*;
+pub use material_variant::*;
+pub use memory::*;
+pub use model_lod::*;
+pub use morph_target::*;
+pub use particle::*;
+#[cfg(feature = "physics")]
+pub use ragdoll::*;
+pub use retarget::*;
+pub use root_motion::*;
+pub use sequence::*;
+pub use spring_bone::*;
+pub use state_machine::*;
+pub use thumbnail::*;
+pub use validation::*;
+pub use vertex_animation::*;
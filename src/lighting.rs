@@ -0,0 +1,258 @@
+use bevy::prelude::*;
+
+use crate::dropdown::{HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
+use crate::focus::*;
+use crate::slider::*;
+use crate::tooltip::*;
+
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LightingSettings>()
+            .add_systems(Startup, setup_lighting_panel)
+            .add_systems(
+                Update,
+                (
+                    handle_shadows_button,
+                    (apply_lighting_settings, update_shadows_label)
+                        .run_if(resource_changed::<LightingSettings>),
+                )
+                    .chain(),
+            )
+            .add_observer(handle_lighting_sliders);
+    }
+}
+
+// applied to the directional light and the camera's ambient light
+#[derive(Resource)]
+pub struct LightingSettings {
+    // directional light angles, in degrees
+    pub pitch: f32,
+    pub yaw: f32,
+
+    pub illuminance: f32,
+    pub ambient_brightness: f32,
+    pub shadows: bool,
+}
+
+impl Default for LightingSettings {
+    fn default() -> Self {
+        Self {
+            pitch: 45.0,
+            yaw: 45.0,
+            illuminance: 10_000.0,
+            ambient_brightness: 200.0,
+            shadows: true,
+        }
+    }
+}
+
+impl LightingSettings {
+    fn shadows_label(&self) -> &'static str {
+        if self.shadows {
+            "Shadows: on"
+        } else {
+            "Shadows: off"
+        }
+    }
+}
+
+// holds the lighting controls, it's placed in the settings panel
+#[derive(Component)]
+pub struct LightingPanel;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum LightingSlider {
+    Pitch,
+    Yaw,
+    Illuminance,
+    AmbientBrightness,
+}
+
+impl LightingSlider {
+    fn label(&self, value: f32) -> String {
+        match self {
+            Self::Pitch => format!("light pitch: {value:.0}°"),
+            Self::Yaw => format!("light yaw: {value:.0}°"),
+            Self::Illuminance => format!("illuminance: {value:.0} lux"),
+            Self::AmbientBrightness => format!("ambient: {value:.0}"),
+        }
+    }
+
+    fn range(&self) -> (f32, f32) {
+        match self {
+            Self::Pitch => (0.0, 90.0),
+            Self::Yaw => (-180.0, 180.0),
+            Self::Illuminance => (0.0, 50_000.0),
+            Self::AmbientBrightness => (0.0, 2_000.0),
+        }
+    }
+
+    fn value(&self, settings: &LightingSettings) -> f32 {
+        match self {
+            Self::Pitch => settings.pitch,
+            Self::Yaw => settings.yaw,
+            Self::Illuminance => settings.illuminance,
+            Self::AmbientBrightness => settings.ambient_brightness,
+        }
+    }
+
+    fn set_value(&self, settings: &mut LightingSettings, value: f32) {
+        match self {
+            Self::Pitch => settings.pitch = value,
+            Self::Yaw => settings.yaw = value,
+            Self::Illuminance => settings.illuminance = value,
+            Self::AmbientBrightness => settings.ambient_brightness = value,
+        }
+    }
+}
+
+#[derive(Component)]
+struct ShadowsButton;
+
+type ShadowsButtonInteractionQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Interaction, &'static mut BackgroundColor),
+    (Changed<Interaction>, With<ShadowsButton>),
+>;
+
+fn setup_lighting_panel(mut commands: Commands, settings: Res<LightingSettings>) {
+    let panel = commands
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            Name::new("LightingPanel"),
+            LightingPanel,
+        ))
+        .id();
+
+    for slider in [
+        LightingSlider::Pitch,
+        LightingSlider::Yaw,
+        LightingSlider::Illuminance,
+        LightingSlider::AmbientBrightness,
+    ] {
+        let (min, max) = slider.range();
+        let value = slider.value(&settings);
+        spawn_slider(
+            &mut commands,
+            Vec2::ZERO,
+            Vec2::new(200.0, 20.0),
+            slider.label(value),
+            min,
+            max,
+            value,
+        )
+        .insert((Name::new("LightingSlider"), slider, ChildOf(panel)));
+    }
+
+    commands
+        .spawn((
+            Button,
+            Node {
+                width: Val::Px(150.0),
+                height: Val::Px(50.0),
+                border: UiRect::all(Val::Px(2.0)),
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BorderColor::all(Color::BLACK),
+            BackgroundColor(NORMAL_BUTTON),
+            Name::new("ShadowsButton"),
+            Tooltip::new("Toggle the directional light's shadows"),
+            Focusable,
+            ShadowsButton,
+            ChildOf(panel),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(settings.shadows_label()),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+        });
+}
+
+fn handle_lighting_sliders(
+    event: On<SliderChanged>,
+    mut settings: ResMut<LightingSettings>,
+    sliders: Query<(&LightingSlider, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    let Ok((slider, children)) = sliders.get(event.entity) else {
+        return;
+    };
+
+    slider.set_value(&mut settings, event.value);
+
+    // update the label
+    for child in children {
+        if let Ok(mut text) = texts.get_mut(*child) {
+            **text = slider.label(event.value);
+        }
+    }
+}
+
+fn handle_shadows_button(
+    mut settings: ResMut<LightingSettings>,
+    mut buttons: ShadowsButtonInteractionQuery,
+) {
+    for (interaction, mut color) in &mut buttons {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+                settings.shadows = !settings.shadows;
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+fn update_shadows_label(
+    settings: Res<LightingSettings>,
+    buttons: Query<&Children, With<ShadowsButton>>,
+    mut texts: Query<&mut Text>,
+) {
+    for children in &buttons {
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                **text = settings.shadows_label().to_string();
+            }
+        }
+    }
+}
+
+fn apply_lighting_settings(
+    settings: Res<LightingSettings>,
+    mut directional_lights: Query<(&mut DirectionalLight, &mut Transform)>,
+    mut ambient_lights: Query<&mut AmbientLight>,
+) {
+    for (mut light, mut transform) in &mut directional_lights {
+        light.illuminance = settings.illuminance;
+        light.shadows_enabled = settings.shadows;
+        transform.rotation = Quat::from_euler(
+            EulerRot::XYZ,
+            -settings.pitch.to_radians(),
+            settings.yaw.to_radians(),
+            0.0,
+        );
+    }
+
+    for mut ambient_light in &mut ambient_lights {
+        ambient_light.brightness = settings.ambient_brightness;
+    }
+}
@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use bevy_mixamo::*;
+
+use crate::AppState;
+use crate::toast::*;
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Loading), setup_loading_screen)
+            .add_systems(
+                Update,
+                update_loading_screen.run_if(in_state(AppState::Loading)),
+            );
+    }
+}
+
+#[derive(Component)]
+struct LoadingProgressBar;
+
+#[derive(Component)]
+struct LoadingProgressText;
+
+fn setup_loading_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.05, 0.05, 0.05)),
+            // cover the rest of the UI while loading
+            GlobalZIndex(100),
+            DespawnOnExit(AppState::Loading),
+            Name::new("LoadingScreen"),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Loading ..."),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                LoadingProgressText,
+            ));
+
+            // progress bar
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(400.0),
+                        height: Val::Px(20.0),
+                        border: UiRect::all(Val::Px(2.0)),
+                        ..default()
+                    },
+                    BorderColor::all(Color::WHITE),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Node {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.35, 0.75, 0.35)),
+                        LoadingProgressBar,
+                    ));
+                });
+        });
+}
+
+// (finished, total) across the manifest, folder scan, characters and animation sets
+pub fn load_progress(
+    manifest: Option<&CharacterManifestHandle>,
+    folder: Option<&CharacterFolderHandle>,
+    characters: &Characters,
+    animation_library: &AnimationLibrary,
+    asset_server: &AssetServer,
+) -> (usize, usize) {
+    let (mut finished, mut total) = characters.load_progress(asset_server);
+
+    let (sets_finished, sets_total) = animation_library.load_progress(asset_server);
+    finished += sets_finished;
+    total += sets_total;
+
+    // the manifest and folder scan count as one more asset each
+    if let Some(manifest) = manifest {
+        finished += manifest.is_finished_loading(asset_server) as usize;
+        total += 1;
+    }
+    if let Some(folder) = folder {
+        finished += folder.is_finished_loading(asset_server) as usize;
+        total += 1;
+    }
+
+    (finished, total)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_loading_screen(
+    mut commands: Commands,
+    manifest: Option<Res<CharacterManifestHandle>>,
+    folder: Option<Res<CharacterFolderHandle>>,
+    characters: Res<Characters>,
+    animation_library: Res<AnimationLibrary>,
+    asset_server: Res<AssetServer>,
+    mut progress_bar: Single<&mut Node, With<LoadingProgressBar>>,
+    mut progress_text: Single<&mut Text, With<LoadingProgressText>>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let (finished, total) = load_progress(
+        manifest.as_deref(),
+        folder.as_deref(),
+        &characters,
+        &animation_library,
+        &asset_server,
+    );
+
+    progress_bar.width = Val::Percent(100.0 * finished as f32 / total.max(1) as f32);
+    ***progress_text = format!("Loading characters ... {finished} / {total}");
+
+    if finished == total {
+        info!("Finished loading {} assets", total);
+        commands.trigger(Toast::info(format!("Loaded {total} assets")));
+        next_state.set(AppState::Ready);
+    }
+}
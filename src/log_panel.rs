@@ -0,0 +1,214 @@
+use std::{collections::VecDeque, sync::mpsc};
+
+use bevy::{
+    log::{
+        BoxedLayer, Level,
+        tracing::{self, Subscriber},
+        tracing_subscriber::{self, Layer},
+    },
+    prelude::*,
+};
+
+use crate::scroll::*;
+
+pub struct LogPanelPlugin;
+
+impl Plugin for LogPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LogLines>()
+            .add_systems(Startup, setup_log_panel)
+            .add_systems(
+                Update,
+                (receive_log_lines, toggle_log_panel, update_log_panel).chain(),
+            );
+    }
+}
+
+// the most recent info, warn and error lines, oldest first
+#[derive(Debug, Default, Resource)]
+pub struct LogLines {
+    pub lines: VecDeque<LogLine>,
+
+    // every line ever received, including the ones dropped since
+    pub received: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+// older lines are dropped
+const MAX_LOG_LINES: usize = 200;
+
+// for LogPlugin::custom_layer, sends every info, warn and error line to LogLines
+pub fn capture_log_layer(app: &mut App) -> Option<BoxedLayer> {
+    let (sender, receiver) = mpsc::channel();
+    app.insert_non_send_resource(CapturedLogLines(receiver));
+
+    Some(LogCaptureLayer { sender }.boxed())
+}
+
+// lines can be logged from any thread, they're moved into LogLines on the main one
+struct CapturedLogLines(mpsc::Receiver<LogLine>);
+
+struct LogCaptureLayer {
+    sender: mpsc::Sender<LogLine>,
+}
+
+impl<S: Subscriber> Layer<S> for LogCaptureLayer {
+    fn on_event(
+        &self,
+        event: &tracing::Event<'_>,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        // more verbose levels compare greater
+        let metadata = event.metadata();
+        if *metadata.level() > Level::INFO {
+            return;
+        }
+
+        let mut message = None;
+        event.record(&mut LogMessageVisitor(&mut message));
+        let Some(message) = message else {
+            return;
+        };
+
+        // fails once the app (and the receiver) is gone, there's nothing left to show it then
+        let _ = self.sender.send(LogLine {
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message,
+        });
+    }
+}
+
+struct LogMessageVisitor<'a>(&'a mut Option<String>);
+
+impl tracing::field::Visit for LogMessageVisitor<'_> {
+    fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            *self.0 = Some(format!("{value:?}"));
+        }
+    }
+}
+
+// the scrolling panel at the bottom of the window, L toggles it
+#[derive(Component)]
+struct LogPanel;
+
+fn level_color(level: Level) -> Color {
+    match level {
+        Level::ERROR => Color::srgb(1.0, 0.35, 0.35),
+        Level::WARN => Color::srgb(1.0, 0.75, 0.25),
+        _ => Color::srgb(0.8, 0.8, 0.8),
+    }
+}
+
+fn setup_log_panel(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Percent(25.0),
+            width: Val::Percent(50.0),
+            max_height: Val::Percent(35.0),
+            flex_direction: FlexDirection::Column,
+            padding: UiRect::all(Val::Px(5.0)),
+            display: Display::None,
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.9)),
+        ScrollView::with_scrollbar(),
+        Name::new("LogPanel"),
+        LogPanel,
+    ));
+}
+
+// the layer isn't there when the log plugin is set up differently (e.g. headless)
+fn receive_log_lines(receiver: Option<NonSend<CapturedLogLines>>, mut log_lines: ResMut<LogLines>) {
+    let Some(receiver) = receiver else {
+        return;
+    };
+
+    for line in receiver.0.try_iter() {
+        log_lines.lines.push_back(line);
+        log_lines.received += 1;
+    }
+    while log_lines.lines.len() > MAX_LOG_LINES {
+        log_lines.lines.pop_front();
+    }
+}
+
+fn toggle_log_panel(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    panel: Single<(&mut Node, &mut ScrollPosition), With<LogPanel>>,
+) {
+    if !keyboard.just_pressed(KeyCode::KeyL) {
+        return;
+    }
+
+    let (mut node, mut scroll_position) = panel.into_inner();
+    node.display = match node.display {
+        Display::None => Display::Flex,
+        _ => Display::None,
+    };
+    // open on the latest lines
+    scroll_position.y = f32::MAX;
+}
+
+// adds a row per new line, following them while scrolled to the bottom
+fn update_log_panel(
+    mut commands: Commands,
+    mut shown: Local<usize>,
+    log_lines: Res<LogLines>,
+    panel: Single<
+        (
+            Entity,
+            &ComputedNode,
+            &mut ScrollPosition,
+            Option<&Children>,
+        ),
+        With<LogPanel>,
+    >,
+) {
+    if log_lines.received == *shown {
+        return;
+    }
+    let new_lines = (log_lines.received - *shown).min(log_lines.lines.len());
+    *shown = log_lines.received;
+
+    let (panel, computed, mut scroll_position, rows) = panel.into_inner();
+    if scroll_position.y >= max_scroll(computed) - 1.0 {
+        scroll_position.y = f32::MAX;
+    }
+
+    // the oldest rows go first
+    if let Some(rows) = rows {
+        let dropped = (rows.len() + new_lines).saturating_sub(MAX_LOG_LINES);
+        for row in rows.iter().take(dropped) {
+            commands.entity(row).despawn();
+        }
+    }
+
+    for line in log_lines
+        .lines
+        .iter()
+        .skip(log_lines.lines.len() - new_lines)
+    {
+        commands.spawn((
+            Text::new(format!(
+                "{:5} {}: {}",
+                line.level, line.target, line.message
+            )),
+            TextFont {
+                font_size: 14.0,
+                ..default()
+            },
+            TextColor(level_color(line.level)),
+            ChildOf(panel),
+        ));
+    }
+}
@@ -0,0 +1,168 @@
+use bevy::{math::Affine3A, prelude::*, scene::SceneInstanceReady};
+
+use crate::character::*;
+use crate::ik::*;
+use crate::retarget::*;
+
+// turns a character model's head towards a world space point
+// the rotation is layered over the animated pose
+#[derive(Debug, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct LookAtTarget {
+    pub target: Vec3,
+
+    pub head: String,
+
+    // spine bones that share the rotation with the head, from the hips up
+    pub spine: Vec<String>,
+
+    // the furthest (in radians) the head turns from the model's forward
+    pub max_angle: f32,
+
+    // the direction the model faces in its own space
+    pub forward: Vec3,
+
+    // the spine bones from the hips up and then the head,
+    // found when the model's scene spawns or the component is inserted
+    bones: Vec<Entity>,
+}
+
+impl LookAtTarget {
+    pub fn new(target: Vec3) -> Self {
+        Self {
+            target,
+            head: format!("{MIXAMO_BONE_PREFIX}Head"),
+            spine: Vec::new(),
+            max_angle: 60f32.to_radians(),
+            forward: Vec3::Z,
+            bones: Vec::new(),
+        }
+    }
+
+    pub fn with_spine(mut self, spine: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.spine = spine.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_max_angle(mut self, max_angle: f32) -> Self {
+        self.max_angle = max_angle;
+        self
+    }
+}
+
+// empty if the model doesn't have the head bone
+fn find_look_at_bones(
+    model: Entity,
+    look_at: &LookAtTarget,
+    children: &Query<&Children>,
+    names: &Query<&Name>,
+) -> Vec<Entity> {
+    let Some(head) = find_bone(model, &look_at.head, children, names) else {
+        return Vec::new();
+    };
+    let mut bones = look_at
+        .spine
+        .iter()
+        .filter_map(|bone| find_bone(model, bone, children, names))
+        .collect::<Vec<_>>();
+    bones.push(head);
+    bones
+}
+
+pub(crate) fn setup_look_at(
+    scene_ready: On<SceneInstanceReady>,
+    mut look_ats: Query<&mut LookAtTarget>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+) {
+    let Ok(mut look_at) = look_ats.get_mut(scene_ready.entity) else {
+        return;
+    };
+    look_at.bones = find_look_at_bones(scene_ready.entity, &look_at, &children, &names);
+}
+
+// targets added after the scene spawned
+pub(crate) fn find_inserted_look_at_bones(
+    event: On<Insert, LookAtTarget>,
+    mut look_ats: Query<&mut LookAtTarget>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+) {
+    let Ok(mut look_at) = look_ats.get_mut(event.entity) else {
+        return;
+    };
+    look_at.bones = find_look_at_bones(event.entity, &look_at, &children, &names);
+}
+
+// the global transform of entity, only walking up as far as an ancestor whose global transform is known
+fn global_transform_below(
+    entity: Entity,
+    known: Option<(Entity, GlobalTransform)>,
+    parents: &Query<&ChildOf>,
+    transforms: &Query<&mut Transform>,
+) -> GlobalTransform {
+    let mut local = Affine3A::IDENTITY;
+    let mut current = entity;
+    loop {
+        if let Some((ancestor, global)) = known
+            && ancestor == current
+        {
+            return GlobalTransform::from(global.affine() * local);
+        }
+        if let Ok(transform) = transforms.get(current) {
+            local = transform.compute_affine() * local;
+        }
+        match parents.get(current) {
+            Ok(child_of) => current = child_of.parent(),
+            Err(_) => return GlobalTransform::from(local),
+        }
+    }
+}
+
+pub(crate) fn solve_look_at(
+    models: Query<(Entity, &LookAtTarget), With<CharacterModel>>,
+    parents: Query<&ChildOf>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for (entity, look_at) in &models {
+        let Some(&head) = look_at.bones.last() else {
+            continue;
+        };
+
+        let model = compute_global_transform(entity, &parents, &transforms);
+        let head_position =
+            global_transform_below(head, Some((entity, model)), &parents, &transforms)
+                .translation();
+        let forward = (model.rotation() * look_at.forward).normalize_or_zero();
+        let direction = (look_at.target - head_position).normalize_or_zero();
+        if forward == Vec3::ZERO || direction == Vec3::ZERO {
+            continue;
+        }
+
+        let mut rotation = Quat::from_rotation_arc(forward, direction);
+        let (axis, angle) = rotation.to_axis_angle();
+        if angle > look_at.max_angle {
+            rotation = Quat::from_axis_angle(axis, look_at.max_angle);
+        }
+
+        // each bone turns an equal share, children inherit their parent's turn
+        // the bones run from the hips up so the last turned bone is usually an ancestor
+        let share = Quat::IDENTITY.slerp(rotation, 1.0 / look_at.bones.len() as f32);
+        let mut known = (entity, model);
+        for &bone in &look_at.bones {
+            let parent = match parents.get(bone) {
+                Ok(child_of) => {
+                    global_transform_below(child_of.parent(), Some(known), &parents, &transforms)
+                }
+                Err(_) => GlobalTransform::IDENTITY,
+            };
+            let Ok(mut transform) = transforms.get_mut(bone) else {
+                continue;
+            };
+            let parent_rotation = parent.rotation();
+            let global_rotation = parent_rotation * transform.rotation;
+            transform.rotation = parent_rotation.inverse() * share * global_rotation;
+            known = (bone, parent * *transform);
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use bevy::{animation::RepeatAnimation, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::character::*;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+#[serde(rename_all = "snake_case")]
+pub enum LoopMode {
+    Once,
+    #[default]
+    Loop,
+    // plays forwards then backwards, forever
+    PingPong,
+}
+
+impl LoopMode {
+    pub fn repeat(self) -> RepeatAnimation {
+        match self {
+            Self::Once => RepeatAnimation::Never,
+            Self::Loop | Self::PingPong => RepeatAnimation::Forever,
+        }
+    }
+}
+
+// "loop" is either a bool (loop or once) or a loop mode name
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum LoopModeDef {
+    Looping(bool),
+    Mode(LoopMode),
+}
+
+impl From<LoopModeDef> for LoopMode {
+    fn from(def: LoopModeDef) -> Self {
+        match def {
+            LoopModeDef::Looping(true) => Self::Loop,
+            LoopModeDef::Looping(false) => Self::Once,
+            LoopModeDef::Mode(mode) => mode,
+        }
+    }
+}
+
+// per-playback loop mode overrides, added alongside the character's AnimationPlayer
+// animations without an override use their data's loop mode
+#[derive(Debug, Default, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct AnimationLoopModes(HashMap<AnimationNodeIndex, LoopMode>);
+
+impl AnimationLoopModes {
+    pub fn get(&self, animation_index: AnimationNodeIndex) -> Option<LoopMode> {
+        self.0.get(&animation_index).copied()
+    }
+
+    pub fn set(&mut self, animation_index: AnimationNodeIndex, loop_mode: LoopMode) {
+        self.0.insert(animation_index, loop_mode);
+    }
+
+    pub fn clear(&mut self, animation_index: AnimationNodeIndex) {
+        self.0.remove(&animation_index);
+    }
+}
+
+// applies the loop mode overrides and turns ping-pong animations around
+// this has to run after the animations advance so that the wrapped pose is never sampled
+pub(crate) fn update_loop_modes(
+    characters: Res<Characters>,
+    animation_graphs: Res<Assets<AnimationGraph>>,
+    animation_clips: Res<Assets<AnimationClip>>,
+    mut animation_players: Query<(
+        &mut AnimationPlayer,
+        &AnimationGraphHandle,
+        Option<&AnimationLoopModes>,
+    )>,
+) {
+    for (mut player, graph, loop_modes) in &mut animation_players {
+        let Some((_, character)) = characters.find_by_graph(graph) else {
+            continue;
+        };
+        let Some(animation_graph) = animation_graphs.get(graph) else {
+            continue;
+        };
+
+        for (animation_index, animation) in player.playing_animations_mut() {
+            let loop_mode_override =
+                loop_modes.and_then(|loop_modes| loop_modes.get(*animation_index));
+            if let Some(loop_mode) = loop_mode_override {
+                animation.set_repeat(loop_mode.repeat());
+            }
+
+            let loop_mode = loop_mode_override.or_else(|| {
+                character
+                    .animation_name(*animation_index)
+                    .and_then(|animation_name| character.animation(animation_name))
+                    .map(|animation_data| animation_data.loop_mode)
+            });
+            // a state machine or sequence may have made it a one-shot
+            if loop_mode != Some(LoopMode::PingPong)
+                || animation.repeat_mode() != RepeatAnimation::Forever
+            {
+                continue;
+            }
+
+            let Some(duration) = animation_graph
+                .get(*animation_index)
+                .and_then(|node| match &node.node_type {
+                    AnimationNodeType::Clip(clip) => animation_clips.get(clip),
+                    _ => None,
+                })
+                .map(AnimationClip::duration)
+            else {
+                continue;
+            };
+
+            // every completion wrapped around, so mirror the time and reverse
+            let backwards = animation.completions() % 2 == 1;
+            if backwards != animation.is_playback_reversed() {
+                let time = duration - animation.seek_time();
+                animation.set_seek_time(time);
+                animation.set_speed(-animation.speed());
+            }
+        }
+    }
+}
@@ -1,56 +1,96 @@
-mod asset_event_bridge;
+mod bench;
+mod bone_inspector;
+mod cli;
+mod console;
+mod crowd;
+mod dialog;
 mod dropdown;
-
-use std::collections::HashMap;
-
-use bevy::{
-    diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin},
-    prelude::*,
-    scene::SceneInstanceReady,
-};
-use bevy_common_assets::json::JsonAssetPlugin;
-use serde::Deserialize;
-
-use asset_event_bridge::*;
+mod environment;
+mod focus;
+mod frame_time;
+mod framing;
+mod golden_pose;
+mod graphics;
+mod headless;
+#[cfg(feature = "inspector")]
+mod inspector;
+mod lighting;
+mod loading;
+mod log_panel;
+mod morph_panel;
+mod panel;
+#[cfg(feature = "physics")]
+mod physics;
+mod playback;
+mod radio;
+mod remote;
+mod screenshot;
+mod scroll;
+mod selection;
+mod settings;
+mod skeleton;
+mod slider;
+mod tabs;
+mod toast;
+mod tooltip;
+mod turntable;
+
+use bevy::prelude::*;
+use bevy_mixamo::*;
+use clap::Parser;
+
+use bench::*;
+use bone_inspector::*;
+use cli::*;
+use console::*;
+use crowd::*;
+use dialog::*;
 use dropdown::*;
-
-#[derive(Deserialize, Asset, TypePath)]
-struct CharacterData {
-    id: String,
-    model_path: String,
-    animation_paths: HashMap<String, String>,
-}
-
-impl CharacterData {
-    pub fn model_scene_path(&self) -> String {
-        format!("{}#Scene0", self.model_path)
-    }
-
-    pub fn animations(&self) -> impl Iterator<Item = &String> {
-        self.animation_paths.keys()
-    }
-
-    pub fn animation_path(&self, name: impl AsRef<str>) -> String {
-        format!("{}#Animation0", self.animation_paths[name.as_ref()])
-    }
+use environment::*;
+use focus::*;
+use frame_time::*;
+use framing::*;
+use golden_pose::*;
+use graphics::*;
+use headless::*;
+#[cfg(feature = "inspector")]
+use inspector::*;
+use lighting::*;
+use loading::*;
+use log_panel::*;
+use morph_panel::*;
+use panel::*;
+#[cfg(feature = "physics")]
+use physics::*;
+use playback::*;
+use radio::*;
+use remote::*;
+use screenshot::*;
+use scroll::*;
+use selection::*;
+use settings::*;
+use skeleton::*;
+use slider::*;
+use tabs::*;
+use toast::*;
+use tooltip::*;
+use turntable::*;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, States)]
+enum AppState {
+    #[default]
+    Loading,
+    Ready,
 }
 
-struct Character {
-    data: Handle<CharacterData>,
-    animations: HashMap<String, (Handle<AnimationGraph>, AnimationNodeIndex)>,
-}
-
-#[derive(Resource)]
-struct Characters(HashMap<String, Character>);
-
-#[derive(Component)]
-struct CharacterModel(Handle<CharacterData>);
-
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup(mut commands: Commands, asset_server: Res<AssetServer>, cli: Res<Cli>) {
     // camera
+    let orbit_camera =
+        OrbitCamera::looking_from(Vec3::new(0.0, 1.0, 5.0), Vec3::new(0.0, 1.0, 0.0));
     commands.spawn((
         Camera3d::default(),
-        Transform::from_xyz(0.0, 1.0, 5.0).looking_at(Vec3::new(0.0, 1.0, 0.0), Vec3::Y),
+        orbit_camera.transform(),
+        orbit_camera,
         AmbientLight {
             color: Color::WHITE,
             brightness: 200.0,
@@ -76,203 +116,793 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
     ));
 
     // load characters
-    let mut characters = HashMap::new();
-
-    info!("Loading character 'mutant' from 'characters/mutant.json' ...");
-    let data = asset_server.load::<CharacterData>("characters/mutant.json");
-
-    // we have to hold the data handle until the asset is loaded
-    // or the asset system will free it before we get a chance to use it
-    characters.insert(
-        "mutant".to_owned(),
-        Character {
-            data,
-            animations: HashMap::new(),
-        },
-    );
+    commands.insert_resource(CharacterManifestHandle::load(
+        "characters/manifest.json",
+        &asset_server,
+    ));
+    commands.insert_resource(CharacterFolderHandle::load("characters", &asset_server));
+    if let Some(path) = &cli.character {
+        commands.trigger(LoadCharacter::new(
+            character_id_from_path(path),
+            path.clone(),
+        ));
+    }
+}
 
-    commands.insert_resource(Characters(characters));
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct CharacterDropdown;
 
-    setup_dropdown(&mut commands);
-    setup_fps_counter(&mut commands);
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ModelDropdown;
+
+// what each of the viewer's dropdown options selects
+#[derive(Debug, Clone)]
+enum ViewerSelection {
+    Character(String),
+    Model(String),
+    // None puts back the model's own materials
+    MaterialVariant(Option<String>),
+    Animation(String),
 }
 
-#[derive(Component)]
-struct CharacterDropdown;
+// every character's model, any of them can be swapped onto the selected character
+fn model_options(characters: &Characters, character_datum: &Assets<CharacterData>) -> Vec<String> {
+    let mut options = characters
+        .ids()
+        .filter_map(|id| characters.get(id))
+        .filter_map(|character| character_datum.get(character.data()))
+        .map(|character_data| character_data.model_path.clone())
+        .collect::<Vec<_>>();
+    options.sort();
+    options.dedup();
+    options
+}
+
+// metres between the characters spawned with --spawn-count
+const SPAWN_SPACING: f32 = 1.5;
+
+// build the character, model and animation dropdowns once everything is loaded
+// and spawn the one from --character, the last run's or the first character
+fn setup_character_dropdown(
+    mut commands: Commands,
+    cli: Res<Cli>,
+    settings: Res<AppSettings>,
+    characters: Res<Characters>,
+    character_datum: Res<Assets<CharacterData>>,
+) {
+    let mut options = characters.ids().cloned().collect::<Vec<_>>();
+    options.sort();
+
+    let id = cli
+        .character
+        .as_deref()
+        .map(character_id_from_path)
+        .or_else(|| {
+            settings
+                .character
+                .clone()
+                .filter(|id| characters.get(id).is_some())
+        })
+        .or_else(|| options.first().cloned());
+    let selected = id
+        .as_ref()
+        .and_then(|id| options.iter().position(|option| option == id));
+    if let Some(id) = id {
+        // side by side, centered on the origin
+        let count = cli.spawn_count.max(1);
+        let width = (count - 1) as f32 * SPAWN_SPACING;
+        for index in 0..count {
+            commands.trigger(
+                SpawnCharacter::new(id.clone()).with_transform(Transform::from_xyz(
+                    index as f32 * SPAWN_SPACING - width * 0.5,
+                    0.0,
+                    0.0,
+                )),
+            );
+        }
+    }
 
-fn setup_dropdown(commands: &mut Commands) {
-    let options = vec!["Mutant".to_owned()];
+    spawn_dropdown(
+        &mut commands,
+        Vec2::new(100.0, 100.0),
+        Vec2::new(200.0, 50.0),
+        "Select Character",
+        options.into_iter().map(|id| DropdownOption {
+            icon: characters
+                .get(&id)
+                .and_then(|character| character.thumbnail().cloned()),
+            ..DropdownOption::new(id.clone(), ViewerSelection::Character(id))
+        }),
+    )
+    .insert((
+        Name::new("CharacterDropdown"),
+        Tooltip::new("Left and right arrows cycle characters"),
+        CharacterDropdown,
+        DropdownState {
+            selected,
+            ..default()
+        },
+    ));
 
     spawn_dropdown(
-        commands,
-        Vec2::new(100.0, 200.0),
+        &mut commands,
+        Vec2::new(980.0, 100.0),
         Vec2::new(200.0, 50.0),
-        "Select Option",
-        &options,
+        "Select Model",
+        model_options(&characters, &character_datum)
+            .into_iter()
+            .map(|path| (path.clone(), ViewerSelection::Model(path))),
     )
-    .insert((Name::new("CharacterDropdown"), CharacterDropdown));
+    .insert((Name::new("ModelDropdown"), ModelDropdown));
+
+    // filled in by setup_animation_dropdown as characters are selected
+    spawn_dropdown(
+        &mut commands,
+        Vec2::new(320.0, 100.0),
+        Vec2::new(200.0, 50.0),
+        "Select Animation",
+        Vec::<DropdownOption<ViewerSelection>>::new(),
+    )
+    .insert((
+        Name::new("AnimationDropdown"),
+        Tooltip::new("Up and down arrows cycle animations"),
+        AnimationDropdown,
+        // characters can have dozens of animations
+        DropdownFilter::default(),
+    ));
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct AnimationDropdown;
+
+// everything that can be played, blend spaces included
+fn animation_options(character: &Character) -> Vec<String> {
+    let mut options = character
+        .animations()
+        .chain(character.blend_spaces().map(|(name, _)| name))
+        .cloned()
+        .collect::<Vec<_>>();
+    options.sort();
+    options
+}
+
+// the index of the option after (or before) the current one, wrapping around
+fn cycle_option(options: &[String], current: Option<&str>, step: isize) -> Option<usize> {
+    if options.is_empty() {
+        return None;
+    }
+
+    let index =
+        match current.and_then(|current| options.iter().position(|option| option == current)) {
+            Some(index) => (index as isize + step).rem_euclid(options.len() as isize) as usize,
+            None => 0,
+        };
+    Some(index)
+}
+
+type AnimationDropdownStateQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static mut DropdownState),
+    (With<AnimationDropdown>, Without<CharacterDropdown>),
+>;
+
+// left / right cycle characters, up / down cycle animations
+// (this goes through the dropdown handler so both behave the same, and selects the option in the dropdown)
+fn handle_keyboard_shortcuts(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    characters: Res<Characters>,
+    animation_players: Query<
+        (&AnimationTransitions, &AnimationGraphHandle),
+        With<SelectedAnimationPlayer>,
+    >,
+    mut character_dropdown: Query<(Entity, &mut DropdownState), With<CharacterDropdown>>,
+    mut animation_dropdown: AnimationDropdownStateQuery,
+) {
+    let character_step = if keyboard.just_pressed(KeyCode::ArrowRight) {
+        1
+    } else if keyboard.just_pressed(KeyCode::ArrowLeft) {
+        -1
+    } else {
+        0
+    };
+    let animation_step = if keyboard.just_pressed(KeyCode::ArrowDown) {
+        1
+    } else if keyboard.just_pressed(KeyCode::ArrowUp) {
+        -1
+    } else {
+        0
+    };
+    if character_step == 0 && animation_step == 0 {
+        return;
+    }
+
+    let current = animation_players.iter().find_map(|(transitions, graph)| {
+        let (id, character) = characters.find_by_graph(graph)?;
+        Some((id, character, transitions.get_main_animation()))
+    });
+
+    if character_step != 0 {
+        let mut ids = characters.ids().cloned().collect::<Vec<_>>();
+        ids.sort();
+        if let Some((entity, mut state)) = character_dropdown.iter_mut().next()
+            && let Some(index) =
+                cycle_option(&ids, current.map(|(id, _, _)| id.as_str()), character_step)
+        {
+            state.selected = Some(index);
+            commands.trigger(DropdownChanged {
+                entity,
+                value: ViewerSelection::Character(ids[index].clone()),
+            });
+        }
+    } else if let Some((_, character, main_animation)) = current
+        && let Some((entity, mut state)) = animation_dropdown.iter_mut().next()
+    {
+        let options = animation_options(character);
+        let current_animation =
+            main_animation.and_then(|animation_index| character.animation_name(animation_index));
+        if let Some(index) = cycle_option(&options, current_animation, animation_step) {
+            state.selected = Some(index);
+            commands.trigger(DropdownChanged {
+                entity,
+                value: ViewerSelection::Animation(options[index].clone()),
+            });
+        }
+    }
 }
 
-fn on_character_data_loaded(
-    event: On<AssetLoadedEvent<CharacterData>>,
+#[derive(Component)]
+struct BlendSpaceSlider(String);
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct MaterialVariantDropdown;
+
+type AnimationDropdownOptionsQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut DropdownOptions<ViewerSelection>,
+        &'static mut DropdownState,
+    ),
+    With<AnimationDropdown>,
+>;
+
+// repopulate the animation dropdown and (re)build the material variant dropdown
+// and blend space slider from the selected character
+#[allow(clippy::too_many_arguments)]
+fn setup_animation_dropdown(
     mut commands: Commands,
+    selected_character: Res<SelectedCharacter>,
     character_datum: Res<Assets<CharacterData>>,
-    mut animation_graphs: ResMut<Assets<AnimationGraph>>,
-    mut characters: ResMut<Characters>,
-    asset_server: Res<AssetServer>,
+    characters: Res<Characters>,
+    character_models: Query<&CharacterModel>,
+    mut animation_dropdowns: AnimationDropdownOptionsQuery,
+    material_variant_dropdowns: Query<Entity, With<MaterialVariantDropdown>>,
+    blend_space_sliders: Query<Entity, With<BlendSpaceSlider>>,
 ) {
-    let character_data = character_datum.get(event.asset_id).unwrap();
-    info!(
-        "Loaded character data for '{}', loading assets ...",
-        character_data.id
-    );
+    for entity in material_variant_dropdowns
+        .iter()
+        .chain(&blend_space_sliders)
+    {
+        commands.entity(entity).despawn();
+    }
+
+    let character = selected_character
+        .0
+        .and_then(|entity| character_models.get(entity).ok())
+        .and_then(|character_model| character_datum.get(&character_model.0))
+        .and_then(|character_data| Some((character_data, characters.get(&character_data.id)?)));
+
+    // emptied if there's no character selected
+    for (mut options, mut state) in &mut animation_dropdowns {
+        options.0 = character
+            .map(|(_, character)| {
+                animation_options(character)
+                    .into_iter()
+                    .map(|name| DropdownOption::new(name.clone(), ViewerSelection::Animation(name)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        *state = DropdownState::default();
+    }
 
-    let character = characters.0.get_mut(&character_data.id).unwrap();
-
-    // load model
-    let model_path = character_data.model_scene_path();
-    info!("Loading character model from '{}' ...", model_path);
-    let model = asset_server.load::<Scene>(model_path);
-
-    // spawn the scene
-    commands
-        .spawn((
-            SceneRoot(model),
-            Transform::from_xyz(0.0, 0.0, 0.0),
-            Name::new(character_data.id.clone()),
-            CharacterModel(character.data.clone()),
-            //Rotator,
+    let Some((character_data, character)) = character else {
+        return;
+    };
+
+    if !character_data.material_variants.is_empty() {
+        let mut variants = character_data
+            .material_variants
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        variants.sort();
+        let options = std::iter::once((
+            "Model Materials".to_string(),
+            ViewerSelection::MaterialVariant(None),
         ))
-        // start the idle animation once the scene spawns
-        .observe(start_idle);
+        .chain(variants.into_iter().map(|variant| {
+            (
+                variant.clone(),
+                ViewerSelection::MaterialVariant(Some(variant)),
+            )
+        }));
+
+        spawn_dropdown(
+            &mut commands,
+            Vec2::new(100.0, 170.0),
+            Vec2::new(200.0, 50.0),
+            "Select Variant",
+            options,
+        )
+        .insert((
+            Name::new("MaterialVariantDropdown"),
+            MaterialVariantDropdown,
+        ));
+    }
 
-    // load animations
-    for animation_name in character_data.animations() {
-        let animation_path = character_data.animation_path(animation_name);
+    // only the first blend space gets a slider
+    let Some((name, range)) = character
+        .blend_spaces()
+        .filter_map(|(name, blend_space)| Some((name, blend_space.range()?)))
+        .min_by_key(|(name, _)| *name)
+    else {
+        return;
+    };
+
+    let (min, max) = range;
+    spawn_slider(
+        &mut commands,
+        Vec2::new(540.0, 100.0),
+        Vec2::new(200.0, 20.0),
+        format!("{name}: {min:.2}"),
+        min,
+        max,
+        min,
+    )
+    .insert((
+        Name::new("BlendSpaceSlider"),
+        BlendSpaceSlider(name.clone()),
+    ));
+}
+
+fn handle_blend_space_slider(
+    event: On<SliderChanged>,
+    selected_character: Res<SelectedCharacter>,
+    blend_space_sliders: Query<(&BlendSpaceSlider, &Children)>,
+    mut blend_space_positions: Query<&mut BlendSpacePositions>,
+    mut texts: Query<&mut Text>,
+) {
+    let Ok((blend_space_slider, children)) = blend_space_sliders.get(event.entity) else {
+        return;
+    };
+
+    if let Some(mut positions) = selected_character
+        .0
+        .and_then(|entity| blend_space_positions.get_mut(entity).ok())
+    {
+        positions.set(blend_space_slider.0.clone(), event.value);
+    }
+
+    // update the label
+    for child in children {
+        if let Ok(mut text) = texts.get_mut(*child) {
+            **text = format!("{}: {:.2}", blend_space_slider.0, event.value);
+        }
+    }
+}
+
+fn handle_dropdown_events(
+    trigger: On<DropdownChanged<ViewerSelection>>,
+    mut commands: Commands,
+    selected_character: Res<SelectedCharacter>,
+    character_datum: Res<Assets<CharacterData>>,
+    characters: Res<Characters>,
+    character_models: Query<(Entity, &CharacterModel, Option<&CharacterAnimator>)>,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+) {
+    info!("Dropdown Selection Changed: {:?}", trigger.value);
+
+    if let ViewerSelection::Character(id) = &trigger.value {
+        for (entity, _, _) in &character_models {
+            commands.entity(entity).despawn();
+        }
+
+        commands.trigger(SpawnCharacter::new(id.clone()));
+        return;
+    }
+
+    // animations are played on (and models swapped onto) the selected character
+    let Some((entity, character_model, animator)) = selected_character
+        .0
+        .and_then(|entity| character_models.get(entity).ok())
+    else {
+        return;
+    };
+    let Some(character_data) = character_datum.get(&character_model.0) else {
+        return;
+    };
+
+    let animation = match &trigger.value {
+        ViewerSelection::Character(_) => return,
+        ViewerSelection::Model(model_path) => {
+            commands.trigger(SwapCharacterModel::new(
+                character_data.id.clone(),
+                model_path.clone(),
+            ));
+            return;
+        }
+        ViewerSelection::MaterialVariant(None) => {
+            commands.trigger(SetMaterialVariant::clear(entity));
+            return;
+        }
+        ViewerSelection::MaterialVariant(Some(variant)) => {
+            commands.trigger(SetMaterialVariant::new(entity, variant.clone()));
+            return;
+        }
+        ViewerSelection::Animation(animation) => animation,
+    };
+
+    let Some(animator) = animator else {
+        return;
+    };
+    let Some(character) = characters.get(&character_data.id) else {
+        return;
+    };
+
+    // the animation may play on one of the model's other players
+    if let Ok((mut player, mut transitions)) =
+        animation_players.get_mut(animator.player_for(character, animation))
+    {
         info!(
-            "Loading character animation '{}' from '{}' ...",
-            animation_name, animation_path
+            "Playing animation '{}' for character '{}' ...",
+            animation, character_data.id
         );
-        let animation_clip =
-            asset_server.load::<AnimationClip>(character_data.animation_path(animation_name));
 
-        let (animation_graph, animation_index) = AnimationGraph::from_clip(animation_clip);
-        let animation_graph = animation_graphs.add(animation_graph);
+        play_animation(
+            &mut player,
+            &mut transitions,
+            character,
+            character_data,
+            animation,
+        );
+    }
+}
 
-        character
-            .animations
-            .insert(animation_name.clone(), (animation_graph, animation_index));
+// shows each character's thumbnail next to its name once it has been rendered
+fn show_character_thumbnail(
+    thumbnail_ready: On<CharacterThumbnailReady>,
+    mut character_dropdowns: Query<&mut DropdownOptions<ViewerSelection>, With<CharacterDropdown>>,
+) {
+    for mut options in &mut character_dropdowns {
+        for option in options.0.iter_mut() {
+            if matches!(&option.value, ViewerSelection::Character(id) if *id == thumbnail_ready.id)
+            {
+                option.icon = Some(thumbnail_ready.image.clone());
+            }
+        }
     }
 }
 
-fn start_idle(
-    scene_ready: On<SceneInstanceReady>,
+// have the selected character watch the camera, crowds would be too costly
+fn look_at_camera(
+    mut commands: Commands,
+    selected_character: Res<SelectedCharacter>,
+    camera: Single<&Transform, With<Camera3d>>,
+    look_at_targets: Query<Entity, With<LookAtTarget>>,
+) {
+    for entity in &look_at_targets {
+        if Some(entity) != selected_character.0 {
+            commands.entity(entity).remove::<LookAtTarget>();
+        }
+    }
+
+    if let Some(entity) = selected_character.0
+        && !look_at_targets.contains(entity)
+    {
+        commands
+            .entity(entity)
+            .try_insert(LookAtTarget::new(camera.translation));
+    }
+}
+
+// --animation replaces the default animation of every character that has it,
+// and the last run's animation that of the last run's character
+fn play_cli_animation(
+    event: On<CharacterReady>,
+    cli: Res<Cli>,
+    settings: Res<AppSettings>,
+    characters: Res<Characters>,
+    animators: Query<&CharacterAnimator>,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+) {
+    let Some(animation_name) = cli.animation.as_ref().or(settings
+        .animation
+        .as_ref()
+        .filter(|_| settings.character.as_ref() == Some(&event.id)))
+    else {
+        return;
+    };
+    let Some(character) = characters.get(&event.id) else {
+        return;
+    };
+    if character.animation_index(animation_name).is_none() {
+        warn!(
+            "Character '{}' has no animation '{}'",
+            event.id, animation_name
+        );
+        return;
+    }
+    let Ok(animator) = animators.get(event.entity) else {
+        return;
+    };
+
+    if let Ok((mut player, mut transitions)) =
+        animation_players.get_mut(animator.player_for(character, animation_name))
+    {
+        play_animation_with_transition(
+            &mut player,
+            &mut transitions,
+            character,
+            animation_name,
+            std::time::Duration::ZERO,
+        );
+    }
+}
+
+// characters with a locomotion blend space can be walked around
+fn add_character_controller(
+    event: On<Add, CharacterModel>,
     mut commands: Commands,
     character_datum: Res<Assets<CharacterData>>,
     characters: Res<Characters>,
     character_models: Query<&CharacterModel>,
-    children: Query<&Children>,
-    mut animation_players: Query<&mut AnimationPlayer>,
 ) {
-    let character_model = character_models.get(scene_ready.entity).unwrap();
-    let character_data = character_datum.get(&character_model.0).unwrap();
-
-    // find the AnimationPlayer for the character
-    // (this is usually on the root node of the scene)
-    for child in children.iter_descendants(scene_ready.entity) {
-        if let Ok(mut player) = animation_players.get_mut(child) {
-            info!(
-                "Running idle animation for character '{}' ...",
-                character_data.id
-            );
+    let Some(character) = character_models
+        .get(event.entity)
+        .ok()
+        .and_then(|character_model| character_datum.get(&character_model.0))
+        .and_then(|character_data| characters.get(&character_data.id))
+    else {
+        return;
+    };
+
+    let controller = CharacterController::default();
+    if character.blend_space(&controller.blend_space).is_some() {
+        commands.entity(event.entity).insert(controller);
+    }
+}
 
-            let (animation_graph, animation_index) = characters
-                .0
-                .get(&character_data.id)
-                .unwrap()
-                .animations
-                .get("idle")
-                .unwrap();
-            player.play(*animation_index).repeat();
+// the camera moves when it frames a character
+fn update_look_at_camera(
+    camera: Single<&Transform, (With<Camera3d>, Changed<Transform>)>,
+    mut look_at_targets: Query<&mut LookAtTarget>,
+) {
+    for mut look_at_target in &mut look_at_targets {
+        look_at_target.target = camera.translation;
+    }
+}
 
-            commands
-                .entity(child)
-                .insert(AnimationGraphHandle(animation_graph.clone()));
+fn log_animation_markers(event: On<AnimationMarker>) {
+    info!(
+        "Animation marker '{}' in animation '{}' for character '{}'",
+        event.name, event.animation, event.character
+    );
+}
 
-            break;
-        }
+fn log_animation_finished(event: On<AnimationFinished>) {
+    info!(
+        "Animation '{}' finished for character '{}'",
+        event.animation, event.character
+    );
+}
+
+#[derive(Component)]
+struct SettingsTabs;
+
+// the lighting and graphics panels are spawned by their plugins at startup,
+// and gathered into tabs here
+fn setup_settings_panel(
+    mut commands: Commands,
+    settings: Res<AppSettings>,
+    lighting_panel: Single<Entity, With<LightingPanel>>,
+    graphics_panel: Single<Entity, With<GraphicsPanel>>,
+) {
+    let panel = spawn_collapsible_panel(
+        &mut commands,
+        Vec2::new(760.0, 280.0),
+        240.0,
+        "Settings",
+        false,
+    )
+    .insert(Name::new("SettingsPanel"))
+    .id();
+
+    spawn_tabs(
+        &mut commands,
+        Vec2::ZERO,
+        ["Lighting", "Graphics"],
+        settings.settings_tab,
+    )
+    .insert((Name::new("SettingsTabs"), SettingsTabs, ChildOf(panel)))
+    .add_children(&[*lighting_panel, *graphics_panel]);
+}
+
+fn remember_settings_tab(
+    event: On<TabChanged>,
+    mut settings: ResMut<AppSettings>,
+    settings_tabs: Query<(), With<SettingsTabs>>,
+) {
+    if settings_tabs.contains(event.entity) {
+        settings.settings_tab = event.selected;
     }
 }
 
-fn handle_dropdown_events(trigger: On<DropdownChanged>) {
-    info!("Dropdown Selection Changed: {}", trigger.selected_item);
+fn show_character_load_error(event: On<CharacterLoadError>, mut commands: Commands) {
+    commands.trigger(Toast::error(format!(
+        "Character '{}' failed to load '{}'",
+        event.id, event.path
+    )));
 }
 
-#[derive(Component)]
-struct Rotator;
+fn show_character_validation_error(event: On<CharacterValidationError>, mut commands: Commands) {
+    commands.trigger(Toast::error(format!(
+        "Character '{}': {}",
+        event.id, event.error
+    )));
+}
 
-fn rotate_model(time: Res<Time>, mut query: Query<&mut Transform, With<Rotator>>) {
-    for mut transform in &mut query {
-        transform.rotate_y(time.delta_secs() * 0.5);
+// characters loaded after startup, the loading screen reports the rest
+fn show_character_loaded(
+    event: On<CharacterLoaded>,
+    mut commands: Commands,
+    state: Res<State<AppState>>,
+) {
+    if *state.get() == AppState::Ready {
+        commands.trigger(Toast::info(format!("Loaded {}", event.id)));
     }
 }
 
-fn main() {
+fn main() -> AppExit {
+    let cli = Cli::parse();
+
+    if cli.headless_validate {
+        return App::new().add_plugins(HeadlessValidatePlugin).run();
+    }
+    if cli.golden_poses || cli.golden_poses_update {
+        return App::new()
+            .add_plugins(GoldenPosePlugin {
+                update: cli.golden_poses_update,
+            })
+            .run();
+    }
+
+    let mut settings = AppSettings::load().unwrap_or_else(|err| {
+        eprintln!("{err}");
+        AppSettings::default()
+    });
+    cli.apply_to(&mut settings);
+
     let mut app = App::new();
 
-    app.add_plugins(DefaultPlugins.set(WindowPlugin {
-        primary_window: Some(Window {
-            present_mode: bevy::window::PresentMode::AutoNoVsync,
-            ..default()
-        }),
-        ..default()
-    }));
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    present_mode: settings.present_mode(),
+                    resolution: (settings.width, settings.height).into(),
+                    ..default()
+                }),
+                ..default()
+            })
+            .set(bevy::log::LogPlugin {
+                custom_layer: capture_log_layer,
+                ..default()
+            }),
+    );
 
-    app.add_plugins(FrameTimeDiagnosticsPlugin::default())
-        .add_plugins((
-            bevy::remote::RemotePlugin::default(),
-            bevy::remote::http::RemoteHttpPlugin::default(),
-        ));
+    app.add_plugins(FrameTimeGraphPlugin).add_plugins((
+        bevy::remote::RemotePlugin::default()
+            .with_method(SPAWN_CROWD_METHOD, process_spawn_crowd_request)
+            .with_method(PLAY_ANIMATION_METHOD, process_play_animation_request)
+            .with_method(SPAWN_CHARACTER_METHOD, process_spawn_character_request)
+            .with_method(LIST_CHARACTERS_METHOD, process_list_characters_request)
+            .with_method(DESPAWN_CHARACTER_METHOD, process_despawn_character_request)
+            .with_method(ANIMATION_STATE_METHOD, process_animation_state_request)
+            .with_method(SCREENSHOT_METHOD, process_screenshot_request)
+            .with_method(MEMORY_REPORT_METHOD, process_memory_report_request),
+        bevy::remote::http::RemoteHttpPlugin::default(),
+    ));
+
+    app.add_plugins(SettingsPlugin(settings));
+
+    app.init_state::<AppState>().add_plugins(LoadingPlugin);
 
-    app.add_plugins(JsonAssetPlugin::<CharacterData>::new(&[".json"]))
-        .add_systems(Update, bridge_asset_events::<CharacterData>)
-        .add_observer(on_character_data_loaded);
+    app.add_plugins(CharacterPlugin)
+        .add_plugins(CharacterDiagnosticsPlugin)
+        .add_plugins(CharacterThumbnailPlugin)
+        .add_plugins(SelectionPlugin)
+        .add_plugins(CrowdPlugin)
+        .add_plugins(FramingPlugin)
+        .add_plugins(SkeletonGizmoPlugin)
+        .add_plugins(BoneInspectorPlugin);
+
+    app.add_plugins(EnvironmentPlugin);
 
     app.add_plugins(DropdownPlugin)
-        .add_observer(handle_dropdown_events);
+        .add_dropdown_values::<ViewerSelection>()
+        .register_type::<CharacterDropdown>()
+        .register_type::<ModelDropdown>()
+        .register_type::<AnimationDropdown>()
+        .register_type::<MaterialVariantDropdown>()
+        .add_observer(handle_dropdown_events)
+        .add_observer(show_character_thumbnail)
+        .add_observer(log_animation_markers)
+        .add_observer(log_animation_finished)
+        .add_observer(add_character_controller)
+        .add_observer(play_cli_animation);
+
+    app.add_plugins(SliderPlugin)
+        .add_plugins(RadioGroupPlugin)
+        .add_plugins(CollapsiblePanelPlugin)
+        .add_plugins(TabsPlugin)
+        .add_plugins(TooltipPlugin)
+        .add_plugins(ConfirmDialogPlugin)
+        .add_plugins(ScrollViewPlugin)
+        .add_plugins(FocusPlugin)
+        .add_plugins(LogPanelPlugin)
+        .add_plugins(ConsolePlugin)
+        .add_observer(handle_blend_space_slider);
+
+    app.add_plugins(PlaybackPlugin)
+        .add_plugins(LightingPlugin)
+        .add_plugins(GraphicsSettingsPlugin)
+        .add_plugins(MorphPanelPlugin)
+        .add_plugins(ScreenshotCapturePlugin)
+        .add_plugins(TurntablePlugin)
+        .add_systems(PostStartup, setup_settings_panel)
+        .add_observer(remember_settings_tab);
+
+    app.add_plugins(ToastPlugin)
+        .add_observer(show_character_load_error)
+        .add_observer(show_character_validation_error)
+        .add_observer(show_character_loaded);
 
     app.add_systems(Startup, setup)
-        .add_systems(Update, update_fps_text)
-        .add_systems(Update, rotate_model);
+        .add_systems(OnEnter(AppState::Ready), setup_character_dropdown)
+        .add_systems(Update, handle_keyboard_shortcuts)
+        .add_systems(
+            Update,
+            setup_animation_dropdown.run_if(resource_changed::<SelectedCharacter>),
+        )
+        .add_systems(
+            Update,
+            (
+                look_at_camera.run_if(resource_changed::<SelectedCharacter>),
+                update_look_at_camera,
+            ),
+        );
 
-    app.run();
-}
+    if cli.fixed_animation {
+        app.add_plugins(FixedAnimationPlugin::default());
+    }
 
-//// VIBED FPS TEXT HERE
+    #[cfg(feature = "physics")]
+    app.add_plugins(PhysicsDemoPlugin);
 
-#[derive(Component)]
-struct FpsText;
+    #[cfg(feature = "inspector")]
+    app.add_plugins(InspectorPlugin);
 
-fn setup_fps_counter(commands: &mut Commands) {
-    commands.spawn((
-        Text::from("FPS: 0.0"),
-        TextColor(Color::WHITE),
-        Node {
-            position_type: PositionType::Absolute,
-            top: Val::Px(10.0),
-            left: Val::Px(10.0),
-            ..default()
-        },
-        FpsText,
-    ));
-}
-
-fn update_fps_text(diagnostics: Res<DiagnosticsStore>, mut query: Query<&mut Text, With<FpsText>>) {
-    for mut text in &mut query {
-        if let Some(fps) = diagnostics.get(&FrameTimeDiagnosticsPlugin::FPS) {
-            if let Some(value) = fps.smoothed() {
-                **text = format!("FPS: {value:.2}");
-            }
-        }
+    if let Some(bench) = cli.crowd_bench() {
+        app.add_plugins(BenchPlugin(bench));
     }
+
+    app.insert_resource(cli);
+
+    app.run()
 }
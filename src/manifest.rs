@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::animation_library::*;
+use crate::asset_event_bridge::*;
+use crate::character::*;
+
+// lists every character available to the app
+#[derive(Deserialize, Asset, TypePath)]
+pub struct CharacterManifest {
+    pub characters: Vec<CharacterManifestEntry>,
+
+    // shared animation sets that characters can reference by id
+    #[serde(default)]
+    pub animation_sets: Vec<AnimationSetManifestEntry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CharacterManifestEntry {
+    pub id: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AnimationSetManifestEntry {
+    pub id: String,
+    pub path: String,
+}
+
+// the manifest that Characters is populated from
+#[derive(Resource)]
+pub struct CharacterManifestHandle {
+    handle: Handle<CharacterManifest>,
+
+    // set once the manifest's characters have started loading
+    applied: bool,
+}
+
+impl CharacterManifestHandle {
+    pub fn load(path: impl Into<String>, asset_server: &AssetServer) -> Self {
+        let path = path.into();
+
+        info!("Loading character manifest from '{}' ...", path);
+        Self {
+            handle: asset_server.load::<CharacterManifest>(path),
+            applied: false,
+        }
+    }
+
+    pub fn handle(&self) -> &Handle<CharacterManifest> {
+        &self.handle
+    }
+
+    // failed manifests count as finished so that a bad path doesn't block forever
+    pub fn is_finished_loading(&self, asset_server: &AssetServer) -> bool {
+        self.applied || asset_server.load_state(&self.handle).is_failed()
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn on_character_manifest_loaded(
+    event: On<AssetLoadedEvent<CharacterManifest>>,
+    manifests: Res<Assets<CharacterManifest>>,
+    manifest_handle: Option<ResMut<CharacterManifestHandle>>,
+    character_datum: Res<Assets<CharacterData>>,
+    animation_sets: Res<Assets<AnimationSet>>,
+    mut animation_library: ResMut<AnimationLibrary>,
+    mut characters: ResMut<Characters>,
+    mut animation_graphs: ResMut<Assets<AnimationGraph>>,
+    asset_server: Res<AssetServer>,
+) {
+    let manifest = manifests.get(event.asset_id).unwrap();
+    info!(
+        "Loaded character manifest with {} characters and {} animation sets",
+        manifest.characters.len(),
+        manifest.animation_sets.len()
+    );
+
+    // sets first so that the characters can resolve them
+    for entry in &manifest.animation_sets {
+        if animation_library.set(&entry.id).is_none() {
+            animation_library.load_set(entry.id.clone(), entry.path.clone(), &asset_server);
+        }
+    }
+
+    for entry in &manifest.characters {
+        load_character(
+            &mut characters,
+            entry.id.clone(),
+            entry.path.clone(),
+            &character_datum,
+            &animation_sets,
+            &mut animation_library,
+            &asset_server,
+            &mut animation_graphs,
+        );
+    }
+
+    if let Some(mut manifest_handle) = manifest_handle
+        && manifest_handle.handle.id() == event.asset_id
+    {
+        manifest_handle.applied = true;
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+
+use bevy::{audio::Volume, diagnostic::FrameCount, prelude::*};
+use serde::Deserialize;
+
+use crate::animation_marker::*;
+use crate::character::*;
+
+// sounds played when an animation marker fires, loaded as part of the character data
+// one of the paths is picked at random each time so that repeated steps don't sound the same
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct MarkerSoundData {
+    pub paths: Vec<String>,
+
+    // replaces paths while the model is on one of these surfaces, see GroundSurface
+    #[serde(default)]
+    pub surfaces: HashMap<String, Vec<String>>,
+
+    #[serde(default = "default_marker_sound_volume")]
+    pub volume: f32,
+
+    // random speed (and so pitch) change either side of 1
+    #[serde(default)]
+    pub pitch_variation: f32,
+}
+
+fn default_marker_sound_volume() -> f32 {
+    1.0
+}
+
+impl MarkerSoundData {
+    fn paths(&self, surface: Option<&GroundSurface>) -> &[String] {
+        surface
+            .and_then(|surface| self.surfaces.get(&surface.0))
+            .unwrap_or(&self.paths)
+    }
+}
+
+// the surface a character model is standing on, e.g. "grass" or "metal"
+// set by the game (from a raycast or trigger volume) to vary its marker sounds
+#[derive(Debug, Clone, Component)]
+pub struct GroundSurface(pub String);
+
+#[derive(Debug, Clone, Resource)]
+pub struct MarkerAudioSettings {
+    pub enabled: bool,
+
+    // scales every marker sound's volume
+    pub volume: f32,
+}
+
+impl Default for MarkerAudioSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            volume: 1.0,
+        }
+    }
+}
+
+// marker sounds are held once played so that every step doesn't load them again
+#[derive(Default, Resource)]
+pub struct MarkerSounds(HashMap<String, Handle<AudioSource>>);
+
+impl MarkerSounds {
+    fn get(&mut self, path: &str, asset_server: &AssetServer) -> Handle<AudioSource> {
+        self.0
+            .entry(path.to_string())
+            .or_insert_with(|| asset_server.load(path.to_string()))
+            .clone()
+    }
+}
+
+// a cheap hash (pcg) so that sound and particle variation doesn't need a random number generator
+pub(crate) fn random_fraction(seed: u32) -> f32 {
+    let state = seed.wrapping_mul(747796405).wrapping_add(2891336453);
+    let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+    ((word >> 22) ^ word) as f32 / u32::MAX as f32
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn play_marker_sounds(
+    event: On<AnimationMarker>,
+    mut commands: Commands,
+    settings: Res<MarkerAudioSettings>,
+    mut marker_sounds: ResMut<MarkerSounds>,
+    frame_count: Res<FrameCount>,
+    character_datum: Res<Assets<CharacterData>>,
+    characters: Res<Characters>,
+    asset_server: Res<AssetServer>,
+    parents: Query<&ChildOf>,
+    surfaces: Query<&GroundSurface, With<CharacterModel>>,
+) {
+    if !settings.enabled {
+        return;
+    }
+
+    let Some(sound_data) = characters
+        .get(&event.character)
+        .and_then(|character| character_datum.get(character.data()))
+        .and_then(|character_data| character_data.marker_sounds.get(&event.name))
+    else {
+        return;
+    };
+
+    // the marker fires on the player, the surface is on the model above it
+    let surface = parents
+        .iter_ancestors(event.entity)
+        .find_map(|ancestor| surfaces.get(ancestor).ok());
+    let paths = sound_data.paths(surface);
+    if paths.is_empty() {
+        return;
+    }
+
+    let seed = frame_count.0.wrapping_add(event.entity.index().index());
+    let path = &paths[(random_fraction(seed) * paths.len() as f32) as usize % paths.len()];
+    let speed =
+        1.0 + (random_fraction(seed.wrapping_add(1)) * 2.0 - 1.0) * sound_data.pitch_variation;
+
+    commands.spawn((
+        AudioPlayer::new(marker_sounds.get(path, &asset_server)),
+        PlaybackSettings::DESPAWN
+            .with_volume(Volume::Linear(sound_data.volume * settings.volume))
+            .with_speed(speed.max(0.01)),
+        Name::new(format!("Marker Sound: {}", event.name)),
+    ));
+}
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use bevy::{gltf::GltfMaterialName, prelude::*, scene::SceneInstanceReady};
+use serde::Deserialize;
+
+use crate::character::*;
+
+// changes to one of the model's materials, loaded as part of the character data
+// anything left out keeps the model's value
+#[derive(Debug, Clone, Default, Deserialize, Reflect)]
+pub struct MaterialOverrideData {
+    // sRGB
+    #[serde(default)]
+    pub base_color: Option<[f32; 4]>,
+    #[serde(default)]
+    pub base_color_texture: Option<String>,
+    #[serde(default)]
+    pub normal_map_texture: Option<String>,
+    // linear RGB
+    #[serde(default)]
+    pub emissive: Option<[f32; 3]>,
+    #[serde(default)]
+    pub metallic: Option<f32>,
+    #[serde(default)]
+    pub perceptual_roughness: Option<f32>,
+}
+
+impl MaterialOverrideData {
+    fn apply(&self, material: &mut StandardMaterial, asset_server: &AssetServer) {
+        if let Some([r, g, b, a]) = self.base_color {
+            material.base_color = Color::srgba(r, g, b, a);
+        }
+        if let Some(path) = &self.base_color_texture {
+            material.base_color_texture = Some(asset_server.load(path));
+        }
+        if let Some(path) = &self.normal_map_texture {
+            material.normal_map_texture = Some(asset_server.load(path));
+        }
+        if let Some([r, g, b]) = self.emissive {
+            material.emissive = LinearRgba::rgb(r, g, b);
+        }
+        if let Some(metallic) = self.metallic {
+            material.metallic = metallic;
+        }
+        if let Some(perceptual_roughness) = self.perceptual_roughness {
+            material.perceptual_roughness = perceptual_roughness;
+        }
+    }
+}
+
+// material overrides by the glTF material name they apply to
+pub type MaterialVariantData = HashMap<String, MaterialOverrideData>;
+
+// the variant a character model is using, kept across model respawns (e.g. model LOD swaps)
+#[derive(Debug, Clone, Component)]
+pub struct MaterialVariant(pub String);
+
+// the mesh's material from the model, restored when the variant is cleared
+#[derive(Component)]
+pub(crate) struct OriginalMaterial(Handle<StandardMaterial>);
+
+// switches a character model's materials to one of its data's variants,
+// or back to the model's own materials with None
+#[derive(Debug, Clone, Event)]
+pub struct SetMaterialVariant {
+    pub character: Entity,
+    pub variant: Option<String>,
+}
+
+impl SetMaterialVariant {
+    pub fn new(character: Entity, variant: impl Into<String>) -> Self {
+        Self {
+            character,
+            variant: Some(variant.into()),
+        }
+    }
+
+    pub fn clear(character: Entity) -> Self {
+        Self {
+            character,
+            variant: None,
+        }
+    }
+}
+
+// a character's variant materials, keyed by the original material and the variant
+type VariantMaterials = HashMap<(AssetId<StandardMaterial>, String), Handle<StandardMaterial>>;
+
+// variant materials are shared between every model of a character
+#[derive(Default, Resource)]
+pub struct MaterialVariantCache(HashMap<String, VariantMaterials>);
+
+impl MaterialVariantCache {
+    pub(crate) fn release(&mut self, character: impl AsRef<str>) {
+        self.0.remove(character.as_ref());
+    }
+}
+
+pub(crate) type MaterialMeshQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static GltfMaterialName,
+        &'static mut MeshMaterial3d<StandardMaterial>,
+        Option<&'static OriginalMaterial>,
+    ),
+>;
+
+#[allow(clippy::too_many_arguments)]
+fn apply_material_variant(
+    commands: &mut Commands,
+    model: Entity,
+    character_data: &CharacterData,
+    variant: Option<&str>,
+    cache: &mut MaterialVariantCache,
+    materials: &mut Assets<StandardMaterial>,
+    asset_server: &AssetServer,
+    children: &Query<&Children>,
+    meshes: &mut MaterialMeshQuery,
+) {
+    let variant_data = variant.and_then(|variant| {
+        let variant_data = character_data.material_variants.get(variant);
+        if variant_data.is_none() {
+            warn!(
+                "Character '{}' has no material variant '{}'",
+                character_data.id, variant
+            );
+        }
+        variant_data
+    });
+
+    for entity in children.iter_descendants(model) {
+        let Ok((material_name, mut material, original)) = meshes.get_mut(entity) else {
+            continue;
+        };
+        let original = match original {
+            Some(original) => original.0.clone(),
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(OriginalMaterial(material.0.clone()));
+                material.0.clone()
+            }
+        };
+
+        let (Some(variant), Some(override_data)) = (
+            variant,
+            variant_data.and_then(|variant_data| variant_data.get(&material_name.0)),
+        ) else {
+            material.0 = original;
+            continue;
+        };
+
+        let variant_material = cache
+            .0
+            .entry(character_data.id.clone())
+            .or_default()
+            .entry((original.id(), variant.to_string()))
+            .or_insert_with(|| {
+                let mut variant_material = materials.get(&original).cloned().unwrap_or_default();
+                override_data.apply(&mut variant_material, asset_server);
+                materials.add(variant_material)
+            });
+        material.0 = variant_material.clone();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn on_set_material_variant(
+    event: On<SetMaterialVariant>,
+    mut commands: Commands,
+    character_datum: Res<Assets<CharacterData>>,
+    character_models: Query<&CharacterModel>,
+    mut cache: ResMut<MaterialVariantCache>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    children: Query<&Children>,
+    mut meshes: MaterialMeshQuery,
+) {
+    let Some(character_data) = character_models
+        .get(event.character)
+        .ok()
+        .and_then(|character_model| character_datum.get(&character_model.0))
+    else {
+        warn!("Cannot set the material variant of an entity that isn't a character model");
+        return;
+    };
+
+    match &event.variant {
+        Some(variant) => {
+            info!(
+                "Setting material variant '{}' for character '{}' ...",
+                variant, character_data.id
+            );
+            commands
+                .entity(event.character)
+                .insert(MaterialVariant(variant.clone()));
+        }
+        None => {
+            commands.entity(event.character).remove::<MaterialVariant>();
+        }
+    }
+
+    apply_material_variant(
+        &mut commands,
+        event.character,
+        character_data,
+        event.variant.as_deref(),
+        &mut cache,
+        &mut materials,
+        &asset_server,
+        &children,
+        &mut meshes,
+    );
+}
+
+// a respawned scene has the model's own materials again
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn reapply_material_variant(
+    scene_ready: On<SceneInstanceReady>,
+    mut commands: Commands,
+    character_datum: Res<Assets<CharacterData>>,
+    character_models: Query<(&CharacterModel, &MaterialVariant)>,
+    mut cache: ResMut<MaterialVariantCache>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    asset_server: Res<AssetServer>,
+    children: Query<&Children>,
+    mut meshes: MaterialMeshQuery,
+) {
+    let Ok((character_model, variant)) = character_models.get(scene_ready.entity) else {
+        return;
+    };
+    let Some(character_data) = character_datum.get(&character_model.0) else {
+        return;
+    };
+
+    apply_material_variant(
+        &mut commands,
+        scene_ready.entity,
+        character_data,
+        Some(&variant.0),
+        &mut cache,
+        &mut materials,
+        &asset_server,
+        &children,
+        &mut meshes,
+    );
+}
@@ -0,0 +1,191 @@
+use std::collections::HashSet;
+
+use bevy::{
+    animation::{
+        AnimationTargetId,
+        graph::{AnimationGraph, AnimationGraphNode, AnimationNodeType},
+    },
+    image::TextureFormatPixelInfo,
+    mesh::Indices,
+    prelude::*,
+};
+use serde::Serialize;
+
+use crate::character::*;
+
+// clips don't expose their keyframes, so they're estimated from their length
+// assuming Mixamo's 30 fps bake, with a time and a Quat (the largest bone value) per keyframe
+const ESTIMATED_KEYFRAMES_PER_SECOND: f32 = 30.0;
+const ESTIMATED_KEYFRAME_BYTES: usize = size_of::<f32>() + size_of::<Quat>();
+
+// estimated bytes held by a character's assets in the main world
+// assets shared with other characters (e.g. library clips) count towards each of them
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CharacterMemoryReport {
+    pub character_id: String,
+
+    // the model and its LODs, the component data of their scene worlds
+    pub scenes: usize,
+    // vertex and index data of the scenes' meshes
+    pub meshes: usize,
+    // the scenes' material textures and morph target images
+    pub images: usize,
+    // the loaded clips and the retargeted / in place / marked clips made from them
+    pub animation_clips: usize,
+    pub animation_graphs: usize,
+
+    pub total: usize,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn character_memory_report(
+    id: impl Into<String>,
+    character: &Character,
+    scenes: &Assets<Scene>,
+    meshes: &Assets<Mesh>,
+    materials: &Assets<StandardMaterial>,
+    images: &Assets<Image>,
+    animation_clips: &Assets<AnimationClip>,
+    animation_graphs: &Assets<AnimationGraph>,
+) -> CharacterMemoryReport {
+    let mut report = CharacterMemoryReport {
+        character_id: id.into(),
+        ..default()
+    };
+
+    let mut mesh_ids = HashSet::new();
+    let mut image_ids = HashSet::new();
+    for scene in character
+        .model()
+        .into_iter()
+        .chain(character.lod_models())
+        .filter_map(|scene| scenes.get(scene))
+    {
+        report.scenes += scene_bytes(scene);
+
+        let world = &scene.world;
+        if let Some(mut query) = world.try_query::<&Mesh3d>() {
+            mesh_ids.extend(query.iter(world).map(|mesh| mesh.id()));
+        }
+        if let Some(mut query) = world.try_query::<&MeshMaterial3d<StandardMaterial>>() {
+            image_ids.extend(
+                query
+                    .iter(world)
+                    .filter_map(|material| materials.get(material))
+                    .flat_map(|material| {
+                        [
+                            &material.base_color_texture,
+                            &material.emissive_texture,
+                            &material.metallic_roughness_texture,
+                            &material.normal_map_texture,
+                            &material.occlusion_texture,
+                        ]
+                    })
+                    .flatten()
+                    .map(Handle::id),
+            );
+        }
+    }
+
+    for mesh in mesh_ids.iter().filter_map(|id| meshes.get(*id)) {
+        report.meshes += mesh_bytes(mesh);
+        if let Ok(morph_targets) = mesh.try_morph_targets() {
+            image_ids.insert(morph_targets.id());
+        }
+    }
+    report.images = image_ids
+        .iter()
+        .filter_map(|id| images.get(*id))
+        .map(image_bytes)
+        .sum();
+
+    let graph = animation_graphs.get(character.graph());
+    let clip_ids = character
+        .clips()
+        .map(Handle::id)
+        .chain(graph.into_iter().flat_map(|graph| {
+            graph
+                .graph
+                .node_weights()
+                .filter_map(|node| match &node.node_type {
+                    AnimationNodeType::Clip(clip) => Some(clip.id()),
+                    _ => None,
+                })
+        }))
+        .collect::<HashSet<_>>();
+    report.animation_clips = clip_ids
+        .iter()
+        .filter_map(|id| animation_clips.get(*id))
+        .map(animation_clip_bytes)
+        .sum();
+    report.animation_graphs = graph.map(animation_graph_bytes).unwrap_or_default();
+
+    report.total = report.scenes
+        + report.meshes
+        + report.images
+        + report.animation_clips
+        + report.animation_graphs;
+    report
+}
+
+// just the components themselves, not anything they allocate
+fn scene_bytes(scene: &Scene) -> usize {
+    let world = &scene.world;
+    world
+        .archetypes()
+        .iter()
+        .map(|archetype| {
+            archetype
+                .components()
+                .iter()
+                .filter_map(|component| world.components().get_info(*component))
+                .map(|info| info.layout().size())
+                .sum::<usize>()
+                * archetype.len() as usize
+        })
+        .sum()
+}
+
+// 0 if the mesh only lives in the render world
+fn mesh_bytes(mesh: &Mesh) -> usize {
+    let attributes = mesh
+        .try_attributes()
+        .map(|attributes| {
+            attributes
+                .map(|(_, values)| values.get_bytes().len())
+                .sum::<usize>()
+        })
+        .unwrap_or_default();
+    let indices = match mesh.try_indices_option() {
+        Ok(Some(Indices::U16(indices))) => indices.len() * size_of::<u16>(),
+        Ok(Some(Indices::U32(indices))) => indices.len() * size_of::<u32>(),
+        _ => 0,
+    };
+    attributes + indices
+}
+
+// compressed images without their data can't be sized, so those count as 0
+fn image_bytes(image: &Image) -> usize {
+    if let Some(data) = &image.data {
+        return data.len();
+    }
+
+    let size = image.texture_descriptor.size;
+    let pixel_size = image
+        .texture_descriptor
+        .format
+        .pixel_size()
+        .unwrap_or_default();
+    (size.width * size.height * size.depth_or_array_layers) as usize * pixel_size
+}
+
+fn animation_clip_bytes(clip: &AnimationClip) -> usize {
+    let keyframes = (clip.duration() * ESTIMATED_KEYFRAMES_PER_SECOND).ceil() as usize + 1;
+    let curves = clip.curves().values().map(Vec::len).sum::<usize>();
+    curves * keyframes * ESTIMATED_KEYFRAME_BYTES
+}
+
+fn animation_graph_bytes(graph: &AnimationGraph) -> usize {
+    graph.graph.node_count() * size_of::<AnimationGraphNode>()
+        + graph.mask_groups.len() * size_of::<(AnimationTargetId, u64)>()
+}
@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::character::*;
+use crate::loop_mode::*;
+
+// a lower detail model that's swapped in past a camera distance
+// the model should share the full model's skeleton so that the same graph can play on it
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct ModelLodData {
+    pub model_path: String,
+    pub distance: f32,
+}
+
+impl ModelLodData {
+    pub fn model_scene_path(&self) -> String {
+        format!("{}#Scene0", self.model_path)
+    }
+}
+
+// switching back to a more detailed model waits until the camera is this fraction closer
+// so that a model sitting on a threshold doesn't swap every frame
+const MODEL_LOD_HYSTERESIS: f32 = 0.1;
+
+// on character models that have LODs
+#[derive(Debug, Clone, Component)]
+pub struct ModelLod {
+    // sorted by distance, the first is the full model at distance 0
+    levels: Vec<(f32, Handle<Scene>)>,
+    current: usize,
+}
+
+impl ModelLod {
+    pub(crate) fn new(levels: Vec<(f32, Handle<Scene>)>) -> Self {
+        Self { levels, current: 0 }
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    fn level(&self, distance: f32) -> usize {
+        let level = self
+            .levels
+            .iter()
+            .rposition(|(threshold, _)| distance >= *threshold)
+            .unwrap_or_default();
+        if level < self.current
+            && distance >= self.levels[self.current].0 * (1.0 - MODEL_LOD_HYSTERESIS)
+        {
+            return self.current;
+        }
+        level
+    }
+}
+
+// the outgoing model's animation state, restored onto the incoming model's player
+// so that swapping models doesn't restart the animation
+#[derive(Component)]
+pub(crate) struct ModelLodAnimationState {
+    pub(crate) player: AnimationPlayer,
+    pub(crate) transitions: Option<AnimationTransitions>,
+    pub(crate) loop_modes: Option<AnimationLoopModes>,
+}
+
+pub(crate) fn update_model_lods(
+    mut commands: Commands,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    mut character_models: Query<(
+        Entity,
+        &GlobalTransform,
+        &mut ModelLod,
+        &mut SceneRoot,
+        Option<&CharacterAnimator>,
+    )>,
+    animation_players: Query<(
+        &AnimationPlayer,
+        Option<&AnimationTransitions>,
+        Option<&AnimationLoopModes>,
+    )>,
+) {
+    for (entity, transform, mut model_lod, mut scene_root, animator) in &mut character_models {
+        let Some(distance) = cameras
+            .iter()
+            .map(|camera_transform| {
+                camera_transform
+                    .translation()
+                    .distance(transform.translation())
+            })
+            .reduce(f32::min)
+        else {
+            continue;
+        };
+
+        let level = model_lod.level(distance);
+        if level == model_lod.current {
+            continue;
+        }
+
+        debug!(
+            "Swapping model LOD {} -> {} at distance {:.1}",
+            model_lod.current, level, distance
+        );
+
+        // if the previous swap hasn't spawned yet its state is still waiting to be restored
+        if let Some((player, transitions, loop_modes)) =
+            animator.and_then(|animator| animation_players.get(animator.player).ok())
+        {
+            commands.entity(entity).insert(ModelLodAnimationState {
+                player: player.clone(),
+                transitions: transitions.cloned(),
+                loop_modes: loop_modes.cloned(),
+            });
+        }
+
+        model_lod.current = level;
+        scene_root.0 = model_lod.levels[level].1.clone();
+    }
+}
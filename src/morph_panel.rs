@@ -0,0 +1,226 @@
+use bevy::prelude::*;
+use bevy_mixamo::*;
+
+use crate::dropdown::{HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
+use crate::focus::*;
+use crate::selection::*;
+use crate::slider::*;
+
+pub struct MorphPanelPlugin;
+
+impl Plugin for MorphPanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                rebuild_morph_panel,
+                handle_expression_buttons,
+                update_morph_target_sliders,
+            )
+                .chain(),
+        )
+        .add_observer(handle_morph_target_sliders);
+    }
+}
+
+// only this many sliders fit under the other panels
+const MAX_MORPH_TARGET_SLIDERS: usize = 8;
+
+// everything in the panel, so that it can be rebuilt for a new selection
+#[derive(Component)]
+struct MorphPanel;
+
+#[derive(Component)]
+struct MorphTargetSlider(String);
+
+#[derive(Component)]
+struct ExpressionButton(String);
+
+fn morph_target_label(name: &str, weight: f32) -> String {
+    format!("{name}: {weight:.2}")
+}
+
+// (re)build the sliders and expression buttons for the selected character's morph targets
+// the morph targets aren't found until the model's scene spawns, which is after it's selected
+fn rebuild_morph_panel(
+    mut commands: Commands,
+    selected_character: Res<SelectedCharacter>,
+    character_datum: Res<Assets<CharacterData>>,
+    character_models: Query<(&CharacterModel, Ref<CharacterMorphTargets>)>,
+    panels: Query<Entity, With<MorphPanel>>,
+) {
+    let selected = selected_character
+        .0
+        .and_then(|entity| character_models.get(entity).ok());
+    let added = selected
+        .as_ref()
+        .is_some_and(|(_, morph_targets)| morph_targets.is_added());
+    if !selected_character.is_changed() && !added {
+        return;
+    }
+
+    for entity in &panels {
+        commands.entity(entity).despawn();
+    }
+
+    let Some((character_model, morph_targets)) = selected else {
+        return;
+    };
+
+    for (index, name) in morph_targets
+        .names()
+        .into_iter()
+        .take(MAX_MORPH_TARGET_SLIDERS)
+        .enumerate()
+    {
+        let weight = morph_targets.weight(name);
+        spawn_slider(
+            &mut commands,
+            Vec2::new(100.0, 260.0 + index as f32 * 50.0),
+            Vec2::new(200.0, 20.0),
+            morph_target_label(name, weight),
+            0.0,
+            1.0,
+            weight,
+        )
+        .insert((
+            Name::new("MorphTargetSlider"),
+            MorphTargetSlider(name.clone()),
+            MorphPanel,
+        ));
+    }
+
+    let Some(character_data) = character_datum.get(&character_model.0) else {
+        return;
+    };
+    let mut expressions = character_data.expressions.keys().collect::<Vec<_>>();
+    expressions.sort();
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(320.0),
+                top: Val::Px(260.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(10.0),
+                ..default()
+            },
+            Name::new("ExpressionButtons"),
+            MorphPanel,
+        ))
+        .with_children(|parent| {
+            for expression in expressions {
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(150.0),
+                            height: Val::Px(40.0),
+                            border: UiRect::all(Val::Px(2.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BorderColor::all(Color::BLACK),
+                        BackgroundColor(NORMAL_BUTTON),
+                        Focusable,
+                        ExpressionButton(expression.clone()),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Text::new(expression.clone()),
+                            TextFont {
+                                font_size: 20.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                        ));
+                    });
+            }
+        });
+}
+
+fn handle_morph_target_sliders(
+    event: On<SliderChanged>,
+    selected_character: Res<SelectedCharacter>,
+    sliders: Query<&MorphTargetSlider>,
+    mut character_models: Query<&mut CharacterMorphTargets>,
+) {
+    let Ok(slider) = sliders.get(event.entity) else {
+        return;
+    };
+
+    if let Some(mut morph_targets) = selected_character
+        .0
+        .and_then(|entity| character_models.get_mut(entity).ok())
+    {
+        morph_targets.set_weight(slider.0.clone(), event.value);
+    }
+}
+
+type ExpressionButtonInteractionQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Interaction,
+        &'static mut BackgroundColor,
+        &'static ExpressionButton,
+    ),
+    Changed<Interaction>,
+>;
+
+fn handle_expression_buttons(
+    mut commands: Commands,
+    selected_character: Res<SelectedCharacter>,
+    mut buttons: ExpressionButtonInteractionQuery,
+) {
+    for (interaction, mut color, button) in &mut buttons {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+                if let Some(entity) = selected_character.0 {
+                    commands.trigger(SetExpression::new(entity, button.0.clone()));
+                }
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+// expressions (and code) change the weights too, so the sliders follow the weights
+fn update_morph_target_sliders(
+    selected_character: Res<SelectedCharacter>,
+    character_models: Query<Ref<CharacterMorphTargets>>,
+    mut sliders: Query<(&MorphTargetSlider, &mut Slider, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    let Some(morph_targets) = selected_character
+        .0
+        .and_then(|entity| character_models.get(entity).ok())
+    else {
+        return;
+    };
+    if !morph_targets.is_changed() {
+        return;
+    }
+
+    for (morph_target_slider, mut slider, children) in &mut sliders {
+        let weight = morph_targets.weight(&morph_target_slider.0);
+        if !slider.is_dragging() {
+            slider.set_value(weight);
+        }
+
+        // update the label
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                **text = morph_target_label(&morph_target_slider.0, weight);
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use bevy::{prelude::*, scene::SceneInstanceReady};
+
+use crate::character::*;
+
+// target weights by morph target name, loaded as part of the character data
+pub type ExpressionData = HashMap<String, f32>;
+
+// the model's morph targets (blendshapes) by name, set once the model's scene has spawned
+// a name can be on more than one mesh (e.g. the face and the teeth), those are set together
+// weights that are set override any animated weights
+#[derive(Debug, Clone, Default, Component)]
+pub struct CharacterMorphTargets {
+    // the MorphWeights entities and the target's index in them
+    targets: HashMap<String, Vec<(Entity, usize)>>,
+
+    weights: HashMap<String, f32>,
+}
+
+impl CharacterMorphTargets {
+    // sorted by name
+    pub fn names(&self) -> Vec<&String> {
+        let mut names = self.targets.keys().collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.targets.is_empty()
+    }
+
+    pub fn weight(&self, name: impl AsRef<str>) -> f32 {
+        self.weights.get(name.as_ref()).copied().unwrap_or_default()
+    }
+
+    pub fn set_weight(&mut self, name: impl Into<String>, weight: f32) {
+        self.weights.insert(name.into(), weight);
+    }
+
+    // sets every target, the ones the expression leaves out go back to 0
+    pub fn set_expression(&mut self, expression: &ExpressionData) {
+        for (name, weight) in &mut self.weights {
+            *weight = expression.get(name).copied().unwrap_or_default();
+        }
+        for (name, weight) in expression {
+            self.weights.insert(name.clone(), *weight);
+        }
+    }
+
+    // hands the targets back to the animations
+    pub fn clear_weights(&mut self) {
+        self.weights.clear();
+    }
+}
+
+// sets a character model's morph targets to one of its data's expressions
+#[derive(Debug, Clone, Event)]
+pub struct SetExpression {
+    pub character: Entity,
+    pub expression: String,
+}
+
+impl SetExpression {
+    pub fn new(character: Entity, expression: impl Into<String>) -> Self {
+        Self {
+            character,
+            expression: expression.into(),
+        }
+    }
+}
+
+// finds the model's morph targets once the scene spawns,
+// a respawned scene (e.g. a model LOD swap) keeps the weights that were set
+pub(crate) fn setup_morph_targets(
+    scene_ready: On<SceneInstanceReady>,
+    mut commands: Commands,
+    character_models: Query<Option<&CharacterMorphTargets>, With<CharacterModel>>,
+    children: Query<&Children>,
+    morph_weights: Query<&MorphWeights>,
+    meshes: Res<Assets<Mesh>>,
+) {
+    let Ok(previous) = character_models.get(scene_ready.entity) else {
+        return;
+    };
+
+    let mut morph_targets = CharacterMorphTargets {
+        targets: HashMap::new(),
+        weights: previous
+            .map(|previous| previous.weights.clone())
+            .unwrap_or_default(),
+    };
+    for entity in children.iter_descendants(scene_ready.entity) {
+        let Some(names) = morph_weights
+            .get(entity)
+            .ok()
+            .and_then(|weights| weights.first_mesh())
+            .and_then(|mesh| meshes.get(mesh))
+            .and_then(|mesh| mesh.try_morph_target_names().ok().flatten())
+        else {
+            continue;
+        };
+
+        for (index, name) in names.iter().enumerate() {
+            morph_targets
+                .targets
+                .entry(name.clone())
+                .or_default()
+                .push((entity, index));
+        }
+    }
+
+    if morph_targets.is_empty() && previous.is_none() {
+        return;
+    }
+    commands.entity(scene_ready.entity).insert(morph_targets);
+}
+
+pub(crate) fn on_set_expression(
+    event: On<SetExpression>,
+    character_datum: Res<Assets<CharacterData>>,
+    mut character_models: Query<(&CharacterModel, &mut CharacterMorphTargets)>,
+) {
+    let Ok((character_model, mut morph_targets)) = character_models.get_mut(event.character) else {
+        warn!(
+            "Cannot set expression '{}' on a model without morph targets",
+            event.expression
+        );
+        return;
+    };
+    let Some(character_data) = character_datum.get(&character_model.0) else {
+        return;
+    };
+    let Some(expression) = character_data.expressions.get(&event.expression) else {
+        warn!(
+            "Character '{}' has no expression '{}'",
+            character_data.id, event.expression
+        );
+        return;
+    };
+
+    morph_targets.set_expression(expression);
+}
+
+pub(crate) fn apply_morph_target_weights(
+    character_models: Query<&CharacterMorphTargets>,
+    mut morph_weights: Query<&mut MorphWeights>,
+) {
+    for morph_targets in &character_models {
+        for (name, weight) in &morph_targets.weights {
+            let Some(targets) = morph_targets.targets.get(name) else {
+                continue;
+            };
+            for (entity, index) in targets {
+                if let Ok(mut weights) = morph_weights.get_mut(*entity)
+                    && let Some(target_weight) = weights.weights_mut().get_mut(*index)
+                {
+                    *target_weight = *weight;
+                }
+            }
+        }
+    }
+}
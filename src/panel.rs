@@ -0,0 +1,173 @@
+use bevy::prelude::*;
+
+use crate::dropdown::{HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
+use crate::focus::*;
+
+pub struct CollapsiblePanelPlugin;
+
+impl Plugin for CollapsiblePanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<CollapsiblePanel>()
+            .register_type::<CollapsiblePanelHeader>()
+            .add_systems(
+                Update,
+                (handle_collapsible_panel_headers, apply_collapsible_panels).chain(),
+            );
+    }
+}
+
+// a titled column that hides everything but its header while collapsed,
+// set expanded to open or close it from code
+#[derive(Debug, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct CollapsiblePanel {
+    pub title: String,
+    pub expanded: bool,
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct CollapsiblePanelHeader;
+
+const PANEL_BACKGROUND: Color = Color::srgba(0.05, 0.05, 0.05, 0.7);
+const HEADER_HEIGHT: f32 = 30.0;
+
+type CollapsiblePanelHeaderQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Interaction,
+        &'static ChildOf,
+        &'static mut BackgroundColor,
+    ),
+    (Changed<Interaction>, With<CollapsiblePanelHeader>),
+>;
+
+type CollapsiblePanelChangedQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static CollapsiblePanel, &'static Children),
+    Or<(Changed<CollapsiblePanel>, Changed<Children>)>,
+>;
+
+fn header_label(panel: &CollapsiblePanel) -> String {
+    let marker = if panel.expanded { "-" } else { "+" };
+    format!("{marker} {}", panel.title)
+}
+
+// the panel's other children are its content, laid out top to bottom
+// whatever position they were spawned with
+pub fn spawn_collapsible_panel<'a>(
+    commands: &'a mut Commands,
+    position: Vec2,
+    width: f32,
+    title: impl Into<String>,
+    expanded: bool,
+) -> EntityCommands<'a> {
+    let panel = CollapsiblePanel {
+        title: title.into(),
+        expanded,
+    };
+    let label = header_label(&panel);
+
+    let mut entity_commands = commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(position.x),
+            top: Val::Px(position.y),
+            width: Val::Px(width),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(10.0),
+            padding: UiRect::all(Val::Px(5.0)),
+            ..default()
+        },
+        BackgroundColor(PANEL_BACKGROUND),
+        panel,
+    ));
+
+    entity_commands.with_children(|parent| {
+        parent
+            .spawn((
+                Button,
+                Node {
+                    height: Val::Px(HEADER_HEIGHT),
+                    border: UiRect::all(Val::Px(2.0)),
+                    padding: UiRect::horizontal(Val::Px(5.0)),
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BorderColor::all(Color::BLACK),
+                BackgroundColor(NORMAL_BUTTON),
+                Focusable,
+                CollapsiblePanelHeader,
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Text::new(label),
+                    TextFont {
+                        font_size: 18.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                ));
+            });
+    });
+
+    entity_commands
+}
+
+fn handle_collapsible_panel_headers(
+    mut headers: CollapsiblePanelHeaderQuery,
+    mut panels: Query<&mut CollapsiblePanel>,
+) {
+    for (interaction, parent, mut color) in &mut headers {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+
+                let Ok(mut panel) = panels.get_mut(parent.parent()) else {
+                    continue;
+                };
+                panel.expanded = !panel.expanded;
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+// content added later is hidden along with the rest
+fn apply_collapsible_panels(
+    panels: CollapsiblePanelChangedQuery,
+    headers: Query<&Children, With<CollapsiblePanelHeader>>,
+    mut nodes: Query<&mut Node>,
+    mut texts: Query<&mut Text>,
+) {
+    for (panel, children) in &panels {
+        for child in children.iter() {
+            if let Ok(header_children) = headers.get(child) {
+                let mut texts = texts.iter_many_mut(header_children);
+                while let Some(mut text) = texts.fetch_next() {
+                    **text = header_label(panel);
+                }
+                continue;
+            }
+
+            let Ok(mut node) = nodes.get_mut(child) else {
+                continue;
+            };
+            node.position_type = PositionType::Relative;
+            node.left = Val::Auto;
+            node.top = Val::Auto;
+            node.display = if panel.expanded {
+                Display::Flex
+            } else {
+                Display::None
+            };
+        }
+    }
+}
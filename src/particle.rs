@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use bevy::{light::NotShadowCaster, prelude::*};
+use serde::Deserialize;
+
+use crate::animation_marker::*;
+use crate::character::*;
+use crate::marker_audio::*;
+use crate::retarget::*;
+
+// a simple burst of particles (e.g. a dust puff), loaded from an .effect.json
+// particles fly out in a cone around up, fall, and shrink away over their lifetime
+#[derive(Debug, Clone, Deserialize, Asset, TypePath)]
+pub struct ParticleEffect {
+    #[serde(default = "default_particle_count")]
+    pub count: u32,
+
+    // seconds
+    #[serde(default = "default_particle_lifetime")]
+    pub lifetime: f32,
+
+    #[serde(default = "default_particle_speed")]
+    pub speed: f32,
+
+    // the cone's half angle in degrees
+    #[serde(default = "default_particle_spread")]
+    pub spread: f32,
+
+    // starting radius
+    #[serde(default = "default_particle_size")]
+    pub size: f32,
+
+    // sRGBA
+    #[serde(default = "default_particle_color")]
+    pub color: [f32; 4],
+
+    #[serde(default = "default_particle_gravity")]
+    pub gravity: f32,
+}
+
+fn default_particle_count() -> u32 {
+    12
+}
+
+fn default_particle_lifetime() -> f32 {
+    0.6
+}
+
+fn default_particle_speed() -> f32 {
+    1.0
+}
+
+fn default_particle_spread() -> f32 {
+    60.0
+}
+
+fn default_particle_size() -> f32 {
+    0.05
+}
+
+fn default_particle_color() -> [f32; 4] {
+    [0.6, 0.55, 0.5, 0.6]
+}
+
+fn default_particle_gravity() -> f32 {
+    -2.0
+}
+
+// spawns a particle effect burst at a world space position
+#[derive(Debug, Clone, Event)]
+pub struct SpawnParticleEffect {
+    pub effect: Handle<ParticleEffect>,
+    pub position: Vec3,
+}
+
+impl SpawnParticleEffect {
+    pub fn new(effect: Handle<ParticleEffect>, position: Vec3) -> Self {
+        Self { effect, position }
+    }
+}
+
+#[derive(Debug, Component)]
+pub struct Particle {
+    velocity: Vec3,
+    gravity: f32,
+    size: f32,
+    age: f32,
+    lifetime: f32,
+}
+
+// every particle is the same sphere, each effect gets its own material
+#[derive(Resource)]
+pub(crate) struct ParticleAssets {
+    mesh: Handle<Mesh>,
+    materials: HashMap<AssetId<ParticleEffect>, Handle<StandardMaterial>>,
+}
+
+impl FromWorld for ParticleAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        Self {
+            mesh: meshes.add(Sphere::new(1.0).mesh().ico(1).unwrap()),
+            materials: HashMap::new(),
+        }
+    }
+}
+
+pub(crate) fn on_spawn_particle_effect(
+    event: On<SpawnParticleEffect>,
+    mut commands: Commands,
+    effects: Res<Assets<ParticleEffect>>,
+    mut particle_assets: ResMut<ParticleAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    time: Res<Time>,
+) {
+    let Some(effect) = effects.get(&event.effect) else {
+        return;
+    };
+
+    let mesh = particle_assets.mesh.clone();
+    let material = particle_assets
+        .materials
+        .entry(event.effect.id())
+        .or_insert_with(|| {
+            let [r, g, b, a] = effect.color;
+            materials.add(StandardMaterial {
+                base_color: Color::srgba(r, g, b, a),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            })
+        })
+        .clone();
+
+    // spread the bursts out so that effects on the same frame don't match
+    let seed = time.elapsed().subsec_nanos();
+    let spread = effect.spread.to_radians();
+    for index in 0..effect.count {
+        let seed = seed.wrapping_add(index * 3);
+        let angle = random_fraction(seed) * spread;
+        let heading = random_fraction(seed.wrapping_add(1)) * std::f32::consts::TAU;
+        let direction = Quat::from_rotation_y(heading) * Quat::from_rotation_x(angle) * Vec3::Y;
+        let speed = effect.speed * (0.5 + random_fraction(seed.wrapping_add(2)) * 0.5);
+
+        commands.spawn((
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_translation(event.position).with_scale(Vec3::splat(effect.size)),
+            NotShadowCaster,
+            Particle {
+                velocity: direction * speed,
+                gravity: effect.gravity,
+                size: effect.size,
+                age: 0.0,
+                lifetime: effect.lifetime,
+            },
+            Name::new("Particle"),
+        ));
+    }
+}
+
+pub(crate) fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform)>,
+) {
+    let delta = time.delta_secs();
+    for (entity, mut particle, mut transform) in &mut particles {
+        particle.age += delta;
+        if particle.age >= particle.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        particle.velocity.y += particle.gravity * delta;
+        transform.translation += particle.velocity * delta;
+        transform.scale = Vec3::splat(particle.size * (1.0 - particle.age / particle.lifetime));
+    }
+}
+
+// spawns the marker's effect at its bone (or the model if it doesn't name one)
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn spawn_marker_effects(
+    event: On<AnimationMarker>,
+    mut commands: Commands,
+    characters: Res<Characters>,
+    parents: Query<&ChildOf>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    character_models: Query<(), With<CharacterModel>>,
+    transforms: Query<&GlobalTransform>,
+) {
+    let Some(character) = characters.get(&event.character) else {
+        return;
+    };
+    let Some(marker_data) = character
+        .animation(&event.animation)
+        .and_then(|animation_data| {
+            animation_data
+                .events
+                .iter()
+                .find(|marker| marker.name == event.name)
+        })
+    else {
+        return;
+    };
+    let Some(effect_data) = &marker_data.effect else {
+        return;
+    };
+    let Some(effect) = character.marker_effect(&effect_data.path) else {
+        return;
+    };
+
+    // the marker fires on the player, the bones are under the model above it
+    let Some(model) = parents
+        .iter_ancestors(event.entity)
+        .find(|ancestor| character_models.contains(*ancestor))
+    else {
+        return;
+    };
+
+    let target = effect_data
+        .bone
+        .as_ref()
+        .and_then(|bone| find_bone(model, bone, &children, &names))
+        .unwrap_or(model);
+    let Ok(transform) = transforms.get(target) else {
+        return;
+    };
+
+    commands.trigger(SpawnParticleEffect::new(
+        effect.clone(),
+        transform.translation(),
+    ));
+}
@@ -0,0 +1,50 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_mixamo::*;
+
+use crate::selection::*;
+
+pub struct PhysicsDemoPlugin;
+
+impl Plugin for PhysicsDemoPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(PhysicsPlugins::default())
+            .add_systems(Startup, setup_ground_collider)
+            .add_systems(Update, toggle_ragdoll);
+    }
+}
+
+// the environment's ground is just a mesh, capsules and ragdolls need something to stand on
+fn setup_ground_collider(mut commands: Commands) {
+    commands.spawn((
+        RigidBody::Static,
+        Collider::cuboid(100.0, 0.1, 100.0),
+        Transform::from_xyz(0.0, -0.05, 0.0),
+        Name::new("Ground Collider"),
+    ));
+}
+
+// R knocks the selected character over, and again has it recover
+fn toggle_ragdoll(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    selected_character: Res<SelectedCharacter>,
+    ragdolls: Query<&Ragdoll>,
+) {
+    if !keyboard.just_pressed(KeyCode::KeyR) {
+        return;
+    }
+    let Some(entity) = selected_character.0 else {
+        return;
+    };
+
+    match ragdolls.get(entity) {
+        Ok(ragdoll) if !ragdoll.is_recovering() => {
+            commands.trigger(RecoverFromRagdoll::new(entity));
+        }
+        Ok(_) => {}
+        Err(_) => {
+            commands.trigger(GoRagdoll::new(entity).with_velocity(Vec3::new(0.0, 1.0, -2.0)));
+        }
+    }
+}
@@ -0,0 +1,458 @@
+use bevy::prelude::*;
+use bevy_mixamo::*;
+
+use crate::dropdown::{HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
+use crate::focus::*;
+use crate::radio::*;
+use crate::selection::*;
+use crate::slider::*;
+use crate::tooltip::*;
+
+pub struct PlaybackPlugin;
+
+impl Plugin for PlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlaybackSettings>()
+            .add_systems(Startup, setup_playback_panel)
+            .add_systems(
+                Update,
+                (
+                    handle_playback_buttons,
+                    toggle_pause,
+                    update_play_pause_label.run_if(resource_changed::<PlaybackSettings>),
+                    update_playback_speed_slider.run_if(resource_changed::<PlaybackSettings>),
+                    step_frames,
+                    apply_playback_settings,
+                    update_timeline,
+                )
+                    .chain(),
+            )
+            .add_observer(handle_playback_speed_slider)
+            .add_observer(handle_loop_mode_radio_group)
+            .add_observer(handle_timeline_slider);
+    }
+}
+
+// applied on top of every character's animations
+#[derive(Resource)]
+pub struct PlaybackSettings {
+    pub paused: bool,
+    pub speed: f32,
+
+    // overrides the main animation's loop mode
+    pub loop_mode: Option<LoopMode>,
+}
+
+impl Default for PlaybackSettings {
+    fn default() -> Self {
+        Self {
+            paused: false,
+            speed: 1.0,
+            loop_mode: None,
+        }
+    }
+}
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum PlaybackButton {
+    PlayPause,
+    Restart,
+}
+
+impl PlaybackButton {
+    fn label(&self, settings: &PlaybackSettings) -> &'static str {
+        match self {
+            Self::PlayPause if settings.paused => "Play",
+            Self::PlayPause => "Pause",
+            Self::Restart => "Restart",
+        }
+    }
+
+    fn tooltip(&self) -> &'static str {
+        match self {
+            Self::PlayPause => "Pause or resume every animation (Space)",
+            Self::Restart => "Restart the selected character's animations",
+        }
+    }
+}
+
+#[derive(Component)]
+struct PlaybackSpeedSlider;
+
+#[derive(Component)]
+struct PlaybackTimeline;
+
+#[derive(Component)]
+struct LoopModeRadioGroup;
+
+// the loop mode radio group's options, None leaves it to the animation data
+const LOOP_MODES: [(&str, Option<LoopMode>); 4] = [
+    ("data", None),
+    ("once", Some(LoopMode::Once)),
+    ("loop", Some(LoopMode::Loop)),
+    ("ping-pong", Some(LoopMode::PingPong)),
+];
+
+// the speed slider's range, which the console keeps to as well
+pub const MIN_SPEED: f32 = 0.1;
+pub const MAX_SPEED: f32 = 3.0;
+
+// mixamo exports at 30fps
+const FRAME_TIME: f32 = 1.0 / 30.0;
+
+fn setup_playback_panel(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(760.0),
+                top: Val::Px(100.0),
+                column_gap: Val::Px(10.0),
+                ..default()
+            },
+            Name::new("PlaybackPanel"),
+        ))
+        .with_children(|parent| {
+            for (button, label) in [
+                (PlaybackButton::PlayPause, "Pause"),
+                (PlaybackButton::Restart, "Restart"),
+            ] {
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(150.0),
+                            height: Val::Px(50.0),
+                            border: UiRect::all(Val::Px(2.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BorderColor::all(Color::BLACK),
+                        BackgroundColor(NORMAL_BUTTON),
+                        Tooltip::new(button.tooltip()),
+                        Focusable,
+                        button,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Text::new(label),
+                            TextFont {
+                                font_size: 20.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                        ));
+                    });
+            }
+        });
+
+    spawn_slider(
+        &mut commands,
+        Vec2::new(760.0, 160.0),
+        Vec2::new(200.0, 20.0),
+        "speed: 1.00x",
+        MIN_SPEED,
+        MAX_SPEED,
+        1.0,
+    )
+    .insert((
+        Name::new("PlaybackSpeedSlider"),
+        Tooltip::new("Scales the speed of every animation"),
+        PlaybackSpeedSlider,
+    ));
+
+    spawn_slider(
+        &mut commands,
+        Vec2::new(760.0, 220.0),
+        Vec2::new(300.0, 20.0),
+        "time: 0.00 / 0.00",
+        0.0,
+        1.0,
+        0.0,
+    )
+    .insert((
+        Name::new("PlaybackTimeline"),
+        Tooltip::new("Drag to scrub, , and . step a frame while paused"),
+        PlaybackTimeline,
+    ));
+
+    spawn_radio_group(
+        &mut commands,
+        Vec2::new(980.0, 160.0),
+        Vec2::new(70.0, 30.0),
+        "loop mode",
+        LOOP_MODES.map(|(label, _)| label),
+        0,
+    )
+    .insert((
+        Name::new("LoopModeRadioGroup"),
+        Tooltip::new("Overrides the loop mode from the animation data"),
+        LoopModeRadioGroup,
+    ));
+}
+
+// the main animation and its clip's duration
+// (blend spaces don't have a clip of their own)
+fn main_animation(
+    transitions: &AnimationTransitions,
+    graph: &AnimationGraphHandle,
+    animation_graphs: &Assets<AnimationGraph>,
+    animation_clips: &Assets<AnimationClip>,
+) -> Option<(AnimationNodeIndex, f32)> {
+    let animation_index = transitions.get_main_animation()?;
+    let AnimationNodeType::Clip(clip) =
+        &animation_graphs.get(graph)?.get(animation_index)?.node_type
+    else {
+        return None;
+    };
+    Some((animation_index, animation_clips.get(clip)?.duration()))
+}
+
+fn update_timeline(
+    animation_graphs: Res<Assets<AnimationGraph>>,
+    animation_clips: Res<Assets<AnimationClip>>,
+    animation_players: Query<
+        (
+            &AnimationPlayer,
+            &AnimationTransitions,
+            &AnimationGraphHandle,
+        ),
+        With<SelectedAnimationPlayer>,
+    >,
+    mut timelines: Query<(&mut Slider, &Children), With<PlaybackTimeline>>,
+    mut texts: Query<&mut Text>,
+) {
+    let Ok((mut slider, children)) = timelines.single_mut() else {
+        return;
+    };
+
+    let Some((time, duration)) =
+        animation_players
+            .iter()
+            .find_map(|(player, transitions, graph)| {
+                let (animation_index, duration) =
+                    main_animation(transitions, graph, &animation_graphs, &animation_clips)?;
+                let animation = player.animation(animation_index)?;
+                Some((animation.seek_time(), duration))
+            })
+    else {
+        return;
+    };
+
+    if !slider.is_dragging() {
+        slider.max = duration;
+        slider.set_value(time);
+    }
+
+    for child in children {
+        if let Ok(mut text) = texts.get_mut(*child) {
+            **text = format!("time: {time:.2} / {duration:.2}");
+        }
+    }
+}
+
+fn handle_timeline_slider(
+    event: On<SliderChanged>,
+    timelines: Query<(), With<PlaybackTimeline>>,
+    mut animation_players: Query<
+        (&mut AnimationPlayer, &AnimationTransitions),
+        With<SelectedAnimationPlayer>,
+    >,
+) {
+    if !timelines.contains(event.entity) {
+        return;
+    }
+
+    for (mut player, transitions) in &mut animation_players {
+        if let Some(animation) = transitions
+            .get_main_animation()
+            .and_then(|animation_index| player.animation_mut(animation_index))
+        {
+            animation.seek_to(event.value);
+        }
+    }
+}
+
+// , and . step back and forward a frame while paused
+fn step_frames(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<PlaybackSettings>,
+    animation_graphs: Res<Assets<AnimationGraph>>,
+    animation_clips: Res<Assets<AnimationClip>>,
+    mut animation_players: Query<
+        (
+            &mut AnimationPlayer,
+            &AnimationTransitions,
+            &AnimationGraphHandle,
+        ),
+        With<SelectedAnimationPlayer>,
+    >,
+) {
+    if !settings.paused {
+        return;
+    }
+
+    let step = if keyboard.just_pressed(KeyCode::Comma) {
+        -FRAME_TIME
+    } else if keyboard.just_pressed(KeyCode::Period) {
+        FRAME_TIME
+    } else {
+        return;
+    };
+
+    for (mut player, transitions, graph) in &mut animation_players {
+        let Some((animation_index, duration)) =
+            main_animation(transitions, graph, &animation_graphs, &animation_clips)
+        else {
+            continue;
+        };
+        if let Some(animation) = player.animation_mut(animation_index) {
+            let time = (animation.seek_time() + step).clamp(0.0, duration);
+            animation.seek_to(time);
+        }
+    }
+}
+
+fn handle_playback_buttons(
+    mut settings: ResMut<PlaybackSettings>,
+    mut buttons: Query<(&Interaction, &PlaybackButton, &mut BackgroundColor), Changed<Interaction>>,
+    mut animation_players: Query<&mut AnimationPlayer, With<SelectedAnimationPlayer>>,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = PRESSED_BUTTON.into();
+
+                match button {
+                    PlaybackButton::PlayPause => {
+                        settings.paused = !settings.paused;
+                    }
+                    PlaybackButton::Restart => {
+                        for mut player in &mut animation_players {
+                            for (_, animation) in player.playing_animations_mut() {
+                                // ping-pong animations may be playing backwards
+                                let speed = animation.speed().abs();
+                                animation.replay();
+                                animation.set_speed(speed);
+                            }
+                        }
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                *color = HOVERED_BUTTON.into();
+            }
+            Interaction::None => {
+                *color = NORMAL_BUTTON.into();
+            }
+        }
+    }
+}
+
+fn toggle_pause(keyboard: Res<ButtonInput<KeyCode>>, mut settings: ResMut<PlaybackSettings>) {
+    if keyboard.just_pressed(KeyCode::Space) {
+        settings.paused = !settings.paused;
+    }
+}
+
+fn update_play_pause_label(
+    settings: Res<PlaybackSettings>,
+    buttons: Query<(&PlaybackButton, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (button, children) in &buttons {
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                **text = button.label(&settings).to_string();
+            }
+        }
+    }
+}
+
+fn handle_playback_speed_slider(
+    event: On<SliderChanged>,
+    mut settings: ResMut<PlaybackSettings>,
+    speed_sliders: Query<(), With<PlaybackSpeedSlider>>,
+) {
+    if speed_sliders.contains(event.entity) {
+        settings.speed = event.value;
+    }
+}
+
+// the speed can also be set from the console
+fn update_playback_speed_slider(
+    settings: Res<PlaybackSettings>,
+    mut speed_sliders: Query<(&mut Slider, &Children), With<PlaybackSpeedSlider>>,
+    mut texts: Query<&mut Text>,
+) {
+    for (mut slider, children) in &mut speed_sliders {
+        if slider.value != settings.speed && !slider.is_dragging() {
+            slider.set_value(settings.speed);
+        }
+
+        // update the label
+        for child in children {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                let label = format!("speed: {:.2}x", settings.speed);
+                if **text != label {
+                    **text = label;
+                }
+            }
+        }
+    }
+}
+
+fn handle_loop_mode_radio_group(
+    event: On<RadioGroupChanged>,
+    mut settings: ResMut<PlaybackSettings>,
+    loop_mode_groups: Query<(), With<LoopModeRadioGroup>>,
+) {
+    if !loop_mode_groups.contains(event.entity) {
+        return;
+    }
+
+    settings.loop_mode = LOOP_MODES[event.selected].1;
+}
+
+// animations pick up their own speed when they start,
+// so the settings are reapplied to everything that's playing
+fn apply_playback_settings(
+    settings: Res<PlaybackSettings>,
+    characters: Res<Characters>,
+    mut animation_players: Query<(
+        &mut AnimationPlayer,
+        &AnimationGraphHandle,
+        &AnimationTransitions,
+        &mut AnimationLoopModes,
+    )>,
+) {
+    for (mut player, graph, transitions, mut loop_modes) in &mut animation_players {
+        let Some((_, character)) = characters.find_by_graph(graph) else {
+            continue;
+        };
+
+        if let Some(main_animation) = transitions.get_main_animation() {
+            match settings.loop_mode {
+                Some(loop_mode) => loop_modes.set(main_animation, loop_mode),
+                None => loop_modes.clear(main_animation),
+            }
+        }
+
+        for (animation_index, animation) in player.playing_animations_mut() {
+            let speed = character
+                .animation_name(*animation_index)
+                .and_then(|animation_name| character.animation(animation_name))
+                .map(|animation_data| animation_data.speed)
+                .unwrap_or(1.0);
+            // keep the direction of reversed (ping-pong) animations
+            animation.set_speed((speed * settings.speed).copysign(animation.speed()));
+
+            if settings.paused {
+                animation.pause();
+            } else {
+                animation.resume();
+            }
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+
+use crate::dropdown::{HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
+use crate::focus::*;
+
+pub struct RadioGroupPlugin;
+
+impl Plugin for RadioGroupPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<RadioGroup>()
+            .register_type::<RadioButton>()
+            .add_systems(
+                Update,
+                (handle_radio_buttons, update_radio_button_colors).chain(),
+            );
+    }
+}
+
+// a row of buttons where exactly one is selected,
+// set it to change the group without going through its buttons (that doesn't trigger RadioGroupChanged)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Component, Reflect)]
+#[reflect(Component)]
+pub struct RadioGroup {
+    pub selected: usize,
+}
+
+#[derive(Debug, Clone, Copy, Component, Reflect)]
+#[reflect(Component)]
+pub struct RadioButton {
+    pub group: Entity,
+    pub index: usize,
+}
+
+// targets the RadioGroup entity, with the selected button's index
+#[derive(EntityEvent)]
+pub struct RadioGroupChanged {
+    pub entity: Entity,
+    pub selected: usize,
+}
+
+pub fn spawn_radio_group<'a>(
+    commands: &'a mut Commands,
+    position: Vec2,
+    button_size: Vec2,
+    label: impl Into<String>,
+    options: impl IntoIterator<Item = impl Into<String>>,
+    selected: usize,
+) -> EntityCommands<'a> {
+    let mut entity_commands = commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(position.x),
+            top: Val::Px(position.y),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(5.0),
+            ..default()
+        },
+        RadioGroup { selected },
+    ));
+    let group = entity_commands.id();
+
+    entity_commands.with_children(|parent| {
+        // label
+        parent.spawn((
+            Text::new(label.into()),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+
+        // buttons
+        parent.spawn(Node::default()).with_children(|parent| {
+            for (index, option) in options.into_iter().enumerate() {
+                parent
+                    .spawn((
+                        Button,
+                        Node {
+                            width: Val::Px(button_size.x),
+                            height: Val::Px(button_size.y),
+                            border: UiRect::all(Val::Px(2.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        BorderColor::all(Color::BLACK),
+                        BackgroundColor(NORMAL_BUTTON),
+                        Focusable,
+                        RadioButton { group, index },
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn((
+                            Text::new(option.into()),
+                            TextFont {
+                                font_size: 14.0,
+                                ..default()
+                            },
+                            TextColor(Color::WHITE),
+                        ));
+                    });
+            }
+        });
+    });
+
+    entity_commands
+}
+
+fn handle_radio_buttons(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &RadioButton), Changed<Interaction>>,
+    mut groups: Query<&mut RadioGroup>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Ok(mut group) = groups.get_mut(button.group) else {
+            continue;
+        };
+        if group.selected == button.index {
+            continue;
+        }
+
+        group.selected = button.index;
+        commands.trigger(RadioGroupChanged {
+            entity: button.group,
+            selected: button.index,
+        });
+    }
+}
+
+// the selection can change from code too, so buttons are recolored every frame
+fn update_radio_button_colors(
+    groups: Query<&RadioGroup>,
+    mut buttons: Query<(&Interaction, &RadioButton, &mut BackgroundColor)>,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        let Ok(group) = groups.get(button.group) else {
+            continue;
+        };
+
+        let new_color = if group.selected == button.index {
+            PRESSED_BUTTON
+        } else if *interaction == Interaction::None {
+            NORMAL_BUTTON
+        } else {
+            HOVERED_BUTTON
+        };
+        color.set_if_neq(new_color.into());
+    }
+}
@@ -0,0 +1,348 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+
+use crate::character::*;
+use crate::ik::*;
+use crate::retarget::*;
+
+// the mixamo bones that get a body: (bone, the bone its capsule reaches to,
+// the segment it's jointed to, capsule thickness as a fraction of its length)
+// the bones in between (e.g. Spine1, Neck, the shoulders) keep their animated pose
+const RAGDOLL_SEGMENTS: &[(&str, &str, Option<usize>, f32)] = &[
+    ("Hips", "Spine", None, 0.8),
+    ("Spine", "Spine2", Some(0), 0.8),
+    ("Spine2", "Neck", Some(1), 0.6),
+    ("Head", "HeadTop_End", Some(2), 0.5),
+    ("LeftArm", "LeftForeArm", Some(2), 0.2),
+    ("LeftForeArm", "LeftHand", Some(4), 0.2),
+    ("RightArm", "RightForeArm", Some(2), 0.2),
+    ("RightForeArm", "RightHand", Some(6), 0.2),
+    ("LeftUpLeg", "LeftLeg", Some(0), 0.2),
+    ("LeftLeg", "LeftFoot", Some(8), 0.15),
+    ("RightUpLeg", "RightLeg", Some(0), 0.2),
+    ("RightLeg", "RightFoot", Some(10), 0.15),
+];
+
+// ragdoll bodies collide with the world but not with each other,
+// overlapping capsules at the joints would push the ragdoll apart
+const RAGDOLL_LAYER: u32 = 0b10;
+pub(crate) const WORLD_LAYER: u32 = 0b01;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RagdollState {
+    // the physics pose is being blended in
+    Active,
+    // the get up animation is being blended back in
+    Recovering,
+}
+
+#[derive(Debug)]
+struct RagdollBody {
+    bone: Entity,
+    body: Entity,
+}
+
+// added to a character model while it's ragdolling
+#[derive(Debug, Component)]
+pub struct Ragdoll {
+    state: RagdollState,
+
+    // 0 is the animated pose, 1 is the physics pose
+    weight: f32,
+    blend_duration: f32,
+
+    // root first, so that each bone is placed after its parent
+    bodies: Vec<RagdollBody>,
+    joints: Vec<Entity>,
+}
+
+impl Ragdoll {
+    pub fn weight(&self) -> f32 {
+        self.weight
+    }
+
+    pub fn is_recovering(&self) -> bool {
+        self.state == RagdollState::Recovering
+    }
+}
+
+// hands a character model over to physics, blending from its animated pose
+#[derive(Debug, Clone, Event)]
+pub struct GoRagdoll {
+    pub character: Entity,
+    pub blend_duration: f32,
+
+    // e.g. from whatever knocked the character over
+    pub velocity: Vec3,
+}
+
+impl GoRagdoll {
+    pub fn new(character: Entity) -> Self {
+        Self {
+            character,
+            blend_duration: 0.1,
+            velocity: Vec3::ZERO,
+        }
+    }
+
+    pub fn with_blend_duration(mut self, blend_duration: f32) -> Self {
+        self.blend_duration = blend_duration;
+        self
+    }
+
+    pub fn with_velocity(mut self, velocity: Vec3) -> Self {
+        self.velocity = velocity;
+        self
+    }
+}
+
+pub fn go_ragdoll(commands: &mut Commands, character: Entity) {
+    commands.trigger(GoRagdoll::new(character));
+}
+
+// blends a ragdolling character model back to its animations,
+// moving the model to where the ragdoll fell and optionally playing a get up animation
+#[derive(Debug, Clone, Event)]
+pub struct RecoverFromRagdoll {
+    pub character: Entity,
+    pub animation: Option<String>,
+    pub blend_duration: f32,
+}
+
+impl RecoverFromRagdoll {
+    pub fn new(character: Entity) -> Self {
+        Self {
+            character,
+            animation: None,
+            blend_duration: 0.5,
+        }
+    }
+
+    pub fn with_animation(mut self, animation: impl Into<String>) -> Self {
+        self.animation = Some(animation.into());
+        self
+    }
+
+    pub fn with_blend_duration(mut self, blend_duration: f32) -> Self {
+        self.blend_duration = blend_duration;
+        self
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn on_go_ragdoll(
+    event: On<GoRagdoll>,
+    mut commands: Commands,
+    character_models: Query<(Option<&Ragdoll>, &CharacterModel)>,
+    character_datum: Res<Assets<CharacterData>>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+    global_transforms: Query<&GlobalTransform>,
+) {
+    let Ok((ragdoll, character_model)) = character_models.get(event.character) else {
+        warn!("Cannot ragdoll an entity that isn't a character model");
+        return;
+    };
+    let id = character_datum
+        .get(&character_model.0)
+        .map(|character_data| character_data.id.as_str())
+        .unwrap_or_default();
+    if ragdoll.is_some() {
+        return;
+    }
+
+    // mixamo prefixes are optional so that this works with normalized names
+    let find_bone = |bone: &str| {
+        children.iter_descendants(event.character).find(|entity| {
+            names
+                .get(*entity)
+                .is_ok_and(|name| normalize_bone_name(name.as_str()) == bone)
+        })
+    };
+
+    // every bone is found before anything is spawned so that a missing one doesn't leave bodies behind
+    let mut segments = Vec::new();
+    for (bone_name, tail_name, _, radius) in RAGDOLL_SEGMENTS {
+        let bones = find_bone(bone_name)
+            .zip(find_bone(tail_name))
+            .and_then(|(bone, tail)| {
+                Some((
+                    bone,
+                    global_transforms.get(bone).ok()?,
+                    global_transforms.get(tail).ok()?,
+                ))
+            });
+        let Some((bone, bone_global, tail_global)) = bones else {
+            warn!(
+                "Character '{}' is missing ragdoll bones '{}{}', '{}{}'",
+                id, MIXAMO_BONE_PREFIX, bone_name, MIXAMO_BONE_PREFIX, tail_name
+            );
+            return;
+        };
+        segments.push((*bone_name, *radius, bone, bone_global, tail_global));
+    }
+
+    let mut bodies = Vec::new();
+    let mut heads = Vec::new();
+    let mut rotations = Vec::new();
+    for (bone_name, radius, bone, bone_global, tail_global) in segments {
+        // bodies are unscaled, the capsule is sized from the bone's world space length
+        let (_, rotation, head) = bone_global.to_scale_rotation_translation();
+        let tail = rotation.inverse() * (tail_global.translation() - head);
+        let body = commands
+            .spawn((
+                RigidBody::Dynamic,
+                Collider::capsule_endpoints(tail.length() * radius * 0.5, Vec3::ZERO, tail),
+                CollisionLayers::new(RAGDOLL_LAYER, WORLD_LAYER),
+                LinearVelocity(event.velocity),
+                Transform::from_translation(head).with_rotation(rotation),
+                Name::new(format!("Ragdoll: {bone_name}")),
+            ))
+            .id();
+
+        bodies.push(RagdollBody { bone, body });
+        heads.push(head);
+        rotations.push(rotation);
+    }
+
+    // joints sit at the child's head
+    let joints = RAGDOLL_SEGMENTS
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (_, _, parent, _))| Some((index, (*parent)?)))
+        .map(|(index, parent)| {
+            let anchor = rotations[parent].inverse() * (heads[index] - heads[parent]);
+            commands
+                .spawn((
+                    SphericalJoint::new(bodies[parent].body, bodies[index].body)
+                        .with_local_anchor1(anchor)
+                        .with_local_anchor2(Vec3::ZERO),
+                    Name::new("Ragdoll Joint"),
+                ))
+                .id()
+        })
+        .collect();
+
+    info!("Ragdolling character '{}' ...", id);
+    commands.entity(event.character).insert(Ragdoll {
+        state: RagdollState::Active,
+        weight: 0.0,
+        blend_duration: event.blend_duration,
+        bodies,
+        joints,
+    });
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn on_recover_from_ragdoll(
+    event: On<RecoverFromRagdoll>,
+    mut character_models: Query<(
+        &mut Ragdoll,
+        &mut Transform,
+        &CharacterModel,
+        Option<&CharacterAnimator>,
+    )>,
+    bodies: Query<&Transform, Without<Ragdoll>>,
+    character_datum: Res<Assets<CharacterData>>,
+    characters: Res<Characters>,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+) {
+    let Ok((mut ragdoll, mut transform, character_model, animator)) =
+        character_models.get_mut(event.character)
+    else {
+        return;
+    };
+
+    ragdoll.state = RagdollState::Recovering;
+    ragdoll.blend_duration = event.blend_duration;
+
+    // the animations play from the model's position, so move it under the ragdoll's hips
+    // the bones are placed in world space while blending so this doesn't move the ragdoll
+    if let Some(hips) = ragdoll
+        .bodies
+        .first()
+        .and_then(|hips| bodies.get(hips.body).ok())
+    {
+        transform.translation.x = hips.translation.x;
+        transform.translation.z = hips.translation.z;
+    }
+
+    let Some(animation) = &event.animation else {
+        return;
+    };
+    let Some(character_data) = character_datum.get(&character_model.0) else {
+        return;
+    };
+    let (Some(character), Some(animator)) = (characters.get(&character_data.id), animator) else {
+        return;
+    };
+    if let Ok((mut player, mut transitions)) =
+        animation_players.get_mut(animator.player_for(character, animation))
+    {
+        play_animation(
+            &mut player,
+            &mut transitions,
+            character,
+            character_data,
+            animation,
+        );
+    }
+}
+
+// runs after the animations have been sampled, blending each ragdolled bone
+// between its animated pose and its body
+pub(crate) fn update_ragdolls(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut models: Query<(Entity, &mut Ragdoll)>,
+    parents: Query<&ChildOf>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for (entity, mut ragdoll) in &mut models {
+        let step = if ragdoll.blend_duration > 0.0 {
+            time.delta_secs() / ragdoll.blend_duration
+        } else {
+            1.0
+        };
+        ragdoll.weight = match ragdoll.state {
+            RagdollState::Active => (ragdoll.weight + step).min(1.0),
+            RagdollState::Recovering => (ragdoll.weight - step).max(0.0),
+        };
+
+        if ragdoll.state == RagdollState::Recovering && ragdoll.weight <= 0.0 {
+            for body in ragdoll
+                .joints
+                .iter()
+                .copied()
+                .chain(ragdoll.bodies.iter().map(|body| body.body))
+            {
+                commands.entity(body).despawn();
+            }
+            commands.entity(entity).remove::<Ragdoll>();
+            continue;
+        }
+
+        for body in &ragdoll.bodies {
+            let (Ok(bone), Ok(body_transform)) = (
+                transforms.get(body.bone).copied(),
+                transforms.get(body.body).copied(),
+            ) else {
+                continue;
+            };
+
+            let parent_global = match parents.get(body.bone) {
+                Ok(child_of) => compute_global_transform(child_of.parent(), &parents, &transforms),
+                Err(_) => GlobalTransform::IDENTITY,
+            };
+
+            // keep the bone's scale, the bodies don't have one
+            let scale = (parent_global * bone).scale();
+            let physics = GlobalTransform::from(body_transform.with_scale(scale))
+                .reparented_to(&parent_global);
+
+            let mut transform = transforms.get_mut(body.bone).unwrap();
+            transform.translation = bone.translation.lerp(physics.translation, ragdoll.weight);
+            transform.rotation = bone.rotation.slerp(physics.rotation, ragdoll.weight);
+        }
+    }
+}
@@ -0,0 +1,394 @@
+use bevy::{
+    animation::RepeatAnimation,
+    prelude::*,
+    remote::{BrpError, BrpResult, builtin_methods::parse_some, error_codes},
+};
+use bevy_mixamo::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::selection::*;
+
+// remote method to play an animation, takes PlayAnimationParams as its params
+pub const PLAY_ANIMATION_METHOD: &str = "mixamo/play_animation";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlayAnimationParams {
+    pub character_id: String,
+    pub animation: String,
+
+    // overrides the animation's loop setting
+    #[serde(default, rename = "loop")]
+    pub looping: Option<bool>,
+}
+
+fn invalid_params(message: String) -> BrpError {
+    BrpError {
+        code: error_codes::INVALID_PARAMS,
+        message,
+        data: None,
+    }
+}
+
+pub type CharacterAnimatorQuery<'w, 's> =
+    Query<'w, 's, (&'static CharacterModel, &'static CharacterAnimator)>;
+
+pub type AnimationPlayerQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut AnimationPlayer,
+        &'static mut AnimationTransitions,
+    ),
+>;
+
+// plays the animation on every spawned model of the character and returns how many,
+// shared with the console
+pub fn play_character_animation(
+    character_id: &str,
+    animation_name: &str,
+    looping: Option<bool>,
+    character_datum: &Assets<CharacterData>,
+    characters: &Characters,
+    character_models: &CharacterAnimatorQuery,
+    animation_players: &mut AnimationPlayerQuery,
+) -> Result<usize, String> {
+    let Some(character) = characters.get(character_id) else {
+        return Err(format!("unknown character '{}'", character_id));
+    };
+    let Some(character_data) = character_datum.get(character.data()) else {
+        return Err(format!("character '{}' isn't loaded", character_id));
+    };
+    if character.animation_index(animation_name).is_none() {
+        return Err(format!(
+            "character '{}' has no animation '{}'",
+            character_id, animation_name
+        ));
+    }
+
+    let mut played = 0;
+    for (_, animator) in character_models
+        .iter()
+        .filter(|(character_model, _)| &character_model.0 == character.data())
+    {
+        let Ok((mut player, mut transitions)) =
+            animation_players.get_mut(animator.player_for(character, animation_name))
+        else {
+            continue;
+        };
+        let Some(animation) = play_animation(
+            &mut player,
+            &mut transitions,
+            character,
+            character_data,
+            animation_name,
+        ) else {
+            continue;
+        };
+
+        if let Some(looping) = looping {
+            animation.set_repeat(if looping {
+                RepeatAnimation::Forever
+            } else {
+                RepeatAnimation::Never
+            });
+        }
+        played += 1;
+    }
+
+    Ok(played)
+}
+
+pub fn process_play_animation_request(
+    In(params): In<Option<Value>>,
+    character_datum: Res<Assets<CharacterData>>,
+    characters: Res<Characters>,
+    character_models: CharacterAnimatorQuery,
+    mut animation_players: AnimationPlayerQuery,
+) -> BrpResult {
+    let params = parse_some::<PlayAnimationParams>(params)?;
+
+    let played = play_character_animation(
+        &params.character_id,
+        &params.animation,
+        params.looping,
+        &character_datum,
+        &characters,
+        &character_models,
+        &mut animation_players,
+    )
+    .map_err(invalid_params)?;
+
+    info!(
+        "Remote played animation '{}' on {} models of character '{}'",
+        params.animation, played, params.character_id
+    );
+    Ok(serde_json::json!({ "played": played }))
+}
+
+// remote methods for driving the viewer without its UI (e.g. from a screenshot pipeline)
+pub const SPAWN_CHARACTER_METHOD: &str = "mixamo/spawn_character";
+pub const LIST_CHARACTERS_METHOD: &str = "mixamo/list_characters";
+pub const DESPAWN_CHARACTER_METHOD: &str = "mixamo/despawn_character";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SpawnCharacterParams {
+    // the character's data file, loaded if it isn't already
+    pub path: String,
+
+    // defaults to the file name without its extension
+    #[serde(default)]
+    pub id: Option<String>,
+
+    #[serde(default)]
+    pub position: [f32; 3],
+
+    // degrees around Y
+    #[serde(default)]
+    pub rotation: f32,
+}
+
+// despawns a single model, or every model of a character
+#[derive(Debug, Clone, Deserialize)]
+pub struct DespawnCharacterParams {
+    #[serde(default)]
+    pub entity: Option<Entity>,
+
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+pub fn process_spawn_character_request(
+    In(params): In<Option<Value>>,
+    mut commands: Commands,
+    characters: Res<Characters>,
+) -> BrpResult {
+    let params = parse_some::<SpawnCharacterParams>(params)?;
+    let id = params
+        .id
+        .unwrap_or_else(|| character_id_from_path(&params.path));
+
+    // the spawn waits on the character to finish loading
+    if characters.get(&id).is_none() {
+        commands.trigger(LoadCharacter::new(id.clone(), params.path));
+    }
+    commands.trigger(
+        SpawnCharacter::new(id.clone()).with_transform(
+            Transform::from_translation(Vec3::from(params.position))
+                .with_rotation(Quat::from_rotation_y(params.rotation.to_radians())),
+        ),
+    );
+
+    Ok(serde_json::json!({ "id": id }))
+}
+
+pub fn process_list_characters_request(
+    In(_params): In<Option<Value>>,
+    character_datum: Res<Assets<CharacterData>>,
+    character_models: Query<(Entity, &CharacterModel, &Transform)>,
+) -> BrpResult {
+    let models = character_models
+        .iter()
+        .map(|(entity, character_model, transform)| {
+            let id = character_datum
+                .get(&character_model.0)
+                .map(|character_data| character_data.id.clone());
+            serde_json::json!({
+                "entity": entity,
+                "id": id,
+                "position": transform.translation.to_array(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Value::Array(models))
+}
+
+pub fn process_despawn_character_request(
+    In(params): In<Option<Value>>,
+    mut commands: Commands,
+    character_datum: Res<Assets<CharacterData>>,
+    character_models: Query<(Entity, &CharacterModel)>,
+) -> BrpResult {
+    let params = parse_some::<DespawnCharacterParams>(params)?;
+    if params.entity.is_none() && params.id.is_none() {
+        return Err(invalid_params(
+            "despawn needs an entity or a character id".to_string(),
+        ));
+    }
+
+    let mut despawned = Vec::new();
+    for (entity, character_model) in &character_models {
+        let id = character_datum
+            .get(&character_model.0)
+            .map(|character_data| character_data.id.as_str());
+        if params.entity.is_some_and(|target| target == entity)
+            || params
+                .id
+                .as_deref()
+                .is_some_and(|target| Some(target) == id)
+        {
+            commands.entity(entity).despawn();
+            despawned.push(entity);
+        }
+    }
+
+    if despawned.is_empty() {
+        return Err(invalid_params("no matching character models".to_string()));
+    }
+    Ok(serde_json::json!({ "despawned": despawned }))
+}
+
+// remote method returning the selected character's AnimationStateResponse
+pub const ANIMATION_STATE_METHOD: &str = "mixamo/animation_state";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnimationStateResponse {
+    pub entity: Entity,
+    pub character_id: String,
+
+    // the main (not layered) animation, None before the scene has spawned
+    pub animation: Option<String>,
+    // seconds into the clip
+    pub time: f32,
+    pub speed: f32,
+    pub loop_mode: Option<LoopMode>,
+    pub paused: bool,
+
+    pub animations: Vec<String>,
+}
+
+pub fn process_animation_state_request(
+    In(_params): In<Option<Value>>,
+    selected_character: Res<SelectedCharacter>,
+    character_datum: Res<Assets<CharacterData>>,
+    characters: Res<Characters>,
+    character_models: Query<(&CharacterModel, Option<&CharacterAnimator>)>,
+    animation_players: Query<(
+        &AnimationPlayer,
+        &AnimationTransitions,
+        Option<&AnimationLoopModes>,
+    )>,
+) -> BrpResult {
+    let Some((entity, (character_model, animator))) = selected_character
+        .0
+        .and_then(|entity| Some((entity, character_models.get(entity).ok()?)))
+    else {
+        return Err(invalid_params("no character is selected".to_string()));
+    };
+    let Some(character_data) = character_datum.get(&character_model.0) else {
+        return Err(invalid_params(
+            "the selected character isn't loaded".to_string(),
+        ));
+    };
+    let Some(character) = characters.get(&character_data.id) else {
+        return Err(invalid_params(format!(
+            "unknown character '{}'",
+            character_data.id
+        )));
+    };
+
+    let mut animations = character.animations().cloned().collect::<Vec<_>>();
+    animations.sort();
+
+    let mut response = AnimationStateResponse {
+        entity,
+        character_id: character_data.id.clone(),
+        animation: None,
+        time: 0.0,
+        speed: 0.0,
+        loop_mode: None,
+        paused: false,
+        animations,
+    };
+
+    let Some((player, transitions, loop_modes)) =
+        animator.and_then(|animator| animation_players.get(animator.player).ok())
+    else {
+        return serde_json::to_value(response).map_err(BrpError::internal);
+    };
+    let Some((animation_index, animation)) = transitions
+        .get_main_animation()
+        .and_then(|index| Some((index, player.animation(index)?)))
+    else {
+        return serde_json::to_value(response).map_err(BrpError::internal);
+    };
+
+    let animation_name = character.animation_name(animation_index);
+    response.animation = animation_name.map(str::to_string);
+    response.time = animation.seek_time();
+    response.speed = animation.speed();
+    response.paused = animation.is_paused();
+
+    // blend spaces always loop
+    response.loop_mode = loop_modes
+        .and_then(|loop_modes| loop_modes.get(animation_index))
+        .or_else(|| {
+            let animation_name = animation_name?;
+            if character.blend_space(animation_name).is_some() {
+                return Some(LoopMode::Loop);
+            }
+            character
+                .animation(animation_name)
+                .map(|animation_data| animation_data.loop_mode)
+        });
+
+    serde_json::to_value(response).map_err(BrpError::internal)
+}
+
+// remote method estimating the memory held by each loaded character's assets,
+// takes MemoryReportParams as its (optional) params, returns CharacterMemoryReports heaviest first
+pub const MEMORY_REPORT_METHOD: &str = "mixamo/memory_report";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MemoryReportParams {
+    // every loaded character if not set
+    #[serde(default)]
+    pub character_id: Option<String>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn process_memory_report_request(
+    In(params): In<Option<Value>>,
+    characters: Res<Characters>,
+    scenes: Res<Assets<Scene>>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+    images: Res<Assets<Image>>,
+    animation_clips: Res<Assets<AnimationClip>>,
+    animation_graphs: Res<Assets<AnimationGraph>>,
+) -> BrpResult {
+    let params = match params {
+        Some(params) => parse_some::<MemoryReportParams>(Some(params))?,
+        None => MemoryReportParams::default(),
+    };
+
+    let ids = match &params.character_id {
+        Some(id) if characters.get(id).is_none() => {
+            return Err(invalid_params(format!("unknown character '{}'", id)));
+        }
+        Some(id) => vec![id],
+        None => characters.ids().collect(),
+    };
+
+    let mut reports = ids
+        .into_iter()
+        .filter_map(|id| Some((id, characters.get(id)?)))
+        .filter(|(_, character)| character.is_loaded())
+        .map(|(id, character)| {
+            character_memory_report(
+                id,
+                character,
+                &scenes,
+                &meshes,
+                &materials,
+                &images,
+                &animation_clips,
+                &animation_graphs,
+            )
+        })
+        .collect::<Vec<_>>();
+    reports.sort_by_key(|report| std::cmp::Reverse(report.total));
+
+    serde_json::to_value(reports).map_err(BrpError::internal)
+}
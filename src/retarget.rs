@@ -0,0 +1,628 @@
+use std::collections::{HashMap, hash_map::Entry};
+
+use bevy::{
+    animation::{
+        AnimatedBy, AnimationEntityMut, AnimationTargetId, animated_field,
+        animation_curves::{
+            AnimatableCurve, AnimatableKeyframeCurve, AnimatableProperty, AnimationCompatibleCurve,
+            EvaluatorId,
+        },
+    },
+    math::{Affine3A, Mat3A, curve::ConstantCurve},
+    prelude::*,
+    scene::SceneInstanceReady,
+};
+use serde::Deserialize;
+
+use crate::root_motion::*;
+
+// mixamo prefixes every bone name with this
+pub const MIXAMO_BONE_PREFIX: &str = "mixamorig:";
+
+// and names every clip this
+pub const MIXAMO_CLIP_NAME: &str = "mixamo.com";
+
+pub fn normalize_bone_name(name: &str) -> &str {
+    name.strip_prefix(MIXAMO_BONE_PREFIX).unwrap_or(name)
+}
+
+// the model's bone with the given name, walking its whole hierarchy
+// so it's best done once when the model spawns
+// mixamo prefixes are optional so that this works with normalized names
+pub fn find_bone(
+    model: Entity,
+    bone: &str,
+    children: &Query<&Children>,
+    names: &Query<&Name>,
+) -> Option<Entity> {
+    children.iter_descendants(model).find(|entity| {
+        names
+            .get(*entity)
+            .is_ok_and(|name| normalize_bone_name(name.as_str()) == normalize_bone_name(bone))
+    })
+}
+
+// maps bone names from the skeleton an animation was authored for
+// to the bone names of the character's skeleton
+// bones that aren't listed keep their name
+//
+// with the source skeleton's rest pose (RetargetSettings::source_skeleton) the clips
+// are converted relative to both rest poses, otherwise only the names are remapped
+// and both skeletons need the same rest pose
+#[derive(Deserialize, Asset, TypePath)]
+pub struct BoneMap {
+    pub bones: HashMap<String, String>,
+}
+
+// how a character's clips are retargeted onto its model
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Reflect)]
+pub struct RetargetSettings {
+    pub bone_map: Option<Handle<BoneMap>>,
+
+    // a model rigged with the skeleton the clips were authored for
+    pub source_skeleton: Option<Handle<Scene>>,
+
+    // drop the mixamo bone prefix from the model's bones
+    // (bone map names are matched against the normalized names)
+    pub normalize_names: bool,
+}
+
+impl RetargetSettings {
+    pub fn is_enabled(&self) -> bool {
+        self.bone_map.is_some() || self.source_skeleton.is_some() || self.normalize_names
+    }
+}
+
+// names from the animation root down to the bone
+pub(crate) fn bone_path<'a>(
+    bone: Entity,
+    root: Entity,
+    name: impl Fn(Entity) -> Option<&'a Name>,
+    parent: impl Fn(Entity) -> Option<Entity>,
+) -> Vec<&'a Name> {
+    let mut path = Vec::new();
+    let mut current = Some(bone);
+    while let Some(node) = current {
+        let Some(name) = name(node) else {
+            break;
+        };
+        path.push(name);
+
+        if node == root {
+            break;
+        }
+        current = parent(node);
+    }
+    path.reverse();
+    path
+}
+
+pub(crate) fn normalized_target_id(path: &[&Name]) -> AnimationTargetId {
+    let path = path
+        .iter()
+        .map(|name| Name::new(normalize_bone_name(name.as_str()).to_string()))
+        .collect::<Vec<_>>();
+    AnimationTargetId::from_names(path.iter())
+}
+
+// whether the curve animates the given field, e.g. animated_field!(Transform::translation)
+pub(crate) fn animates_field(curve: &VariableCurve, field: impl AnimatableProperty) -> bool {
+    matches!(
+        (curve.0.evaluator_id(), field.evaluator_id()),
+        (EvaluatorId::ComponentField(curve_field), EvaluatorId::ComponentField(field))
+            if curve_field == field
+    )
+}
+
+// clips are sampled at this rate when their curves are rebuilt
+// (converted to another rest pose or played in place), the same as mixamo exports them at
+const RETARGET_SAMPLE_RATE: f32 = 30.0;
+
+// a bone's rest pose, locally and from the scene root
+#[derive(Debug, Clone, Copy)]
+struct RestPose {
+    local: Transform,
+    global: Affine3A,
+    parent_global: Affine3A,
+}
+
+impl RestPose {
+    fn new(entity: Entity, transforms: &HashMap<Entity, (Transform, Option<Entity>)>) -> Self {
+        let global = |entity| {
+            let mut global = Affine3A::IDENTITY;
+            let mut current = Some(entity);
+            while let Some(node) = current
+                && let Some((transform, parent)) = transforms.get(&node)
+            {
+                global = transform.compute_affine() * global;
+                current = *parent;
+            }
+            global
+        };
+
+        let (local, parent) = transforms[&entity];
+        Self {
+            local,
+            global: global(entity),
+            parent_global: parent.map(global).unwrap_or(Affine3A::IDENTITY),
+        }
+    }
+}
+
+// every node's transform and parent in the scene
+fn scene_transforms(world: &World) -> HashMap<Entity, (Transform, Option<Entity>)> {
+    let Some(mut nodes) = world.try_query::<(Entity, &Transform, Option<&ChildOf>)>() else {
+        return HashMap::new();
+    };
+    nodes
+        .iter(world)
+        .map(|(entity, transform, parent)| (entity, (*transform, parent.map(ChildOf::parent))))
+        .collect()
+}
+
+// the rest pose of every animated bone in the skeleton, by the clips' targets
+fn skeleton_rest_poses(scene: &Scene) -> HashMap<AnimationTargetId, RestPose> {
+    let world = &scene.world;
+    let transforms = scene_transforms(world);
+    let Some(mut animation_targets) = world.try_query::<(Entity, &AnimationTargetId)>() else {
+        return HashMap::new();
+    };
+    animation_targets
+        .iter(world)
+        .filter(|(entity, _)| transforms.contains_key(entity))
+        .map(|(entity, target_id)| (*target_id, RestPose::new(entity, &transforms)))
+        .collect()
+}
+
+// converts a source bone's animated local transform to the target bone's,
+// applying the same change from rest in world space
+#[derive(Debug, Clone, Copy)]
+struct RestPoseConversion {
+    // target rotation = before * source rotation * after
+    rotation_before: Quat,
+    rotation_after: Quat,
+
+    // from the source parent's space to the target parent's,
+    // scaled by the difference in the skeletons' heights
+    translation: Mat3A,
+    source_translation: Vec3,
+    target_translation: Vec3,
+
+    scale: Vec3,
+}
+
+impl RestPoseConversion {
+    fn new(source: &RestPose, target: &RestPose, height_ratio: f32) -> Self {
+        let rotation = |affine: &Affine3A| affine.to_scale_rotation_translation().1;
+
+        Self {
+            rotation_before: rotation(&target.parent_global).inverse()
+                * rotation(&source.parent_global),
+            rotation_after: rotation(&source.global).inverse() * rotation(&target.global),
+            translation: target.parent_global.matrix3.inverse()
+                * source.parent_global.matrix3
+                * height_ratio,
+            source_translation: source.local.translation,
+            target_translation: target.local.translation,
+            scale: target.local.scale / source.local.scale.max(Vec3::splat(f32::EPSILON)),
+        }
+    }
+
+    fn rotation(&self, rotation: Quat) -> Quat {
+        (self.rotation_before * rotation * self.rotation_after).normalize()
+    }
+
+    fn translation(&self, translation: Vec3) -> Vec3 {
+        self.target_translation + self.translation * (translation - self.source_translation)
+    }
+
+    fn scale(&self, scale: Vec3) -> Vec3 {
+        scale * self.scale
+    }
+}
+
+// samples type erased curves by evaluating them onto a scratch entity's transform
+pub(crate) struct PoseSampler {
+    world: World,
+    entity: Entity,
+    query: QueryState<AnimationEntityMut<'static, 'static>>,
+}
+
+impl PoseSampler {
+    pub(crate) fn new() -> Self {
+        let mut world = World::new();
+        let entity = world.spawn(Transform::default()).id();
+        let query = world.query::<AnimationEntityMut>();
+        Self {
+            world,
+            entity,
+            query,
+        }
+    }
+
+    // the transform's animated field at each sample time, the others are left at rest
+    pub(crate) fn sample(&mut self, curve: &VariableCurve, duration: f32) -> Vec<(f32, Transform)> {
+        let domain = curve.0.domain();
+        let start = if domain.has_finite_start() {
+            domain.start().max(0.0)
+        } else {
+            0.0
+        };
+        let end = if domain.has_finite_end() {
+            domain.end()
+        } else {
+            duration
+        };
+        let samples = ((end - start) * RETARGET_SAMPLE_RATE).ceil().max(0.0) as usize;
+
+        let mut evaluator = curve.0.create_evaluator();
+        (0..=samples)
+            .filter_map(|sample| {
+                let time = (start + sample as f32 / RETARGET_SAMPLE_RATE).min(end.max(start));
+                curve
+                    .0
+                    .apply(evaluator.as_mut(), time, 1.0, AnimationNodeIndex::new(0))
+                    .ok()?;
+                let entity = self.query.get_mut(&mut self.world, self.entity).ok()?;
+                evaluator.commit(entity).ok()?;
+                Some((time, *self.world.get::<Transform>(self.entity)?))
+            })
+            .collect()
+    }
+}
+
+// a clip curve rebuilt from its samples
+pub(crate) fn sampled_curve<P>(
+    property: P,
+    mut samples: Vec<(f32, P::Property)>,
+) -> Option<VariableCurve>
+where
+    P: AnimatableProperty + Clone,
+    P::Property: Clone,
+    ConstantCurve<P::Property>: AnimationCompatibleCurve<P::Property>,
+    AnimatableKeyframeCurve<P::Property>: AnimationCompatibleCurve<P::Property>,
+{
+    samples.dedup_by(|(a, _), (b, _)| a == b);
+    match samples.as_slice() {
+        [] => None,
+        [(_, value)] => Some(VariableCurve::new(AnimatableCurve::new(
+            property,
+            ConstantCurve::new(Interval::EVERYWHERE, value.clone()),
+        ))),
+        _ => AnimatableKeyframeCurve::new(samples)
+            .ok()
+            .map(|curve| VariableCurve::new(AnimatableCurve::new(property, curve))),
+    }
+}
+
+// where a bone on the character's skeleton gets its animation from in the source clips
+#[derive(Debug, Clone, Copy)]
+struct BoneRetarget {
+    source: AnimationTargetId,
+
+    // without the source skeleton's rest pose the curves are used as they are
+    rest_pose: Option<RestPoseConversion>,
+}
+
+impl BoneRetarget {
+    fn retarget_curve(
+        &self,
+        curve: &VariableCurve,
+        duration: f32,
+        sampler: &mut PoseSampler,
+    ) -> Option<VariableCurve> {
+        let Some(rest_pose) = &self.rest_pose else {
+            return Some(curve.clone());
+        };
+
+        if animates_field(curve, animated_field!(Transform::translation)) {
+            let samples = sampler.sample(curve, duration);
+            sampled_curve(
+                animated_field!(Transform::translation),
+                samples
+                    .into_iter()
+                    .map(|(time, pose)| (time, rest_pose.translation(pose.translation)))
+                    .collect(),
+            )
+        } else if animates_field(curve, animated_field!(Transform::rotation)) {
+            let samples = sampler.sample(curve, duration);
+            sampled_curve(
+                animated_field!(Transform::rotation),
+                samples
+                    .into_iter()
+                    .map(|(time, pose)| (time, rest_pose.rotation(pose.rotation)))
+                    .collect(),
+            )
+        } else if animates_field(curve, animated_field!(Transform::scale)) {
+            let samples = sampler.sample(curve, duration);
+            sampled_curve(
+                animated_field!(Transform::scale),
+                samples
+                    .into_iter()
+                    .map(|(time, pose)| (time, rest_pose.scale(pose.scale)))
+                    .collect(),
+            )
+        } else {
+            // e.g. morph weights
+            Some(curve.clone())
+        }
+    }
+}
+
+// animation target on the character's skeleton -> animation target in the source clips
+#[derive(Debug, Clone, Default)]
+pub struct BoneRemap {
+    targets: HashMap<AnimationTargetId, BoneRetarget>,
+}
+
+impl BoneRemap {
+    // walks the model's animation targets and works out
+    // which source bone path each one corresponds to
+    // (and how to convert its animation, given the source skeleton)
+    pub fn from_scene(
+        scene: &Scene,
+        source_skeleton: Option<&Scene>,
+        bone_map: Option<&BoneMap>,
+        normalize_names: bool,
+    ) -> Self {
+        let source_names = bone_map
+            .iter()
+            .flat_map(|bone_map| bone_map.bones.iter())
+            .map(|(source, target)| (target.as_str(), source.as_str()))
+            .collect::<HashMap<_, _>>();
+
+        let world = &scene.world;
+        let Some(mut nodes) = world.try_query::<(Entity, &Name, Option<&ChildOf>)>() else {
+            return Self::default();
+        };
+        let nodes = nodes
+            .iter(world)
+            .map(|(entity, name, parent)| (entity, (name, parent.map(ChildOf::parent))))
+            .collect::<HashMap<_, _>>();
+
+        let Some(mut animation_targets) =
+            world.try_query_filtered::<(Entity, &AnimatedBy), With<AnimationTargetId>>()
+        else {
+            return Self::default();
+        };
+
+        let transforms = scene_transforms(world);
+        let source_rest_poses = source_skeleton.map(skeleton_rest_poses).unwrap_or_default();
+
+        // (target, source, rest poses, depth)
+        let mut bones = Vec::new();
+        for (entity, animated_by) in animation_targets.iter(world) {
+            let path = bone_path(
+                entity,
+                animated_by.0,
+                |node| nodes.get(&node).map(|(name, _)| *name),
+                |node| nodes.get(&node).and_then(|(_, parent)| *parent),
+            );
+
+            let target_id = if normalize_names {
+                normalized_target_id(&path)
+            } else {
+                AnimationTargetId::from_names(path.iter().copied())
+            };
+
+            let source_path = path
+                .iter()
+                .map(|name| {
+                    let target_name = if normalize_names {
+                        normalize_bone_name(name.as_str())
+                    } else {
+                        name.as_str()
+                    };
+                    // unmapped bones keep the model's original name
+                    Name::new(
+                        source_names
+                            .get(target_name)
+                            .copied()
+                            .unwrap_or(name.as_str())
+                            .to_string(),
+                    )
+                })
+                .collect::<Vec<_>>();
+
+            let source_id = AnimationTargetId::from_names(source_path.iter());
+            let rest_poses = source_rest_poses
+                .get(&source_id)
+                .filter(|_| transforms.contains_key(&entity))
+                .map(|source| (*source, RestPose::new(entity, &transforms)));
+            bones.push((target_id, source_id, rest_poses, path.len()));
+        }
+
+        // root translation is scaled by the hips' (the highest up bone with a height) rest heights
+        let height_ratio = bones
+            .iter()
+            .filter_map(|(_, _, rest_poses, depth)| {
+                let (source, target) = rest_poses.as_ref()?;
+                let source_height = source.global.translation.y;
+                let target_height = target.global.translation.y;
+                (source_height > f32::EPSILON && target_height > f32::EPSILON)
+                    .then_some((*depth, target_height / source_height))
+            })
+            .min_by_key(|(depth, _)| *depth)
+            .map_or(1.0, |(_, height_ratio)| height_ratio);
+
+        let targets = bones
+            .into_iter()
+            .map(|(target_id, source, rest_poses, _)| {
+                let rest_pose = rest_poses.map(|(source, target)| {
+                    RestPoseConversion::new(&source, &target, height_ratio)
+                });
+                (target_id, BoneRetarget { source, rest_pose })
+            })
+            .collect();
+
+        Self { targets }
+    }
+
+    pub fn retarget_clip(&self, clip: &AnimationClip) -> AnimationClip {
+        let mut sampler = PoseSampler::new();
+        let mut retargeted = AnimationClip::default();
+        for (target_id, bone) in &self.targets {
+            let Some(curves) = clip.curves_for_target(bone.source) else {
+                continue;
+            };
+
+            for curve in curves {
+                if let Some(curve) = bone.retarget_curve(curve, clip.duration(), &mut sampler) {
+                    retargeted.add_variable_curve_to_target(*target_id, curve);
+                }
+            }
+        }
+        retargeted.set_duration(clip.duration());
+        retargeted
+    }
+}
+
+// the model, bone map, source skeleton and normalization a remap table was built for
+type RetargetKey = (
+    AssetId<Scene>,
+    Option<AssetId<BoneMap>>,
+    Option<AssetId<Scene>>,
+    bool,
+);
+
+// remap tables and retargeted clips, keyed by what they were built for
+// so that characters sharing a model and settings share the retargeted clips
+// (in place clips are cached here as well)
+#[derive(Default, Resource)]
+pub struct RetargetCache {
+    remaps: HashMap<RetargetKey, BoneRemap>,
+    clips: HashMap<(AssetId<AnimationClip>, RetargetKey), Handle<AnimationClip>>,
+    in_place_clips: HashMap<(AssetId<AnimationClip>, AnimationTargetId), Handle<AnimationClip>>,
+}
+
+impl RetargetCache {
+    pub fn retarget_clip(
+        &mut self,
+        clip: &Handle<AnimationClip>,
+        model: &Handle<Scene>,
+        settings: &RetargetSettings,
+        scenes: &Assets<Scene>,
+        bone_maps: &Assets<BoneMap>,
+        animation_clips: &mut Assets<AnimationClip>,
+    ) -> Option<Handle<AnimationClip>> {
+        let key = (
+            model.id(),
+            settings.bone_map.as_ref().map(Handle::id),
+            settings.source_skeleton.as_ref().map(Handle::id),
+            settings.normalize_names,
+        );
+        let clip_key = (clip.id(), key);
+        if let Some(retargeted) = self.clips.get(&clip_key) {
+            return Some(retargeted.clone());
+        }
+
+        let remap = match self.remaps.entry(key) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let bone_map = match &settings.bone_map {
+                    Some(bone_map) => Some(bone_maps.get(bone_map)?),
+                    None => None,
+                };
+                let source_skeleton = match &settings.source_skeleton {
+                    Some(source_skeleton) => Some(scenes.get(source_skeleton)?),
+                    None => None,
+                };
+                let remap = BoneRemap::from_scene(
+                    scenes.get(model)?,
+                    source_skeleton,
+                    bone_map,
+                    settings.normalize_names,
+                );
+                entry.insert(remap)
+            }
+        };
+
+        let retargeted = remap.retarget_clip(animation_clips.get(clip)?);
+        let retargeted = animation_clips.add(retargeted);
+        self.clips.insert(clip_key, retargeted.clone());
+        Some(retargeted)
+    }
+
+    pub fn in_place_clip(
+        &mut self,
+        clip: &Handle<AnimationClip>,
+        root_target: AnimationTargetId,
+        animation_clips: &mut Assets<AnimationClip>,
+    ) -> Option<Handle<AnimationClip>> {
+        let key = (clip.id(), root_target);
+        if let Some(in_place) = self.in_place_clips.get(&key) {
+            return Some(in_place.clone());
+        }
+
+        let in_place = strip_root_translation(animation_clips.get(clip)?, root_target);
+        let in_place = animation_clips.add(in_place);
+        self.in_place_clips.insert(key, in_place.clone());
+        Some(in_place)
+    }
+
+    // drops everything built from clips or models that aren't in use anymore
+    pub(crate) fn release(
+        &mut self,
+        clip_in_use: impl Fn(AssetId<AnimationClip>) -> bool,
+        model_in_use: impl Fn(AssetId<Scene>) -> bool,
+    ) {
+        self.remaps
+            .retain(|(model, _, _, _), _| model_in_use(*model));
+        self.clips
+            .retain(|(clip, (model, _, _, _)), _| clip_in_use(*clip) && model_in_use(*model));
+        self.in_place_clips.retain(|(clip, _), _| {
+            clip_in_use(*clip)
+                || self
+                    .clips
+                    .values()
+                    .any(|retargeted| retargeted.id() == *clip)
+        });
+    }
+
+    // drops everything built from the bone map so that edits are picked up
+    pub fn invalidate_bone_map(&mut self, bone_map: AssetId<BoneMap>) {
+        self.remaps.retain(|(_, id, _, _), _| *id != Some(bone_map));
+        self.clips
+            .retain(|(_, (_, id, _, _)), _| *id != Some(bone_map));
+    }
+}
+
+// renames a spawned model's bones to match the normalized clips
+// the model asset is left alone since other characters may share it
+pub(crate) fn normalize_model_bone_names(
+    scene_ready: On<SceneInstanceReady>,
+    children: Query<&Children>,
+    parents: Query<&ChildOf>,
+    mut names: Query<&mut Name>,
+    mut animation_targets: Query<(&mut AnimationTargetId, &AnimatedBy)>,
+) {
+    // work out the new ids before renaming anything
+    let target_ids = children
+        .iter_descendants(scene_ready.entity)
+        .filter_map(|entity| {
+            let (_, animated_by) = animation_targets.get(entity).ok()?;
+            let path = bone_path(
+                entity,
+                animated_by.0,
+                |node| names.get(node).ok(),
+                |node| parents.get(node).ok().map(ChildOf::parent),
+            );
+            Some((entity, normalized_target_id(&path)))
+        })
+        .collect::<Vec<_>>();
+
+    for (entity, target_id) in target_ids {
+        let (mut animation_target_id, _) = animation_targets.get_mut(entity).unwrap();
+        *animation_target_id = target_id;
+    }
+
+    for entity in children.iter_descendants(scene_ready.entity) {
+        let Ok(mut name) = names.get_mut(entity) else {
+            continue;
+        };
+        if name.as_str().starts_with(MIXAMO_BONE_PREFIX) {
+            let normalized = normalize_bone_name(name.as_str()).to_string();
+            name.set(normalized);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use bevy::{
+    animation::{AnimatedBy, AnimationTargetId, animated_field},
+    prelude::*,
+};
+
+use crate::retarget::*;
+
+// the bone that carries a mixamo clip's root motion
+pub const MIXAMO_ROOT_BONE: &str = "mixamorig:Hips";
+
+// finds the root bone's animation target on the model
+// so that it matches clips that have been retargeted to the model
+pub fn root_bone_target(
+    scene: &Scene,
+    root_bone: &str,
+    normalize_names: bool,
+) -> Option<AnimationTargetId> {
+    let world = &scene.world;
+    let nodes = world
+        .try_query::<(Entity, &Name, Option<&ChildOf>)>()?
+        .iter(world)
+        .map(|(entity, name, parent)| (entity, (name, parent.map(ChildOf::parent))))
+        .collect::<HashMap<_, _>>();
+
+    let mut animation_targets =
+        world.try_query_filtered::<(Entity, &Name, &AnimatedBy), With<AnimationTargetId>>()?;
+    let (entity, _, animated_by) = animation_targets.iter(world).find(|(_, name, _)| {
+        normalize_bone_name(name.as_str()) == normalize_bone_name(root_bone)
+    })?;
+
+    let path = bone_path(
+        entity,
+        animated_by.0,
+        |node| nodes.get(&node).map(|(name, _)| *name),
+        |node| nodes.get(&node).and_then(|(_, parent)| *parent),
+    );
+
+    Some(if normalize_names {
+        normalized_target_id(&path)
+    } else {
+        AnimationTargetId::from_names(path.iter().copied())
+    })
+}
+
+// zeroes the root bone's horizontal translation so that the clip plays in place
+// the height is kept so that bobbing, crouching and jumping still move the character
+pub fn strip_root_translation(
+    clip: &AnimationClip,
+    root_target: AnimationTargetId,
+) -> AnimationClip {
+    let mut in_place = clip.clone();
+    let duration = clip.duration();
+    let mut sampler = PoseSampler::new();
+    if let Some(curves) = in_place.curves_for_target_mut(root_target) {
+        curves.retain_mut(|curve| {
+            if !animates_field(curve, animated_field!(Transform::translation)) {
+                return true;
+            }
+
+            let samples = sampler.sample(curve, duration);
+            let Some(vertical) = sampled_curve(
+                animated_field!(Transform::translation),
+                samples
+                    .into_iter()
+                    .map(|(time, pose)| (time, Vec3::Y * pose.translation.y))
+                    .collect(),
+            ) else {
+                return false;
+            };
+            *curve = vertical;
+            true
+        });
+    }
+    in_place
+}
@@ -0,0 +1,189 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::{
+    prelude::*,
+    remote::{BrpResult, builtin_methods::parse_some},
+    render::view::screenshot::{Screenshot, ScreenshotCaptured, save_to_disk},
+};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::graphics::*;
+use crate::toast::*;
+
+pub struct ScreenshotCapturePlugin;
+
+impl Plugin for ScreenshotCapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, handle_screenshot_keys)
+            .add_observer(on_capture_screenshot);
+    }
+}
+
+// remote method to capture a screenshot, takes CaptureScreenshot as its (optional) params
+pub const SCREENSHOT_METHOD: &str = "mixamo/screenshot";
+
+pub const SCREENSHOT_DIRECTORY: &str = "screenshots";
+
+// saves the next frame to a png, by default screenshots/screenshot-<unix time>.png
+#[derive(Debug, Clone, Default, Event, Deserialize)]
+pub struct CaptureScreenshot {
+    #[serde(default)]
+    pub path: Option<String>,
+
+    // the UI is hidden for the captured frame
+    #[serde(default)]
+    pub hide_ui: bool,
+}
+
+impl CaptureScreenshot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn hide_ui(mut self) -> Self {
+        self.hide_ui = true;
+        self
+    }
+
+    pub fn path(&self) -> String {
+        self.path.clone().unwrap_or_else(|| {
+            let time = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            format!(
+                "{}/screenshot-{}-{:03}.png",
+                SCREENSHOT_DIRECTORY,
+                time.as_secs(),
+                time.subsec_millis()
+            )
+        })
+    }
+}
+
+// the scaled 3d view is a UI node too, but it isn't UI
+pub type UiRootQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static mut Visibility),
+    (
+        With<Node>,
+        Without<ChildOf>,
+        Without<ResolutionScaleViewport>,
+    ),
+>;
+
+// the UI roots a screenshot hid, shown again once it's captured
+#[derive(Component)]
+struct HiddenUi(Vec<(Entity, Visibility)>);
+
+// hides every visible UI root, returning them and their visibility for show_ui
+pub fn hide_ui(ui_roots: &mut UiRootQuery) -> Vec<(Entity, Visibility)> {
+    let mut hidden = Vec::new();
+    for (entity, mut visibility) in ui_roots {
+        if *visibility != Visibility::Hidden {
+            hidden.push((entity, *visibility));
+            *visibility = Visibility::Hidden;
+        }
+    }
+    hidden
+}
+
+pub fn show_ui(hidden: &[(Entity, Visibility)], visibilities: &mut Query<&mut Visibility>) {
+    for (entity, previous) in hidden {
+        if let Ok(mut visibility) = visibilities.get_mut(*entity) {
+            *visibility = *previous;
+        }
+    }
+}
+
+pub fn process_screenshot_request(
+    In(params): In<Option<Value>>,
+    mut commands: Commands,
+) -> BrpResult {
+    let mut capture_screenshot = match params {
+        Some(params) => parse_some::<CaptureScreenshot>(Some(params))?,
+        None => CaptureScreenshot::new(),
+    };
+
+    // resolve the path now so that it can be returned
+    let path = capture_screenshot.path();
+    capture_screenshot.path = Some(path.clone());
+    commands.trigger(capture_screenshot);
+
+    Ok(serde_json::json!({ "path": path }))
+}
+
+// F12 captures the frame, shift + F12 without the UI
+fn handle_screenshot_keys(mut commands: Commands, keyboard: Res<ButtonInput<KeyCode>>) {
+    if !keyboard.just_pressed(KeyCode::F12) {
+        return;
+    }
+
+    let mut capture_screenshot = CaptureScreenshot::new();
+    if keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        capture_screenshot = capture_screenshot.hide_ui();
+    }
+    commands.trigger(capture_screenshot);
+}
+
+fn on_capture_screenshot(
+    event: On<CaptureScreenshot>,
+    mut commands: Commands,
+    mut ui_roots: UiRootQuery,
+) {
+    let path = event.path();
+    if let Some(directory) = std::path::Path::new(&path).parent()
+        && let Err(err) = std::fs::create_dir_all(directory)
+    {
+        warn!(
+            "Failed to create screenshot directory for '{}': {}",
+            path, err
+        );
+        commands.trigger(Toast::error(format!(
+            "Failed to save screenshot to '{path}'"
+        )));
+        return;
+    }
+
+    let hidden = if event.hide_ui {
+        hide_ui(&mut ui_roots)
+    } else {
+        Vec::new()
+    };
+
+    info!("Capturing screenshot to '{}' ...", path);
+    commands
+        .spawn((Screenshot::primary_window(), HiddenUi(hidden)))
+        .observe(save_to_disk(path.clone()))
+        .observe(restore_hidden_ui)
+        .observe(confirm_screenshot_saved(path));
+}
+
+// save_to_disk only logs whether it managed to write the file
+fn confirm_screenshot_saved(path: String) -> impl FnMut(On<ScreenshotCaptured>, Commands) {
+    move |_, mut commands| {
+        commands.trigger(if std::path::Path::new(&path).exists() {
+            Toast::info(format!("Saved screenshot to '{path}'"))
+        } else {
+            Toast::error(format!("Failed to save screenshot to '{path}'"))
+        });
+    }
+}
+
+fn restore_hidden_ui(
+    event: On<ScreenshotCaptured>,
+    hidden_ui: Query<&HiddenUi>,
+    mut visibilities: Query<&mut Visibility>,
+) {
+    let Ok(hidden_ui) = hidden_ui.get(event.entity) else {
+        return;
+    };
+
+    show_ui(&hidden_ui.0, &mut visibilities);
+}
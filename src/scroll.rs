@@ -0,0 +1,258 @@
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+    ui::{RelativeCursorPosition, UiGlobalTransform, UiSystems},
+    window::PrimaryWindow,
+};
+
+pub struct ScrollViewPlugin;
+
+impl Plugin for ScrollViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ScrollView>()
+            .add_observer(add_scroll_view)
+            .add_observer(remove_scroll_view)
+            .add_systems(
+                Update,
+                (scroll_with_mouse_wheel, drag_scrollbar_thumbs).chain(),
+            )
+            // with this frame's scroll position, so the thumb doesn't trail the content
+            .add_systems(
+                PostUpdate,
+                (
+                    place_scrollbar_thumbs.before(UiSystems::Layout),
+                    clamp_scroll_positions.after(UiSystems::Layout),
+                ),
+            );
+    }
+}
+
+// clips its children and scrolls them vertically with the mouse wheel,
+// insert it on a node with a bounded height (e.g. max_height)
+#[derive(Debug, Default, Clone, Copy, Component, Reflect)]
+#[reflect(Component)]
+#[require(ScrollPosition, RelativeCursorPosition)]
+pub struct ScrollView {
+    // a thumb along the right edge that can be dragged, shown while the content doesn't fit
+    pub scrollbar: bool,
+}
+
+impl ScrollView {
+    pub fn with_scrollbar() -> Self {
+        Self { scrollbar: true }
+    }
+}
+
+// a root node of its own, rather than a child of the view,
+// so that views can despawn and rebuild their children without losing it
+#[derive(Component)]
+struct ScrollbarThumb {
+    view: Entity,
+
+    // the cursor's y and the scroll position when the drag started
+    drag: Option<(f32, f32)>,
+}
+
+// on the view
+#[derive(Component)]
+struct ScrollViewThumb(Entity);
+
+// pixels per line when scrolling with a mouse wheel
+const SCROLL_LINE_HEIGHT: f32 = 20.0;
+
+const SCROLLBAR_WIDTH: f32 = 8.0;
+
+const THUMB_COLOR: Color = Color::srgba(0.5, 0.5, 0.5, 0.8);
+const HOVERED_THUMB_COLOR: Color = Color::srgba(0.7, 0.7, 0.7, 0.9);
+
+// over the view, under tooltips and dialogs
+const THUMB_Z_INDEX: i32 = 1;
+
+// in logical pixels, like ScrollPosition
+// matches how far the layout lets the content scroll
+pub fn max_scroll(computed: &ComputedNode) -> f32 {
+    (computed.content_size().y - computed.size().y + computed.scrollbar_size.y).max(0.0)
+        * computed.inverse_scale_factor()
+}
+
+fn add_scroll_view(
+    event: On<Add, ScrollView>,
+    mut commands: Commands,
+    mut views: Query<(&ScrollView, &mut Node)>,
+) {
+    let Ok((view, mut node)) = views.get_mut(event.entity) else {
+        return;
+    };
+
+    node.overflow = Overflow::scroll_y();
+    if !view.scrollbar {
+        return;
+    }
+    node.scrollbar_width = SCROLLBAR_WIDTH;
+
+    let thumb = commands
+        .spawn((
+            Button,
+            Node {
+                position_type: PositionType::Absolute,
+                display: Display::None,
+                ..default()
+            },
+            BackgroundColor(THUMB_COLOR),
+            GlobalZIndex(THUMB_Z_INDEX),
+            Name::new("ScrollbarThumb"),
+            ScrollbarThumb {
+                view: event.entity,
+                drag: None,
+            },
+        ))
+        .id();
+    commands.entity(event.entity).insert(ScrollViewThumb(thumb));
+}
+
+fn remove_scroll_view(
+    event: On<Remove, ScrollView>,
+    mut commands: Commands,
+    views: Query<&ScrollViewThumb>,
+) {
+    if let Ok(thumb) = views.get(event.entity) {
+        commands.entity(thumb.0).try_despawn();
+        commands
+            .entity(event.entity)
+            .try_remove::<ScrollViewThumb>();
+    }
+}
+
+fn scroll_with_mouse_wheel(
+    mut mouse_wheel: MessageReader<MouseWheel>,
+    mut views: Query<
+        (&RelativeCursorPosition, &ComputedNode, &mut ScrollPosition),
+        With<ScrollView>,
+    >,
+) {
+    let dy = mouse_wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y * SCROLL_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => event.y,
+        })
+        .sum::<f32>();
+    if dy == 0.0 {
+        return;
+    }
+
+    for (cursor_position, computed, mut scroll_position) in &mut views {
+        if cursor_position.cursor_over() {
+            scroll_position.y = (scroll_position.y - dy).clamp(0.0, max_scroll(computed));
+        }
+    }
+}
+
+// moves the content as far as the thumb is dragged, relative to the track
+fn drag_scrollbar_thumbs(
+    window: Single<&Window, With<PrimaryWindow>>,
+    ui_scale: Res<UiScale>,
+    mut thumbs: Query<(&Interaction, &mut ScrollbarThumb, &mut BackgroundColor)>,
+    mut views: Query<(&ComputedNode, &mut ScrollPosition)>,
+) {
+    let cursor_y = window
+        .cursor_position()
+        .map(|cursor_position| cursor_position.y / ui_scale.0);
+
+    for (interaction, mut thumb, mut color) in &mut thumbs {
+        color.set_if_neq(
+            if *interaction == Interaction::None {
+                THUMB_COLOR
+            } else {
+                HOVERED_THUMB_COLOR
+            }
+            .into(),
+        );
+
+        let (Interaction::Pressed, Some(cursor_y)) = (interaction, cursor_y) else {
+            thumb.drag = None;
+            continue;
+        };
+        let Ok((computed, mut scroll_position)) = views.get_mut(thumb.view) else {
+            continue;
+        };
+        let Some((track, _)) = computed.vertical_scrollbar() else {
+            continue;
+        };
+
+        let (start_y, start_scroll) = *thumb.drag.get_or_insert((cursor_y, scroll_position.y));
+        let content_per_track = computed.content_size().y / track.height().max(1.0);
+        let scroll = start_scroll + (cursor_y - start_y) * content_per_track;
+        scroll_position.y = scroll.clamp(0.0, max_scroll(computed));
+    }
+}
+
+type ScrollClampQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static ComputedNode, &'static mut ScrollPosition),
+    (
+        With<ScrollView>,
+        Or<(Changed<ComputedNode>, Changed<ScrollPosition>)>,
+    ),
+>;
+
+// the layout only clamps what it shows, so a position set past the end from code
+// (e.g. f32::MAX to scroll to the bottom) is clamped here to keep the wheel and thumb in step
+fn clamp_scroll_positions(mut views: ScrollClampQuery) {
+    for (computed, mut scroll_position) in &mut views {
+        let y = scroll_position.y.clamp(0.0, max_scroll(computed));
+        if scroll_position.y != y {
+            scroll_position.y = y;
+        }
+    }
+}
+
+// sizes and positions each thumb over its view's scrollbar track,
+// from the view's last layout
+fn place_scrollbar_thumbs(
+    views: Query<(
+        &ComputedNode,
+        &UiGlobalTransform,
+        &ScrollPosition,
+        &ScrollViewThumb,
+    )>,
+    mut thumbs: Query<&mut Node, With<ScrollbarThumb>>,
+) {
+    for (computed, transform, scroll_position, thumb) in &views {
+        let Ok(mut thumb_node) = thumbs.get_mut(thumb.0) else {
+            continue;
+        };
+
+        // everything in physical pixels, with the view's center at 0, 0
+        let content_height = computed.content_size().y;
+        let track = computed
+            .vertical_scrollbar()
+            .map(|(track, _)| track)
+            .filter(|track| !computed.is_empty() && content_height > track.height());
+        let Some(track) = track else {
+            if thumb_node.display != Display::None {
+                thumb_node.display = Display::None;
+            }
+            continue;
+        };
+
+        let scale = computed.inverse_scale_factor();
+        let scroll = scroll_position.y.clamp(0.0, max_scroll(computed)) / scale;
+        let thumb_height = track.height() * track.height() / content_height;
+        let thumb_top = track.min.y + scroll * track.height() / content_height;
+        let top_left = (transform.translation + Vec2::new(track.min.x, thumb_top)) * scale;
+
+        let node = Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(top_left.x),
+            top: Val::Px(top_left.y),
+            width: Val::Px(track.width() * scale),
+            height: Val::Px(thumb_height * scale),
+            ..default()
+        };
+        if *thumb_node != node {
+            *thumb_node = node;
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use bevy::prelude::*;
+use bevy_mixamo::*;
+
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(MeshPickingPlugin)
+            .init_resource::<SelectedCharacter>()
+            .add_systems(
+                Update,
+                (mark_selected_animation_player, highlight_selected_character),
+            )
+            .add_observer(select_spawned_character)
+            .add_observer(deselect_despawned_character);
+    }
+}
+
+// the character that the UI panels operate on
+#[derive(Debug, Default, Resource)]
+pub struct SelectedCharacter(pub Option<Entity>);
+
+// on the selected character's AnimationPlayer
+// so that systems can filter for it instead of searching the character's hierarchy
+#[derive(Component)]
+pub struct SelectedAnimationPlayer;
+
+// swapped in for a selected mesh's material, the original is put back on deselect
+#[derive(Component)]
+struct SelectionHighlight {
+    original: Handle<StandardMaterial>,
+}
+
+// in nits, this comes out at about 0.3 after the camera's default exposure
+const HIGHLIGHT_EMISSIVE: LinearRgba = LinearRgba::rgb(300.0, 220.0, 40.0);
+
+// new characters are selected if nothing else is, and can be selected by clicking on them
+fn select_spawned_character(
+    event: On<Add, CharacterModel>,
+    mut commands: Commands,
+    mut selected_character: ResMut<SelectedCharacter>,
+) {
+    if selected_character.0.is_none() {
+        selected_character.0 = Some(event.entity);
+    }
+
+    commands
+        .entity(event.entity)
+        .observe(select_clicked_character);
+}
+
+// clicks on the model's meshes bubble up to the model
+fn select_clicked_character(
+    event: On<Pointer<Click>>,
+    mut selected_character: ResMut<SelectedCharacter>,
+    character_models: Query<&CharacterModel>,
+    character_datum: Res<Assets<CharacterData>>,
+) {
+    if event.event.button != PointerButton::Primary {
+        return;
+    }
+    let Ok(character_model) = character_models.get(event.entity) else {
+        return;
+    };
+    if selected_character.0 == Some(event.entity) {
+        return;
+    }
+
+    if let Some(character_data) = character_datum.get(&character_model.0) {
+        info!("Selected character '{}'", character_data.id);
+    }
+    selected_character.0 = Some(event.entity);
+}
+
+fn deselect_despawned_character(
+    event: On<Remove, CharacterModel>,
+    mut selected_character: ResMut<SelectedCharacter>,
+) {
+    if selected_character.0 == Some(event.entity) {
+        selected_character.0 = None;
+    }
+}
+
+// the player isn't available until the character's scene has spawned
+fn mark_selected_animation_player(
+    mut commands: Commands,
+    selected_character: Res<SelectedCharacter>,
+    animators: Query<&CharacterAnimator>,
+    marked: Query<Entity, With<SelectedAnimationPlayer>>,
+) {
+    let animation_player = selected_character
+        .0
+        .and_then(|entity| animators.get(entity).ok())
+        .map(|animator| animator.player);
+
+    for entity in &marked {
+        if Some(entity) != animation_player {
+            commands.entity(entity).remove::<SelectedAnimationPlayer>();
+        }
+    }
+
+    // the cached player is gone while a model LOD swap respawns the model
+    if let Some(animation_player) = animation_player
+        && !marked.contains(animation_player)
+    {
+        commands
+            .entity(animation_player)
+            .try_insert(SelectedAnimationPlayer);
+    }
+}
+
+// tints the selected character's meshes so it's obvious which model the UI controls
+// this also catches meshes that spawn after their character was selected
+fn highlight_selected_character(
+    mut commands: Commands,
+    selected_character: Res<SelectedCharacter>,
+    children: Query<&Children>,
+    added_meshes: Query<(), Added<MeshMaterial3d<StandardMaterial>>>,
+    mut meshes: Query<(
+        Entity,
+        &mut MeshMaterial3d<StandardMaterial>,
+        Option<&SelectionHighlight>,
+    )>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    if !selected_character.is_changed() && added_meshes.is_empty() {
+        return;
+    }
+
+    let selected_meshes = selected_character
+        .0
+        .map(|entity| {
+            children
+                .iter_descendants(entity)
+                .filter(|child| meshes.contains(*child))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    for (entity, mut material, highlight) in &mut meshes {
+        let selected = selected_meshes.contains(&entity);
+        match (selected, highlight) {
+            (true, None) => {
+                let Some(mut highlighted) = materials.get(&material.0).cloned() else {
+                    continue;
+                };
+                highlighted.emissive += HIGHLIGHT_EMISSIVE;
+
+                commands.entity(entity).insert(SelectionHighlight {
+                    original: material.0.clone(),
+                });
+                material.0 = materials.add(highlighted);
+            }
+            (false, Some(highlight)) => {
+                material.0 = highlight.original.clone();
+                commands.entity(entity).remove::<SelectionHighlight>();
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::VecDeque;
+
+use bevy::{animation::RepeatAnimation, prelude::*};
+
+use crate::character::*;
+
+// one animation in a sequence
+#[derive(Debug, Clone, Reflect)]
+pub struct AnimationSequenceStep {
+    pub animation: String,
+
+    // overrides the animation's loop setting
+    // a looping step never finishes, so it holds the rest of the sequence
+    pub repeat: Option<bool>,
+
+    // overrides the character's crossfade duration (in seconds)
+    pub blend_time: Option<f32>,
+}
+
+impl AnimationSequenceStep {
+    pub fn new(animation: impl Into<String>) -> Self {
+        Self {
+            animation: animation.into(),
+            repeat: None,
+            blend_time: None,
+        }
+    }
+
+    pub fn with_repeat(mut self, repeat: bool) -> Self {
+        self.repeat = Some(repeat);
+        self
+    }
+
+    pub fn with_blend_time(mut self, blend_time: f32) -> Self {
+        self.blend_time = Some(blend_time);
+        self
+    }
+}
+
+impl From<&str> for AnimationSequenceStep {
+    fn from(animation: &str) -> Self {
+        Self::new(animation)
+    }
+}
+
+impl From<String> for AnimationSequenceStep {
+    fn from(animation: String) -> Self {
+        Self::new(animation)
+    }
+}
+
+// queues animations on a character model, each plays once the one before it finishes
+// added to every character model, don't combine it with a state machine
+// since both drive the character's transitions
+#[derive(Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct AnimationSequence {
+    current: Option<AnimationSequenceStep>,
+    steps: VecDeque<AnimationSequenceStep>,
+
+    // play the next step now rather than waiting for the current one
+    interrupt: bool,
+}
+
+impl AnimationSequence {
+    pub fn current(&self) -> Option<&AnimationSequenceStep> {
+        self.current.as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    // replaces the queue and starts the first step straight away
+    pub fn play_sequence<S>(&mut self, steps: impl IntoIterator<Item = S>)
+    where
+        S: Into<AnimationSequenceStep>,
+    {
+        self.steps = steps.into_iter().map(Into::into).collect();
+        self.interrupt = true;
+    }
+
+    // adds a step to the end of the queue
+    pub fn queue(&mut self, step: impl Into<AnimationSequenceStep>) {
+        self.steps.push_back(step.into());
+    }
+
+    // drops the queued steps, the current animation keeps playing
+    pub fn clear(&mut self) {
+        self.steps.clear();
+        self.interrupt = false;
+    }
+}
+
+pub(crate) fn update_animation_sequences(
+    character_datum: Res<Assets<CharacterData>>,
+    characters: Res<Characters>,
+    mut sequences: Query<(&CharacterAnimator, &CharacterModel, &mut AnimationSequence)>,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+) {
+    for (animator, character_model, mut sequence) in &mut sequences {
+        if sequence.steps.is_empty() {
+            continue;
+        }
+        let Some(character_data) = character_datum.get(&character_model.0) else {
+            continue;
+        };
+        let Some(character) = characters.get(&character_data.id) else {
+            continue;
+        };
+
+        // masked steps don't become the main animation, so check the step's own node
+        let finished = sequence
+            .current
+            .as_ref()
+            .and_then(|step| {
+                let (player, _) = animation_players
+                    .get(animator.player_for(character, &step.animation))
+                    .ok()?;
+                player
+                    .animation(character.animation_index(&step.animation)?)
+                    .map(|animation| animation.is_finished())
+            })
+            .unwrap_or(true);
+        if !sequence.interrupt && !finished {
+            continue;
+        }
+
+        let Ok((mut player, mut transitions)) = animation_players
+            .get_mut(animator.player_for(character, &sequence.steps.front().unwrap().animation))
+        else {
+            continue;
+        };
+
+        let step = sequence.steps.pop_front().unwrap();
+        let transition_duration = match step.blend_time {
+            Some(blend_time) => crossfade_duration(blend_time),
+            None => {
+                let current_animation = transitions
+                    .get_main_animation()
+                    .and_then(|index| character.animation_name(index));
+                character_data.transition_duration(current_animation, &step.animation)
+            }
+        };
+
+        debug!(
+            "Character '{}' playing sequence animation '{}'",
+            character_data.id, step.animation
+        );
+
+        if let Some(animation) = play_animation_with_transition(
+            &mut player,
+            &mut transitions,
+            character,
+            &step.animation,
+            transition_duration,
+        ) {
+            if let Some(repeat) = step.repeat {
+                animation.set_repeat(if repeat {
+                    RepeatAnimation::Forever
+                } else {
+                    RepeatAnimation::Never
+                });
+            }
+        } else {
+            warn!(
+                "Character '{}' has no animation '{}' to sequence",
+                character_data.id, step.animation
+            );
+        }
+
+        sequence.current = Some(step);
+        sequence.interrupt = false;
+    }
+}
@@ -0,0 +1,174 @@
+use bevy::{prelude::*, window::PresentMode};
+use bevy_mixamo::*;
+use serde::{Deserialize, Serialize};
+
+use crate::framing::*;
+use crate::graphics::*;
+use crate::selection::*;
+
+// restores the viewer's state from settings.toml and saves it back on exit
+pub struct SettingsPlugin(pub AppSettings);
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.0.clone())
+            .insert_resource(UiScale(self.0.ui_scale))
+            .add_systems(Update, track_window_settings)
+            .add_systems(Last, save_settings_on_exit)
+            .add_observer(restore_orbit_camera);
+    }
+}
+
+// relative to where the viewer is run from
+pub const SETTINGS_PATH: &str = "settings.toml";
+
+// command line options win over these
+#[derive(Debug, Clone, Resource, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    pub width: u32,
+    pub height: u32,
+    pub vsync: bool,
+    pub ui_scale: f32,
+
+    // None until the viewer has been closed once
+    pub camera: Option<CameraSettings>,
+
+    // the selected character and the animation it was playing
+    pub character: Option<String>,
+    pub animation: Option<String>,
+
+    // the settings panel's open tab
+    pub settings_tab: usize,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            width: 1280,
+            height: 720,
+            vsync: false,
+            ui_scale: 1.0,
+            camera: None,
+            character: None,
+            animation: None,
+            settings_tab: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CameraSettings {
+    pub focus: [f32; 3],
+    pub distance: f32,
+    pub yaw: f32,
+    pub elevation: f32,
+}
+
+impl AppSettings {
+    // the defaults if there's no settings file yet
+    pub fn load() -> Result<Self, String> {
+        let toml = match std::fs::read_to_string(SETTINGS_PATH) {
+            Ok(toml) => toml,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(err) => return Err(format!("Failed to read '{}': {}", SETTINGS_PATH, err)),
+        };
+        toml::from_str(&toml).map_err(|err| format!("Failed to parse '{}': {}", SETTINGS_PATH, err))
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let toml = toml::to_string(self).map_err(|err| err.to_string())?;
+        std::fs::write(SETTINGS_PATH, toml)
+            .map_err(|err| format!("Failed to write '{}': {}", SETTINGS_PATH, err))
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+}
+
+fn restore_orbit_camera(
+    event: On<Add, OrbitCamera>,
+    mut commands: Commands,
+    settings: Res<AppSettings>,
+    mut cameras: Query<(&mut OrbitCamera, &mut Transform)>,
+) {
+    let Some(camera) = settings.camera else {
+        return;
+    };
+    let Ok((mut orbit_camera, mut transform)) = cameras.get_mut(event.entity) else {
+        return;
+    };
+
+    orbit_camera.focus = Vec3::from(camera.focus);
+    orbit_camera.distance = camera.distance;
+    orbit_camera.yaw = camera.yaw;
+    orbit_camera.elevation = camera.elevation;
+    *transform = orbit_camera.transform();
+
+    // don't frame the first character over it
+    commands.insert_resource(KeepCamera);
+}
+
+// the window is gone by the time the app exits, so it's tracked as it changes
+fn track_window_settings(
+    mut settings: ResMut<AppSettings>,
+    windows: Query<&Window, Changed<Window>>,
+) {
+    for window in &windows {
+        settings.width = window.resolution.physical_width();
+        settings.height = window.resolution.physical_height();
+        settings.vsync = is_vsync(window.present_mode);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_settings_on_exit(
+    mut app_exit: MessageReader<AppExit>,
+    mut settings: ResMut<AppSettings>,
+    ui_scale: Res<UiScale>,
+    cameras: Query<&OrbitCamera>,
+    selected_character: Res<SelectedCharacter>,
+    character_datum: Res<Assets<CharacterData>>,
+    characters: Res<Characters>,
+    character_models: Query<&CharacterModel>,
+    animation_players: Query<&AnimationTransitions, With<SelectedAnimationPlayer>>,
+) {
+    if app_exit.read().count() == 0 {
+        return;
+    }
+
+    settings.ui_scale = ui_scale.0;
+    if let Some(orbit_camera) = cameras.iter().next() {
+        settings.camera = Some(CameraSettings {
+            focus: orbit_camera.focus.to_array(),
+            distance: orbit_camera.distance,
+            yaw: orbit_camera.yaw,
+            elevation: orbit_camera.elevation,
+        });
+    }
+
+    // keep the last character if none is selected
+    if let Some(character_data) = selected_character
+        .0
+        .and_then(|entity| character_models.get(entity).ok())
+        .and_then(|character_model| character_datum.get(&character_model.0))
+    {
+        settings.character = Some(character_data.id.clone());
+        settings.animation = characters.get(&character_data.id).and_then(|character| {
+            let animation_index = animation_players.iter().next()?.get_main_animation()?;
+            character
+                .animation_name(animation_index)
+                .map(str::to_string)
+        });
+    }
+
+    match settings.save() {
+        Ok(()) => info!("Saved settings to '{}'", SETTINGS_PATH),
+        Err(err) => warn!("{}", err),
+    }
+}
@@ -0,0 +1,133 @@
+use bevy::{mesh::skinning::SkinnedMesh, prelude::*};
+use bevy_mixamo::*;
+
+pub struct SkeletonGizmoPlugin;
+
+impl Plugin for SkeletonGizmoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_gizmo_group::<SkeletonGizmos>()
+            .init_resource::<SelectedBone>()
+            .add_systems(Startup, setup_skeleton_gizmos)
+            .add_systems(Update, (toggle_skeleton_gizmos, draw_skeletons).chain());
+    }
+}
+
+// the character's bones, B toggles them
+#[derive(Default, Reflect, GizmoConfigGroup)]
+pub struct SkeletonGizmos;
+
+// highlighted by the skeleton gizmo
+#[derive(Debug, Default, Resource)]
+pub struct SelectedBone(pub Option<Entity>);
+
+const JOINT_RADIUS: f32 = 0.015;
+
+// hue step between each level of the hierarchy
+const DEPTH_HUE_STEP: f32 = 25.0;
+
+const SELECTED_JOINT_RADIUS: f32 = 0.03;
+
+// the model's bones, sorted so they can be binary searched
+// every skinned mesh in the model shares (some of) the same joints
+pub fn skeleton_joints(
+    entity: Entity,
+    children: &Query<&Children>,
+    skinned_meshes: &Query<&SkinnedMesh>,
+) -> Vec<Entity> {
+    let mut joints = children
+        .iter_descendants(entity)
+        .filter_map(|child| skinned_meshes.get(child).ok())
+        .flat_map(|skinned_mesh| skinned_mesh.joints.iter().copied())
+        .collect::<Vec<_>>();
+    joints.sort();
+    joints.dedup();
+    joints
+}
+
+// the closest joint above this one and how many joints are above it
+pub fn parent_joint(
+    joint: Entity,
+    joints: &[Entity],
+    parents: &Query<&ChildOf>,
+) -> (Option<Entity>, usize) {
+    let mut depth = 0;
+    let mut parent_joint = None;
+    for ancestor in parents.iter_ancestors(joint) {
+        if joints.binary_search(&ancestor).is_ok() {
+            parent_joint.get_or_insert(ancestor);
+            depth += 1;
+        }
+    }
+    (parent_joint, depth)
+}
+
+fn setup_skeleton_gizmos(mut config_store: ResMut<GizmoConfigStore>) {
+    let (config, _) = config_store.config_mut::<SkeletonGizmos>();
+    config.enabled = false;
+    config.line.width = 3.0;
+
+    // draw on top of the model
+    config.depth_bias = -1.0;
+}
+
+fn toggle_skeleton_gizmos(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut config_store: ResMut<GizmoConfigStore>,
+) {
+    if keyboard.just_pressed(KeyCode::KeyB) {
+        let (config, _) = config_store.config_mut::<SkeletonGizmos>();
+        config.enabled = !config.enabled;
+    }
+}
+
+fn draw_skeletons(
+    mut gizmos: Gizmos<SkeletonGizmos>,
+    selected_bone: Res<SelectedBone>,
+    character_models: Query<Entity, With<CharacterModel>>,
+    children: Query<&Children>,
+    skinned_meshes: Query<&SkinnedMesh>,
+    parents: Query<&ChildOf>,
+    transforms: Query<&GlobalTransform>,
+) {
+    if !gizmos.config.enabled {
+        return;
+    }
+
+    for entity in &character_models {
+        let joints = skeleton_joints(entity, &children, &skinned_meshes);
+
+        for &joint in &joints {
+            let Ok(transform) = transforms.get(joint) else {
+                continue;
+            };
+
+            let (parent_joint, depth) = parent_joint(joint, &joints, &parents);
+            let color = Color::hsl((depth as f32 * DEPTH_HUE_STEP) % 360.0, 0.8, 0.5);
+
+            gizmos.sphere(
+                Isometry3d::from_translation(transform.translation()),
+                JOINT_RADIUS,
+                color,
+            );
+
+            if let Some(parent_transform) =
+                parent_joint.and_then(|parent_joint| transforms.get(parent_joint).ok())
+            {
+                gizmos.line(
+                    parent_transform.translation(),
+                    transform.translation(),
+                    color,
+                );
+            }
+
+            if selected_bone.0 == Some(joint) {
+                gizmos.sphere(
+                    Isometry3d::from_translation(transform.translation()),
+                    SELECTED_JOINT_RADIUS,
+                    Color::WHITE,
+                );
+                gizmos.axes(*transform, 0.1);
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use bevy::{input::InputSystems, prelude::*, ui::RelativeCursorPosition};
+
+use crate::focus::*;
+
+pub struct SliderPlugin;
+
+impl Plugin for SliderPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            step_focused_slider.after(InputSystems).before(FocusSystems),
+        )
+        .add_systems(
+            Update,
+            (handle_slider_interactions, update_slider_fills).chain(),
+        );
+    }
+}
+
+#[derive(Component)]
+pub struct Slider {
+    pub min: f32,
+    pub max: f32,
+    pub value: f32,
+    dragging: bool,
+}
+
+impl Slider {
+    pub fn is_dragging(&self) -> bool {
+        self.dragging
+    }
+
+    // for driving the slider from code, this doesn't trigger SliderChanged
+    pub fn set_value(&mut self, value: f32) {
+        self.value = value.clamp(self.min, self.max);
+    }
+
+    fn fraction(&self) -> f32 {
+        if self.max > self.min {
+            (self.value - self.min) / (self.max - self.min)
+        } else {
+            0.0
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct SliderTrack;
+
+#[derive(Component)]
+pub struct SliderFill;
+
+#[derive(Event)]
+pub struct SliderChanged {
+    pub entity: Entity,
+    pub value: f32,
+}
+
+const TRACK_COLOR: Color = Color::srgb(0.15, 0.15, 0.15);
+const FILL_COLOR: Color = Color::srgb(0.35, 0.75, 0.35);
+
+// how much of the range left / right moves a focused slider
+const FOCUSED_STEP: f32 = 0.05;
+
+pub fn spawn_slider<'a>(
+    commands: &'a mut Commands,
+    position: Vec2,
+    size: Vec2,
+    label: impl Into<String>,
+    min: f32,
+    max: f32,
+    value: f32,
+) -> EntityCommands<'a> {
+    let slider = Slider {
+        min,
+        max,
+        value: value.clamp(min, max),
+        dragging: false,
+    };
+    let fraction = slider.fraction();
+
+    let mut entity_commands = commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(position.x),
+            top: Val::Px(position.y),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(5.0),
+            ..default()
+        },
+        slider,
+    ));
+
+    entity_commands.with_children(|parent| {
+        // label
+        parent.spawn((
+            Text::new(label.into()),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+
+        // track
+        parent
+            .spawn((
+                Button,
+                Node {
+                    width: Val::Px(size.x),
+                    height: Val::Px(size.y),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                BorderColor::all(Color::BLACK),
+                BackgroundColor(TRACK_COLOR),
+                RelativeCursorPosition::default(),
+                Focusable,
+                SliderTrack,
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    Node {
+                        width: Val::Percent(100.0 * fraction),
+                        height: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(FILL_COLOR),
+                    SliderFill,
+                ));
+            });
+    });
+
+    entity_commands
+}
+
+fn handle_slider_interactions(
+    mut commands: Commands,
+    tracks: Query<(&Interaction, &RelativeCursorPosition, &ChildOf), With<SliderTrack>>,
+    mut sliders: Query<&mut Slider>,
+) {
+    // dragging just keeps the track pressed
+    for (interaction, cursor_position, parent) in &tracks {
+        let Ok(mut slider) = sliders.get_mut(parent.parent()) else {
+            continue;
+        };
+
+        let dragging = *interaction == Interaction::Pressed;
+        if slider.dragging != dragging {
+            slider.dragging = dragging;
+        }
+        if !dragging {
+            continue;
+        }
+        let Some(normalized) = cursor_position.normalized else {
+            continue;
+        };
+
+        // normalized is relative to the center of the track
+        let fraction = (normalized.x + 0.5).clamp(0.0, 1.0);
+        let value = slider.min + fraction * (slider.max - slider.min);
+        if value == slider.value {
+            continue;
+        }
+        slider.value = value;
+
+        commands.trigger(SliderChanged {
+            entity: parent.parent(),
+            value,
+        });
+    }
+}
+
+// the track takes the focus, left / right (on the keyboard or the d-pad) then move the slider
+// rather than the focus
+fn step_focused_slider(
+    mut commands: Commands,
+    focus: Res<UiFocus>,
+    mut keyboard: ResMut<ButtonInput<KeyCode>>,
+    mut gamepads: Query<&mut Gamepad>,
+    tracks: Query<&ChildOf, With<SliderTrack>>,
+    mut sliders: Query<&mut Slider>,
+) {
+    let Some(parent) = focus.entity.and_then(|entity| tracks.get(entity).ok()) else {
+        return;
+    };
+    let Ok(mut slider) = sliders.get_mut(parent.parent()) else {
+        return;
+    };
+
+    let mut step = 0.0;
+    if take_input(
+        &mut keyboard,
+        &mut gamepads,
+        Some(KeyCode::ArrowLeft),
+        Some(GamepadButton::DPadLeft),
+    ) {
+        step -= FOCUSED_STEP;
+    }
+    if take_input(
+        &mut keyboard,
+        &mut gamepads,
+        Some(KeyCode::ArrowRight),
+        Some(GamepadButton::DPadRight),
+    ) {
+        step += FOCUSED_STEP;
+    }
+
+    let value = (slider.value + step * (slider.max - slider.min)).clamp(slider.min, slider.max);
+    if value == slider.value {
+        return;
+    }
+    slider.value = value;
+
+    commands.trigger(SliderChanged {
+        entity: parent.parent(),
+        value,
+    });
+}
+
+fn update_slider_fills(
+    sliders: Query<(&Slider, &Children), Changed<Slider>>,
+    tracks: Query<&Children, With<SliderTrack>>,
+    mut fills: Query<&mut Node, With<SliderFill>>,
+) {
+    for (slider, children) in &sliders {
+        for track_children in tracks.iter_many(children) {
+            let mut fills = fills.iter_many_mut(track_children);
+            while let Some(mut fill) = fills.fetch_next() {
+                fill.width = Val::Percent(100.0 * slider.fraction());
+            }
+        }
+    }
+}
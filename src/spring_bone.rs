@@ -0,0 +1,219 @@
+use bevy::{prelude::*, scene::SceneInstanceReady};
+use serde::Deserialize;
+
+use crate::character::*;
+use crate::ik::*;
+use crate::retarget::*;
+
+// a chain of bones (hair, a tail, cloth flaps) that lags behind and swings with the animation,
+// loaded as part of the character data
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct SpringChainData {
+    // from the root of the chain to its tip, each bone must be the parent of the next
+    // the tip only gives the last bone its length, it isn't simulated itself
+    pub bones: Vec<String>,
+
+    // how hard the bones are pulled back towards the animated pose
+    #[serde(default = "default_spring_stiffness")]
+    pub stiffness: f32,
+
+    // how quickly the swinging dies down, per second
+    #[serde(default = "default_spring_damping")]
+    pub damping: f32,
+
+    // world space acceleration
+    #[serde(default)]
+    pub gravity: [f32; 3],
+}
+
+fn default_spring_stiffness() -> f32 {
+    100.0
+}
+
+fn default_spring_damping() -> f32 {
+    10.0
+}
+
+// large frame times would overshoot the spring, so those are simulated as this
+const MAX_SPRING_DELTA: f32 = 1.0 / 30.0;
+
+#[derive(Debug)]
+struct SpringJoint {
+    bone: Entity,
+    // the next bone in the chain, its position is the bone's tail
+    tail: Entity,
+
+    // world space tail positions, None until the first update
+    current: Option<Vec3>,
+    previous: Vec3,
+}
+
+#[derive(Debug)]
+struct SpringChain {
+    joints: Vec<SpringJoint>,
+    stiffness: f32,
+    damping: f32,
+    gravity: Vec3,
+}
+
+// added to the character model when its data has spring bone chains
+#[derive(Debug, Component)]
+pub struct SpringBones {
+    pub enabled: bool,
+
+    chains: Vec<SpringChain>,
+}
+
+impl SpringBones {
+    // drops the simulated motion, e.g. after teleporting the model
+    pub fn reset(&mut self) {
+        for joint in self.chains.iter_mut().flat_map(|chain| &mut chain.joints) {
+            joint.current = None;
+        }
+    }
+}
+
+// finds the model's chain bones once the scene spawns
+pub(crate) fn setup_spring_bones(
+    scene_ready: On<SceneInstanceReady>,
+    mut commands: Commands,
+    character_datum: Res<Assets<CharacterData>>,
+    character_models: Query<&CharacterModel>,
+    children: Query<&Children>,
+    parents: Query<&ChildOf>,
+    names: Query<&Name>,
+) {
+    let Ok(character_model) = character_models.get(scene_ready.entity) else {
+        return;
+    };
+    let Some(character_data) = character_datum.get(&character_model.0) else {
+        return;
+    };
+    if character_data.spring_bones.is_empty() {
+        return;
+    }
+
+    let find_bone = |bone: &str| find_bone(scene_ready.entity, bone, &children, &names);
+    let is_parent = |parent: Entity, child: Entity| {
+        parents
+            .get(child)
+            .is_ok_and(|child_of| child_of.parent() == parent)
+    };
+
+    let mut chains = Vec::new();
+    for chain_data in &character_data.spring_bones {
+        let Some(bones) = chain_data
+            .bones
+            .iter()
+            .map(|bone| find_bone(bone))
+            .collect::<Option<Vec<_>>>()
+        else {
+            warn!(
+                "Character '{}' is missing spring bones {:?}",
+                character_data.id, chain_data.bones
+            );
+            continue;
+        };
+
+        if bones.len() < 2 || bones.windows(2).any(|pair| !is_parent(pair[0], pair[1])) {
+            warn!(
+                "Character '{}' spring bones {:?} aren't a chain",
+                character_data.id, chain_data.bones
+            );
+            continue;
+        }
+
+        chains.push(SpringChain {
+            joints: bones
+                .windows(2)
+                .map(|pair| SpringJoint {
+                    bone: pair[0],
+                    tail: pair[1],
+                    current: None,
+                    previous: Vec3::ZERO,
+                })
+                .collect(),
+            stiffness: chain_data.stiffness,
+            damping: chain_data.damping,
+            gravity: Vec3::from_array(chain_data.gravity),
+        });
+    }
+
+    commands.entity(scene_ready.entity).insert(SpringBones {
+        enabled: true,
+        chains,
+    });
+}
+
+// verlet integrated springs pulling each bone's tail towards where the animation put it
+// chains are solved from the root out, so each bone swings from its parent's new pose
+pub(crate) fn solve_spring_bones(
+    time: Res<Time>,
+    mut models: Query<&mut SpringBones>,
+    parents: Query<&ChildOf>,
+    mut transforms: Query<&mut Transform>,
+) {
+    let delta = time.delta_secs().min(MAX_SPRING_DELTA);
+    if delta <= 0.0 {
+        return;
+    }
+
+    for mut spring_bones in &mut models {
+        if !spring_bones.enabled {
+            continue;
+        }
+
+        for chain in &mut spring_bones.chains {
+            let damping = (1.0 - chain.damping * delta).clamp(0.0, 1.0);
+            for joint in &mut chain.joints {
+                let (Ok(bone), Ok(tail)) = (
+                    transforms.get(joint.bone).copied(),
+                    transforms.get(joint.tail).copied(),
+                ) else {
+                    continue;
+                };
+
+                let parent_global = match parents.get(joint.bone) {
+                    Ok(child_of) => {
+                        compute_global_transform(child_of.parent(), &parents, &transforms)
+                    }
+                    Err(_) => GlobalTransform::IDENTITY,
+                };
+                let bone_global = parent_global * bone;
+                let head = bone_global.translation();
+                let animated_tail = (bone_global * tail).translation();
+                let length = head.distance(animated_tail);
+                if length <= f32::EPSILON {
+                    continue;
+                }
+
+                let current = joint.current.unwrap_or(animated_tail);
+                let previous = if joint.current.is_some() {
+                    joint.previous
+                } else {
+                    animated_tail
+                };
+
+                let acceleration = (animated_tail - current) * chain.stiffness + chain.gravity;
+                let next = current + (current - previous) * damping + acceleration * delta * delta;
+
+                // the bone can swing but not stretch
+                let next = head
+                    + (next - head)
+                        .try_normalize()
+                        .unwrap_or((animated_tail - head) / length)
+                        * length;
+                joint.previous = current;
+                joint.current = Some(next);
+
+                let swing = Quat::from_rotation_arc(
+                    (animated_tail - head) / length,
+                    (next - head) / length,
+                );
+                let bone_rotation = bone_global.rotation();
+                transforms.get_mut(joint.bone).unwrap().rotation =
+                    bone.rotation * (bone_rotation.inverse() * swing * bone_rotation);
+            }
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{animation::RepeatAnimation, prelude::*};
+use serde::Deserialize;
+
+use crate::character::*;
+
+// state machine description, loaded as part of the character data
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct AnimationStateMachineData {
+    pub initial_state: String,
+    pub states: HashMap<String, AnimationStateData>,
+
+    // evaluated in order, the first transition whose conditions pass is taken
+    #[serde(default)]
+    pub transitions: Vec<AnimationStateTransition>,
+}
+
+impl AnimationStateMachineData {
+    fn next_transition(
+        &self,
+        state: &str,
+        state_machine: &AnimationStateMachine,
+        finished: bool,
+    ) -> Option<&AnimationStateTransition> {
+        self.transitions.iter().find(|transition| {
+            (transition.from == state || transition.from == ANY_STATE)
+                && transition.to != state
+                && transition
+                    .conditions
+                    .iter()
+                    .all(|condition| condition.check(state_machine, finished))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct AnimationStateData {
+    pub animation: String,
+
+    // overrides the animation's loop setting
+    #[serde(default)]
+    pub repeat: Option<bool>,
+}
+
+// transitions "from" this state are valid from every state
+pub const ANY_STATE: &str = "*";
+
+#[derive(Debug, Clone, Deserialize, Reflect)]
+pub struct AnimationStateTransition {
+    pub from: String,
+    pub to: String,
+
+    #[serde(default)]
+    pub conditions: Vec<AnimationCondition>,
+
+    // overrides the character's crossfade duration (in seconds)
+    #[serde(default)]
+    pub blend_time: Option<f32>,
+}
+
+#[derive(Debug, Clone, Deserialize, Reflect)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AnimationCondition {
+    Bool { parameter: String, value: bool },
+    Greater { parameter: String, value: f32 },
+    Less { parameter: String, value: f32 },
+    Trigger { parameter: String },
+    Finished,
+}
+
+impl AnimationCondition {
+    fn check(&self, state_machine: &AnimationStateMachine, finished: bool) -> bool {
+        match self {
+            Self::Bool { parameter, value } => state_machine.get_bool(parameter) == *value,
+            Self::Greater { parameter, value } => state_machine.get_float(parameter) > *value,
+            Self::Less { parameter, value } => state_machine.get_float(parameter) < *value,
+            Self::Trigger { parameter } => state_machine.triggers.contains(parameter),
+            Self::Finished => finished,
+        }
+    }
+}
+
+// runtime state machine parameters, added to the character model
+// when its data defines a state machine
+#[derive(Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct AnimationStateMachine {
+    current_state: Option<String>,
+    floats: HashMap<String, f32>,
+    bools: HashMap<String, bool>,
+    triggers: HashSet<String>,
+}
+
+impl AnimationStateMachine {
+    pub fn current_state(&self) -> Option<&str> {
+        self.current_state.as_deref()
+    }
+
+    pub fn get_float(&self, parameter: impl AsRef<str>) -> f32 {
+        self.floats
+            .get(parameter.as_ref())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn set_float(&mut self, parameter: impl Into<String>, value: f32) {
+        self.floats.insert(parameter.into(), value);
+    }
+
+    pub fn get_bool(&self, parameter: impl AsRef<str>) -> bool {
+        self.bools
+            .get(parameter.as_ref())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn set_bool(&mut self, parameter: impl Into<String>, value: bool) {
+        self.bools.insert(parameter.into(), value);
+    }
+
+    // triggers are only checked the next time the state machine updates,
+    // after which they're dropped whether or not a transition took them
+    pub fn set_trigger(&mut self, parameter: impl Into<String>) {
+        self.triggers.insert(parameter.into());
+    }
+}
+
+pub(crate) fn update_animation_state_machines(
+    character_datum: Res<Assets<CharacterData>>,
+    characters: Res<Characters>,
+    mut state_machines: Query<(
+        &CharacterAnimator,
+        &CharacterModel,
+        &mut AnimationStateMachine,
+    )>,
+    mut animation_players: Query<(&mut AnimationPlayer, &mut AnimationTransitions)>,
+) {
+    for (animator, character_model, mut state_machine) in &mut state_machines {
+        let Some(character_data) = character_datum.get(&character_model.0) else {
+            continue;
+        };
+        let Some(state_machine_data) = &character_data.state_machine else {
+            continue;
+        };
+        let Some(character) = characters.get(&character_data.id) else {
+            continue;
+        };
+
+        let next_transition = match state_machine.current_state() {
+            None => Some((state_machine_data.initial_state.clone(), Some(0.0))),
+            Some(current_state) => {
+                // checked on the player that the current state's animation plays on
+                let finished = state_machine_data
+                    .states
+                    .get(current_state)
+                    .and_then(|state| {
+                        let (player, transitions) = animation_players
+                            .get(animator.player_for(character, &state.animation))
+                            .ok()?;
+                        transitions
+                            .get_main_animation()
+                            .and_then(|index| player.animation(index))
+                            .map(|animation| animation.is_finished())
+                    })
+                    .unwrap_or(true);
+
+                state_machine_data
+                    .next_transition(current_state, &state_machine, finished)
+                    .map(|transition| (transition.to.clone(), transition.blend_time))
+            }
+        };
+
+        // so that a trigger no transition wanted can't fire one much later
+        state_machine.triggers.clear();
+
+        let Some((next_state, blend_time)) = next_transition else {
+            continue;
+        };
+
+        let Some(state) = state_machine_data.states.get(&next_state) else {
+            warn!(
+                "Character '{}' has no animation state '{}'",
+                character_data.id, next_state
+            );
+            continue;
+        };
+
+        let Ok((mut player, mut transitions)) =
+            animation_players.get_mut(animator.player_for(character, &state.animation))
+        else {
+            continue;
+        };
+
+        debug!(
+            "Character '{}' entering animation state '{}'",
+            character_data.id, next_state
+        );
+
+        let transition_duration = match blend_time {
+            Some(blend_time) => crossfade_duration(blend_time),
+            None => {
+                let current_animation = transitions
+                    .get_main_animation()
+                    .and_then(|index| character.animation_name(index));
+                character_data.transition_duration(current_animation, &state.animation)
+            }
+        };
+
+        if let Some(animation) = play_animation_with_transition(
+            &mut player,
+            &mut transitions,
+            character,
+            &state.animation,
+            transition_duration,
+        ) {
+            if let Some(repeat) = state.repeat {
+                animation.set_repeat(if repeat {
+                    RepeatAnimation::Forever
+                } else {
+                    RepeatAnimation::Never
+                });
+            }
+        } else {
+            warn!(
+                "Character '{}' has no animation '{}' for state '{}'",
+                character_data.id, state.animation, next_state
+            );
+        }
+
+        state_machine.current_state = Some(next_state);
+    }
+}
@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+
+use crate::dropdown::{HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
+use crate::focus::*;
+
+pub struct TabsPlugin;
+
+impl Plugin for TabsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Tabs>()
+            .register_type::<TabBar>()
+            .register_type::<TabButton>()
+            .add_systems(Update, (handle_tab_buttons, apply_tabs).chain());
+    }
+}
+
+// a bar of tab buttons over pages, only the selected tab's page is shown,
+// set it to switch tabs without going through the bar (that doesn't trigger TabChanged)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Component, Reflect)]
+#[reflect(Component)]
+pub struct Tabs {
+    pub selected: usize,
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct TabBar;
+
+#[derive(Debug, Clone, Copy, Component, Reflect)]
+#[reflect(Component)]
+pub struct TabButton {
+    pub tabs: Entity,
+    pub index: usize,
+}
+
+// targets the Tabs entity, with the selected tab's index
+#[derive(EntityEvent)]
+pub struct TabChanged {
+    pub entity: Entity,
+    pub selected: usize,
+}
+
+const TAB_HEIGHT: f32 = 30.0;
+
+type TabsChangedQuery<'w, 's> =
+    Query<'w, 's, (&'static Tabs, &'static Children), Or<(Changed<Tabs>, Changed<Children>)>>;
+
+// the tabs' other children are the pages, in the same order as the titles,
+// laid out under the bar whatever position they were spawned with
+pub fn spawn_tabs<'a>(
+    commands: &'a mut Commands,
+    position: Vec2,
+    titles: impl IntoIterator<Item = impl Into<String>>,
+    selected: usize,
+) -> EntityCommands<'a> {
+    let mut entity_commands = commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(position.x),
+            top: Val::Px(position.y),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(10.0),
+            ..default()
+        },
+        Tabs { selected },
+    ));
+    let tabs = entity_commands.id();
+
+    entity_commands.with_children(|parent| {
+        parent
+            .spawn((Node::default(), TabBar))
+            .with_children(|parent| {
+                for (index, title) in titles.into_iter().enumerate() {
+                    parent
+                        .spawn((
+                            Button,
+                            Node {
+                                height: Val::Px(TAB_HEIGHT),
+                                border: UiRect::all(Val::Px(2.0)),
+                                padding: UiRect::horizontal(Val::Px(10.0)),
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            BorderColor::all(Color::BLACK),
+                            BackgroundColor(NORMAL_BUTTON),
+                            Focusable,
+                            TabButton { tabs, index },
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn((
+                                Text::new(title.into()),
+                                TextFont {
+                                    font_size: 16.0,
+                                    ..default()
+                                },
+                                TextColor(Color::WHITE),
+                            ));
+                        });
+                }
+            });
+    });
+
+    entity_commands
+}
+
+fn handle_tab_buttons(
+    mut commands: Commands,
+    buttons: Query<(&Interaction, &TabButton), Changed<Interaction>>,
+    mut tabs: Query<&mut Tabs>,
+) {
+    for (interaction, button) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        let Ok(mut tabs) = tabs.get_mut(button.tabs) else {
+            continue;
+        };
+        if tabs.selected == button.index {
+            continue;
+        }
+
+        tabs.selected = button.index;
+        commands.trigger(TabChanged {
+            entity: button.tabs,
+            selected: button.index,
+        });
+    }
+}
+
+// the selected tab's button stays highlighted, like a selected radio button
+fn apply_tabs(
+    changed_tabs: TabsChangedQuery,
+    all_tabs: Query<&Tabs>,
+    tab_bars: Query<(), With<TabBar>>,
+    mut buttons: Query<(&Interaction, &TabButton, &mut BackgroundColor)>,
+    mut nodes: Query<&mut Node>,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        let Ok(tabs) = all_tabs.get(button.tabs) else {
+            continue;
+        };
+
+        let new_color = if tabs.selected == button.index {
+            PRESSED_BUTTON
+        } else if *interaction == Interaction::None {
+            NORMAL_BUTTON
+        } else {
+            HOVERED_BUTTON
+        };
+        color.set_if_neq(new_color.into());
+    }
+
+    for (tabs, children) in &changed_tabs {
+        let pages = children.iter().filter(|child| !tab_bars.contains(*child));
+        for (index, page) in pages.enumerate() {
+            let Ok(mut node) = nodes.get_mut(page) else {
+                continue;
+            };
+            node.position_type = PositionType::Relative;
+            node.left = Val::Auto;
+            node.top = Val::Auto;
+            node.display = if tabs.selected == index {
+                Display::Flex
+            } else {
+                Display::None
+            };
+        }
+    }
+}
@@ -0,0 +1,274 @@
+use std::collections::HashSet;
+
+use bevy::{
+    camera::{RenderTarget, primitives::Aabb, visibility::RenderLayers},
+    image::BevyDefault,
+    prelude::*,
+    render::render_resource::TextureFormat,
+    scene::SceneInstanceReady,
+};
+
+use crate::character::*;
+
+// renders each ready character once to a small image, stored on the Character,
+// one character at a time
+pub struct CharacterThumbnailPlugin;
+
+impl Plugin for CharacterThumbnailPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SkippedThumbnails>()
+            .add_observer(on_thumbnail_scene_ready)
+            .add_systems(
+                Update,
+                (
+                    start_character_thumbnail,
+                    frame_character_thumbnail,
+                    finish_character_thumbnail,
+                )
+                    .chain(),
+            );
+    }
+}
+
+// width and height in pixels
+pub const THUMBNAIL_SIZE: u32 = 128;
+
+// thumbnails are rendered on their own layer so nothing else shows up in them,
+// and they don't show up in anything else
+pub const THUMBNAIL_RENDER_LAYER: usize = 31;
+
+// far away from the scene, in case a mesh is seen before its render layer is set
+const THUMBNAIL_OFFSET: Vec3 = Vec3::new(0.0, -10000.0, 0.0);
+
+// extra room around the model
+const THUMBNAIL_MARGIN: f32 = 1.05;
+
+// frames the camera renders for before the image is used,
+// rendering runs a frame behind
+const THUMBNAIL_RENDER_FRAMES: u32 = 3;
+
+// gives up on models that never get bounds (e.g. no meshes)
+const THUMBNAIL_TIMEOUT_FRAMES: u32 = 300;
+
+// triggered once a character's thumbnail has been rendered
+#[derive(Debug, Clone, Event)]
+pub struct CharacterThumbnailReady {
+    pub id: String,
+    pub image: Handle<Image>,
+}
+
+// characters that timed out, so they aren't tried again
+#[derive(Default, Resource)]
+struct SkippedThumbnails(HashSet<String>);
+
+// on the model's scene root while its thumbnail is rendered
+#[derive(Component)]
+struct ThumbnailCapture {
+    id: String,
+    image: Handle<Image>,
+    camera: Entity,
+    scene_ready: bool,
+    frames: u32,
+    // counts down once the camera is framed
+    render_frames: Option<u32>,
+}
+
+fn start_character_thumbnail(
+    mut commands: Commands,
+    characters: Res<Characters>,
+    skipped: Res<SkippedThumbnails>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    captures: Query<(), With<ThumbnailCapture>>,
+) {
+    if !captures.is_empty() {
+        return;
+    }
+
+    let mut ids = characters
+        .ids()
+        .filter(|id| !skipped.0.contains(*id))
+        .collect::<Vec<_>>();
+    ids.sort();
+    let Some((id, character, model)) = ids.into_iter().find_map(|id| {
+        let character = characters.get(id)?;
+        if character.thumbnail().is_some() || !character.is_ready(&asset_server) {
+            return None;
+        }
+        Some((id, character, character.model()?.clone()))
+    }) else {
+        return;
+    };
+
+    info!("Rendering thumbnail for character '{}' ...", id);
+
+    let image = images.add(Image::new_target_texture(
+        THUMBNAIL_SIZE,
+        THUMBNAIL_SIZE,
+        TextureFormat::bevy_default(),
+        None,
+    ));
+    let render_layers = RenderLayers::layer(THUMBNAIL_RENDER_LAYER);
+
+    // inactive until it's framed
+    let camera = commands
+        .spawn((
+            Camera3d::default(),
+            Camera {
+                is_active: false,
+                order: -1,
+                clear_color: ClearColorConfig::Custom(Color::NONE),
+                ..default()
+            },
+            RenderTarget::Image(image.clone().into()),
+            AmbientLight {
+                brightness: 500.0,
+                ..default()
+            },
+            render_layers.clone(),
+            Transform::from_translation(THUMBNAIL_OFFSET),
+            Name::new("ThumbnailCamera"),
+        ))
+        .with_children(|parent| {
+            // lights the model from the camera
+            parent.spawn((
+                DirectionalLight {
+                    illuminance: light_consts::lux::OVERCAST_DAY,
+                    ..default()
+                },
+                render_layers.clone(),
+            ));
+        })
+        .id();
+
+    // in its bind pose, nothing animates it
+    commands.spawn((
+        SceneRoot(model),
+        Transform::from_translation(THUMBNAIL_OFFSET) * character.model_transform(),
+        render_layers,
+        Name::new("ThumbnailModel"),
+        ThumbnailCapture {
+            id: id.clone(),
+            image,
+            camera,
+            scene_ready: false,
+            frames: 0,
+            render_frames: None,
+        },
+    ));
+}
+
+// render layers aren't inherited, so the scene's entities get the thumbnail layer too
+fn on_thumbnail_scene_ready(
+    scene_ready: On<SceneInstanceReady>,
+    mut commands: Commands,
+    mut captures: Query<&mut ThumbnailCapture>,
+    children: Query<&Children>,
+) {
+    let Ok(mut capture) = captures.get_mut(scene_ready.entity) else {
+        return;
+    };
+
+    for child in children.iter_descendants(scene_ready.entity) {
+        commands
+            .entity(child)
+            .insert(RenderLayers::layer(THUMBNAIL_RENDER_LAYER));
+    }
+    capture.scene_ready = true;
+}
+
+// points the camera at the model from the front once its bounds are known
+// mesh bounds and global transforms aren't available until after the scene has spawned
+fn frame_character_thumbnail(
+    mut captures: Query<(Entity, &mut ThumbnailCapture)>,
+    children: Query<&Children>,
+    bounds: Query<(&Aabb, &GlobalTransform)>,
+    mut cameras: Query<(&mut Camera, &mut Transform, &Projection)>,
+) {
+    for (entity, mut capture) in &mut captures {
+        capture.frames += 1;
+        if !capture.scene_ready || capture.render_frames.is_some() {
+            continue;
+        }
+
+        let mut min = Vec3::splat(f32::MAX);
+        let mut max = Vec3::splat(f32::MIN);
+        for child in children.iter_descendants(entity) {
+            let Ok((aabb, transform)) = bounds.get(child) else {
+                continue;
+            };
+
+            let (center, half_extents) = (Vec3::from(aabb.center), Vec3::from(aabb.half_extents));
+            for x in [-1.0, 1.0] {
+                for y in [-1.0, 1.0] {
+                    for z in [-1.0, 1.0] {
+                        let corner =
+                            transform.transform_point(center + half_extents * Vec3::new(x, y, z));
+                        min = min.min(corner);
+                        max = max.max(corner);
+                    }
+                }
+            }
+        }
+
+        // bounds are calculated once the meshes load
+        if min.cmpgt(max).any() {
+            continue;
+        }
+        let Ok((mut camera, mut transform, projection)) = cameras.get_mut(capture.camera) else {
+            continue;
+        };
+
+        let center = (min + max) * 0.5;
+        let radius = (max - min).length() * 0.5 * THUMBNAIL_MARGIN;
+        let fov = match projection {
+            Projection::Perspective(perspective) => perspective.fov,
+            _ => std::f32::consts::FRAC_PI_4,
+        };
+
+        // Mixamo characters face +Z
+        *transform = Transform::from_translation(center + Vec3::Z * radius / (fov * 0.5).sin())
+            .looking_at(center, Vec3::Y);
+        camera.is_active = true;
+        capture.render_frames = Some(THUMBNAIL_RENDER_FRAMES);
+    }
+}
+
+fn finish_character_thumbnail(
+    mut commands: Commands,
+    mut characters: ResMut<Characters>,
+    mut skipped: ResMut<SkippedThumbnails>,
+    mut captures: Query<(Entity, &mut ThumbnailCapture)>,
+) {
+    for (entity, mut capture) in &mut captures {
+        match capture.render_frames {
+            Some(0) => {}
+            Some(frames) => {
+                capture.render_frames = Some(frames - 1);
+                continue;
+            }
+            None if capture.frames > THUMBNAIL_TIMEOUT_FRAMES => {
+                warn!(
+                    "Timed out rendering thumbnail for character '{}'",
+                    capture.id
+                );
+                skipped.0.insert(capture.id.clone());
+            }
+            None => continue,
+        }
+
+        commands.entity(capture.camera).despawn();
+        commands.entity(entity).despawn();
+
+        // the character may have been unloaded meanwhile
+        if capture.render_frames.is_some()
+            && let Some(character) = characters.get_mut(&capture.id)
+        {
+            character.set_thumbnail(capture.image.clone());
+            commands.trigger(CharacterThumbnailReady {
+                id: capture.id.clone(),
+                image: capture.image.clone(),
+            });
+        }
+    }
+}
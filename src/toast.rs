@@ -0,0 +1,129 @@
+use bevy::{prelude::*, ui::FocusPolicy};
+
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_toasts)
+            .add_systems(Update, update_toasts)
+            .add_observer(show_toast);
+    }
+}
+
+// a short message stacked in the bottom left corner that goes away by itself,
+// commands.trigger(Toast::info("Loaded mutant"))
+#[derive(Debug, Clone, Event)]
+pub struct Toast {
+    pub level: ToastLevel,
+    pub message: String,
+}
+
+impl Toast {
+    pub fn info(message: impl Into<String>) -> Self {
+        Self {
+            level: ToastLevel::Info,
+            message: message.into(),
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            level: ToastLevel::Error,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastLevel {
+    Info,
+    Error,
+}
+
+impl ToastLevel {
+    // errors stay up long enough to be read
+    fn seconds(&self) -> f32 {
+        match self {
+            Self::Info => 4.0,
+            Self::Error => 10.0,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Self::Info => Color::srgba(0.1, 0.1, 0.1, 0.9),
+            Self::Error => Color::srgba(0.5, 0.05, 0.05, 0.9),
+        }
+    }
+}
+
+// the oldest are dismissed early past this many
+const MAX_TOASTS: usize = 5;
+
+#[derive(Component)]
+struct Toasts;
+
+#[derive(Component)]
+struct ToastTimer(Timer);
+
+fn setup_toasts(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
+            max_width: Val::Percent(24.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(5.0),
+            ..default()
+        },
+        // toasts don't get in the way of clicks
+        FocusPolicy::Pass,
+        Name::new("Toasts"),
+        Toasts,
+    ));
+}
+
+// the newest toast is at the bottom
+fn show_toast(
+    toast: On<Toast>,
+    mut commands: Commands,
+    toasts: Single<(Entity, Option<&Children>), With<Toasts>>,
+) {
+    let (toasts, children) = *toasts;
+    if let Some(children) = children {
+        let dismissed = (children.len() + 1).saturating_sub(MAX_TOASTS);
+        // one may be timing out this frame too
+        for child in children.iter().take(dismissed) {
+            commands.entity(child).try_despawn();
+        }
+    }
+
+    commands.entity(toasts).with_child((
+        Node {
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        BackgroundColor(toast.level.color()),
+        FocusPolicy::Pass,
+        Text::new(toast.message.clone()),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        ToastTimer(Timer::from_seconds(toast.level.seconds(), TimerMode::Once)),
+    ));
+}
+
+fn update_toasts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut toasts: Query<(Entity, &mut ToastTimer)>,
+) {
+    for (entity, mut timer) in &mut toasts {
+        if timer.0.tick(time.delta()).is_finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use bevy::{prelude::*, ui::FocusPolicy, window::PrimaryWindow};
+
+pub struct TooltipPlugin;
+
+impl Plugin for TooltipPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Tooltip>()
+            .init_resource::<TooltipHover>()
+            .add_systems(Startup, setup_tooltip)
+            .add_systems(Update, update_tooltip);
+    }
+}
+
+// shown next to the cursor after hovering the entity, or any of its descendants
+// (e.g. a slider's track), for a moment
+#[derive(Debug, Clone, Component, Reflect)]
+#[reflect(Component)]
+pub struct Tooltip(pub String);
+
+impl Tooltip {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self(text.into())
+    }
+}
+
+// how long to hover before the tooltip shows, in seconds
+const TOOLTIP_DELAY: f32 = 0.5;
+
+// from the cursor, so the tooltip doesn't cover what it's describing
+const TOOLTIP_OFFSET: Vec2 = Vec2::new(16.0, 16.0);
+
+// the entity with the Tooltip that's hovered and for how long
+#[derive(Default, Resource)]
+struct TooltipHover {
+    entity: Option<Entity>,
+    time: f32,
+}
+
+// the one floating panel all tooltips are shown in
+#[derive(Component)]
+struct TooltipPanel;
+
+fn setup_tooltip(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            display: Display::None,
+            padding: UiRect::axes(Val::Px(8.0), Val::Px(4.0)),
+            border: UiRect::all(Val::Px(1.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.05, 0.05, 0.05, 0.9)),
+        BorderColor::all(Color::srgb(0.5, 0.5, 0.5)),
+        // over everything else
+        GlobalZIndex(i32::MAX),
+        FocusPolicy::Pass,
+        Text::new(""),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Name::new("Tooltip"),
+        TooltipPanel,
+    ));
+}
+
+fn is_hovered(
+    entity: Entity,
+    interactions: &Query<&Interaction>,
+    children: &Query<&Children>,
+) -> bool {
+    std::iter::once(entity)
+        .chain(children.iter_descendants(entity))
+        .filter_map(|entity| interactions.get(entity).ok())
+        .any(|interaction| *interaction != Interaction::None)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_tooltip(
+    time: Res<Time>,
+    ui_scale: Res<UiScale>,
+    mut hover: ResMut<TooltipHover>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    tooltips: Query<(Entity, &Tooltip)>,
+    interactions: Query<&Interaction>,
+    children: Query<&Children>,
+    panel: Single<(&mut Node, &mut Text), With<TooltipPanel>>,
+) {
+    // keep the one that's already counting down
+    let hovered = hover
+        .entity
+        .filter(|entity| {
+            tooltips.contains(*entity) && is_hovered(*entity, &interactions, &children)
+        })
+        .or_else(|| {
+            tooltips
+                .iter()
+                .map(|(entity, _)| entity)
+                .find(|entity| is_hovered(*entity, &interactions, &children))
+        });
+
+    if hover.entity != hovered {
+        hover.entity = hovered;
+        hover.time = 0.0;
+    } else {
+        hover.time += time.delta_secs();
+    }
+
+    // only touched when something changes, so the UI isn't laid out again every frame
+    let (mut node, mut text) = panel.into_inner();
+    let shown = hovered
+        .filter(|_| hover.time >= TOOLTIP_DELAY)
+        .and_then(|entity| tooltips.get(entity).ok())
+        .zip(window.cursor_position());
+    let Some(((_, tooltip), cursor_position)) = shown else {
+        if node.display != Display::None {
+            node.display = Display::None;
+        }
+        return;
+    };
+
+    let position = cursor_position / ui_scale.0 + TOOLTIP_OFFSET;
+    if node.display != Display::Flex
+        || node.left != Val::Px(position.x)
+        || node.top != Val::Px(position.y)
+    {
+        node.display = Display::Flex;
+        node.left = Val::Px(position.x);
+        node.top = Val::Px(position.y);
+    }
+    if text.0 != tooltip.0 {
+        text.0.clone_from(&tooltip.0);
+    }
+}
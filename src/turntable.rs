@@ -0,0 +1,202 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use bevy_mixamo::*;
+
+use crate::screenshot::*;
+use crate::selection::*;
+
+pub struct TurntablePlugin;
+
+impl Plugin for TurntablePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                handle_turntable_keys,
+                rotate_model,
+                capture_turntable_frames.run_if(resource_exists::<Turntable>),
+            )
+                .chain(),
+        )
+        .add_observer(on_start_turntable);
+    }
+}
+
+// radians per second
+const ROTATOR_SPEED: f32 = 0.5;
+
+#[derive(Component)]
+pub struct Rotator;
+
+fn rotate_model(time: Res<Time>, mut query: Query<&mut Transform, With<Rotator>>) {
+    for mut transform in &mut query {
+        transform.rotate_y(time.delta_secs() * ROTATOR_SPEED);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurntableLength {
+    // one full turn of the model
+    Rotation,
+    // one loop of the model's current animation, turning as it plays
+    AnimationLoop,
+}
+
+// turns the selected character and saves every frame to screenshots/turntable-<unix time>/
+// time is stepped by exactly one frame each update so that the frames are evenly spaced
+// however long they take to capture
+#[derive(Debug, Clone, Event)]
+pub struct StartTurntable {
+    pub length: TurntableLength,
+    pub fps: u32,
+}
+
+impl StartTurntable {
+    pub fn new(length: TurntableLength) -> Self {
+        Self { length, fps: 30 }
+    }
+}
+
+#[derive(Resource)]
+struct Turntable {
+    model: Entity,
+    directory: String,
+    frame: u32,
+    frames: u32,
+
+    // the UI is hidden for the whole turntable rather than per frame
+    hidden_ui: Vec<(Entity, Visibility)>,
+}
+
+// T renders a full turn, shift + T one loop of the current animation
+fn handle_turntable_keys(
+    mut commands: Commands,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    turntable: Option<Res<Turntable>>,
+) {
+    if !keyboard.just_pressed(KeyCode::KeyT) || turntable.is_some() {
+        return;
+    }
+
+    let length = if keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        TurntableLength::AnimationLoop
+    } else {
+        TurntableLength::Rotation
+    };
+    commands.trigger(StartTurntable::new(length));
+}
+
+// the duration of the main animation playing on the model, at its playback speed
+fn current_animation_duration(
+    animator: &CharacterAnimator,
+    animation_players: &Query<(
+        &AnimationPlayer,
+        &AnimationTransitions,
+        &AnimationGraphHandle,
+    )>,
+    animation_graphs: &Assets<AnimationGraph>,
+    animation_clips: &Assets<AnimationClip>,
+) -> Option<f32> {
+    let (player, transitions, graph) = animation_players.get(animator.player).ok()?;
+    let animation_index = transitions.get_main_animation()?;
+    let AnimationNodeType::Clip(clip) =
+        &animation_graphs.get(graph)?.get(animation_index)?.node_type
+    else {
+        return None;
+    };
+    let speed = player.animation(animation_index)?.speed().abs();
+    if speed <= 0.0 {
+        return None;
+    }
+    Some(animation_clips.get(clip)?.duration() / speed)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn on_start_turntable(
+    event: On<StartTurntable>,
+    mut commands: Commands,
+    selected_character: Res<SelectedCharacter>,
+    animators: Query<&CharacterAnimator>,
+    animation_players: Query<(
+        &AnimationPlayer,
+        &AnimationTransitions,
+        &AnimationGraphHandle,
+    )>,
+    animation_graphs: Res<Assets<AnimationGraph>>,
+    animation_clips: Res<Assets<AnimationClip>>,
+    mut ui_roots: UiRootQuery,
+) {
+    let Some(model) = selected_character.0 else {
+        warn!("Select a character to render a turntable of");
+        return;
+    };
+
+    let duration = match event.length {
+        TurntableLength::Rotation => std::f32::consts::TAU / ROTATOR_SPEED,
+        TurntableLength::AnimationLoop => {
+            let Some(duration) = animators.get(model).ok().and_then(|animator| {
+                current_animation_duration(
+                    animator,
+                    &animation_players,
+                    &animation_graphs,
+                    &animation_clips,
+                )
+            }) else {
+                warn!("The selected character isn't playing an animation");
+                return;
+            };
+            duration
+        }
+    };
+    let fps = event.fps.max(1);
+    let frames = (duration * fps as f32).round().max(1.0) as u32;
+
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let directory = format!("{}/turntable-{}", SCREENSHOT_DIRECTORY, time.as_secs());
+    info!(
+        "Rendering a {} frame turntable to '{}' ...",
+        frames, directory
+    );
+
+    commands.entity(model).insert(Rotator);
+    commands.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+        1.0 / fps as f32,
+    )));
+    commands.insert_resource(Turntable {
+        model,
+        directory,
+        frame: 0,
+        frames,
+        hidden_ui: hide_ui(&mut ui_roots),
+    });
+}
+
+fn capture_turntable_frames(
+    mut commands: Commands,
+    mut turntable: ResMut<Turntable>,
+    mut visibilities: Query<&mut Visibility>,
+) {
+    if turntable.frame >= turntable.frames {
+        info!(
+            "Rendered {} turntable frames to '{}'",
+            turntable.frames, turntable.directory
+        );
+
+        if let Ok(mut model) = commands.get_entity(turntable.model) {
+            model.remove::<Rotator>();
+        }
+        commands.insert_resource(TimeUpdateStrategy::Automatic);
+        show_ui(&turntable.hidden_ui, &mut visibilities);
+        commands.remove_resource::<Turntable>();
+        return;
+    }
+
+    commands.trigger(CaptureScreenshot::new().with_path(format!(
+        "{}/frame_{:04}.png",
+        turntable.directory, turntable.frame
+    )));
+    turntable.frame += 1;
+}
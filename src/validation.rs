@@ -0,0 +1,371 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{
+    asset::{AssetPath, UntypedAssetId},
+    gltf::Gltf,
+    prelude::*,
+};
+
+use crate::animation_library::*;
+use crate::asset_event_bridge::*;
+use crate::character::*;
+
+// a problem with a character's data that would otherwise show up later as a missing animation
+// or a failed load, one event per problem
+#[derive(Debug, Clone, Event)]
+pub struct CharacterValidationError {
+    pub id: String,
+    pub path: String,
+    pub error: String,
+}
+
+impl CharacterData {
+    // checks that don't need any other assets
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.id.trim().is_empty() {
+            errors.push("id is empty".to_string());
+        }
+
+        if self.model_path.trim().is_empty() {
+            errors.push("model_path is empty".to_string());
+        } else if !asset_exists(&self.model_path) {
+            errors.push(format!("model '{}' doesn't exist", self.model_path));
+        }
+
+        for animation_name in self.animation_paths.duplicates() {
+            errors.push(format!(
+                "animation '{animation_name}' is defined more than once"
+            ));
+        }
+
+        let mut animation_names = self.animation_paths.keys().collect::<Vec<_>>();
+        animation_names.sort();
+        for animation_name in animation_names {
+            let animation_data = &self.animation_paths[animation_name];
+            if !asset_exists(&animation_data.path) {
+                errors.push(format!(
+                    "animation '{}' file '{}' doesn't exist",
+                    animation_name, animation_data.path
+                ));
+            }
+
+            // blend spaces play like animations
+            if self.blend_spaces.contains_key(animation_name) {
+                errors.push(format!(
+                    "'{animation_name}' is both an animation and a blend space"
+                ));
+            }
+        }
+
+        if let Some(bone_map_path) = &self.bone_map_path
+            && !asset_exists(bone_map_path)
+        {
+            errors.push(format!("bone map '{bone_map_path}' doesn't exist"));
+        }
+
+        if let Some(source_skeleton_path) = &self.source_skeleton_path
+            && !asset_exists(source_skeleton_path)
+        {
+            errors.push(format!(
+                "source skeleton '{source_skeleton_path}' doesn't exist"
+            ));
+        }
+
+        errors
+    }
+
+    // checks that need the character's glTFs and animation sets, which have to be loaded
+    pub fn validate_loaded(
+        &self,
+        gltfs: &Assets<Gltf>,
+        animation_library: &AnimationLibrary,
+        animation_sets: &Assets<AnimationSet>,
+        asset_server: &AssetServer,
+    ) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        // glTFs that failed to load are reported when the character's assets fail
+        let gltf = |path: &str| {
+            asset_server
+                .get_handle::<Gltf>(gltf_path(path))
+                .and_then(|gltf| gltfs.get(&gltf))
+        };
+
+        // every scene path is loaded as #Scene0
+        let mut scene_paths = vec![("model", &self.model_path)];
+        if let Some(source_skeleton_path) = &self.source_skeleton_path {
+            scene_paths.push(("source skeleton", source_skeleton_path));
+        }
+        for (what, path) in scene_paths {
+            if let Some(gltf) = gltf(path)
+                && gltf.scenes.is_empty()
+            {
+                errors.push(format!("{what} '{path}' has no scenes"));
+            }
+        }
+
+        let mut animation_names = self.animation_paths.keys().collect::<Vec<_>>();
+        animation_names.sort();
+        for animation_name in animation_names {
+            let animation_data = &self.animation_paths[animation_name];
+            if let Some(gltf) = gltf(&animation_data.path)
+                && animation_data.clip_index >= gltf.animations.len()
+            {
+                errors.push(format!(
+                    "animation '{}' clip_index {} is out of range, '{}' has {} animations",
+                    animation_name,
+                    animation_data.clip_index,
+                    animation_data.path,
+                    gltf.animations.len()
+                ));
+            }
+        }
+
+        // the character's own animations and later sets silently override earlier ones
+        let mut set_animations = HashMap::<&String, &String>::new();
+        for set_id in &self.animation_sets {
+            let Some(set) = animation_library
+                .set(set_id)
+                .and_then(|set| animation_sets.get(set))
+            else {
+                continue;
+            };
+
+            let mut set_animation_names = set.animations.keys().collect::<Vec<_>>();
+            set_animation_names.sort();
+            for animation_name in set_animation_names {
+                if let Some(other_set_id) = set_animations.insert(animation_name, set_id) {
+                    errors.push(format!(
+                        "animation '{animation_name}' is in both animation sets '{other_set_id}' and '{set_id}'"
+                    ));
+                }
+            }
+        }
+
+        let mut set_animation_names = set_animations.into_iter().collect::<Vec<_>>();
+        set_animation_names.sort();
+        for (animation_name, set_id) in set_animation_names {
+            if self.animation_paths.contains_key(animation_name) {
+                errors.push(format!(
+                    "animation '{animation_name}' is also in animation set '{set_id}'"
+                ));
+            }
+            if self.blend_spaces.contains_key(animation_name) {
+                errors.push(format!(
+                    "'{animation_name}' is both an animation in animation set '{set_id}' and a blend space"
+                ));
+            }
+        }
+
+        errors
+    }
+
+    // the glTFs that validate_loaded looks into
+    fn gltf_paths(&self) -> Vec<String> {
+        let mut paths = vec![gltf_path(&self.model_path)];
+        paths.extend(self.source_skeleton_path.iter().map(|path| gltf_path(path)));
+        paths.extend(
+            self.animation_paths
+                .values()
+                .map(|animation_data| gltf_path(&animation_data.path)),
+        );
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+}
+
+// the whole glTF, without any label
+fn gltf_path(path: &str) -> String {
+    AssetPath::parse(path).without_label().to_string()
+}
+
+// files can only be checked up front when assets come from the local filesystem
+#[cfg(not(any(target_arch = "wasm32", target_os = "android")))]
+fn asset_exists(path: &str) -> bool {
+    // AssetPlugin's default file_path
+    bevy::asset::io::file::FileAssetReader::get_base_path()
+        .join("assets")
+        .join(AssetPath::parse(path).path())
+        .exists()
+}
+
+#[cfg(any(target_arch = "wasm32", target_os = "android"))]
+fn asset_exists(_path: &str) -> bool {
+    true
+}
+
+// characters waiting on their glTFs and animation sets to finish loading
+// before validate_loaded can run, the handles keep the glTFs loaded until then
+#[derive(Default, Resource)]
+pub(crate) struct PendingCharacterValidations(Vec<PendingCharacterValidation>);
+
+// character data that has been validated at least once
+// a hot reload is both modified and loaded again, it's only validated as modified
+#[derive(Default, Resource)]
+pub(crate) struct ValidatedCharacterData(HashSet<AssetId<CharacterData>>);
+
+struct PendingCharacterValidation {
+    character_data: AssetId<CharacterData>,
+    gltfs: Vec<Handle<Gltf>>,
+}
+
+fn character_data_path(asset_id: AssetId<CharacterData>, asset_server: &AssetServer) -> String {
+    asset_server
+        .get_path(asset_id)
+        .map(|path| path.to_string())
+        .unwrap_or_default()
+}
+
+fn validate_character_data(
+    commands: &mut Commands,
+    asset_id: AssetId<CharacterData>,
+    character_datum: &Assets<CharacterData>,
+    pending_validations: &mut PendingCharacterValidations,
+    asset_server: &AssetServer,
+) {
+    let Some(character_data) = character_datum.get(asset_id) else {
+        return;
+    };
+
+    let mut errors = character_data.validate();
+
+    // ids have to be unique across every loaded character
+    if character_datum
+        .iter()
+        .any(|(other_id, other)| other_id != asset_id && other.id == character_data.id)
+    {
+        errors.push(format!("duplicate character id '{}'", character_data.id));
+    }
+
+    report_validation_errors(
+        commands,
+        character_data,
+        &character_data_path(asset_id, asset_server),
+        errors,
+    );
+
+    // a modified character starts over
+    pending_validations
+        .0
+        .retain(|pending| pending.character_data != asset_id);
+    pending_validations.0.push(PendingCharacterValidation {
+        character_data: asset_id,
+        gltfs: character_data
+            .gltf_paths()
+            .into_iter()
+            .filter(|path| asset_exists(path))
+            .map(|path| asset_server.load::<Gltf>(path))
+            .collect(),
+    });
+}
+
+fn report_validation_errors(
+    commands: &mut Commands,
+    character_data: &CharacterData,
+    path: &str,
+    errors: Vec<String>,
+) {
+    for error in errors {
+        error!(
+            "Invalid character data for '{}' in '{}': {}",
+            character_data.id, path, error
+        );
+
+        commands.trigger(CharacterValidationError {
+            id: character_data.id.clone(),
+            path: path.to_string(),
+            error,
+        });
+    }
+}
+
+// failed loads count as finished so that a bad path doesn't block the other checks
+pub(crate) fn validate_loaded_characters(
+    mut commands: Commands,
+    mut pending_validations: ResMut<PendingCharacterValidations>,
+    character_datum: Res<Assets<CharacterData>>,
+    animation_library: Res<AnimationLibrary>,
+    animation_sets: Res<Assets<AnimationSet>>,
+    gltfs: Res<Assets<Gltf>>,
+    asset_server: Res<AssetServer>,
+) {
+    let is_finished = |asset_id: UntypedAssetId| {
+        let load_state = asset_server.load_state(asset_id);
+        load_state.is_loaded() || load_state.is_failed()
+    };
+
+    pending_validations.0.retain(|pending| {
+        let Some(character_data) = character_datum.get(pending.character_data) else {
+            return false;
+        };
+
+        let gltfs_finished = pending
+            .gltfs
+            .iter()
+            .all(|gltf| is_finished(gltf.id().untyped()));
+        let sets_finished = character_data
+            .animation_sets
+            .iter()
+            .filter_map(|set_id| animation_library.set(set_id))
+            .all(|set| is_finished(set.id().untyped()));
+        if !gltfs_finished || !sets_finished {
+            return true;
+        }
+
+        let errors = character_data.validate_loaded(
+            &gltfs,
+            &animation_library,
+            &animation_sets,
+            &asset_server,
+        );
+        report_validation_errors(
+            &mut commands,
+            character_data,
+            &character_data_path(pending.character_data, &asset_server),
+            errors,
+        );
+        false
+    });
+}
+
+pub(crate) fn on_character_data_loaded_validate(
+    event: On<AssetLoadedEvent<CharacterData>>,
+    mut commands: Commands,
+    character_datum: Res<Assets<CharacterData>>,
+    mut validated: ResMut<ValidatedCharacterData>,
+    mut pending_validations: ResMut<PendingCharacterValidations>,
+    asset_server: Res<AssetServer>,
+) {
+    if !validated.0.insert(event.asset_id) {
+        return;
+    }
+
+    validate_character_data(
+        &mut commands,
+        event.asset_id,
+        &character_datum,
+        &mut pending_validations,
+        &asset_server,
+    );
+}
+
+pub(crate) fn on_character_data_modified_validate(
+    event: On<AssetModifiedEvent<CharacterData>>,
+    mut commands: Commands,
+    character_datum: Res<Assets<CharacterData>>,
+    mut validated: ResMut<ValidatedCharacterData>,
+    mut pending_validations: ResMut<PendingCharacterValidations>,
+    asset_server: Res<AssetServer>,
+) {
+    validated.0.insert(event.asset_id);
+    validate_character_data(
+        &mut commands,
+        event.asset_id,
+        &character_datum,
+        &mut pending_validations,
+        &asset_server,
+    );
+}
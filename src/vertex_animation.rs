@@ -0,0 +1,552 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::RenderAssetUsages,
+    light::NotShadowCaster,
+    mesh::{
+        MeshTag, MeshVertexAttribute, MeshVertexBufferLayoutRef, VertexFormat,
+        skinning::{SkinnedMesh, SkinnedMeshInverseBindposes},
+    },
+    pbr::{ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline},
+    prelude::*,
+    render::render_resource::{
+        AsBindGroup, Extent3d, RenderPipelineDescriptor, ShaderType, SpecializedMeshPipelineError,
+        TextureDimension, TextureFormat,
+    },
+    scene::SceneInstanceReady,
+    shader::ShaderRef,
+};
+
+use crate::animation_lod::*;
+use crate::character::*;
+use crate::retarget::*;
+
+// a clip baked into a texture of skinning matrices so that big crowds can play it on the GPU
+// without AnimationPlayers, at the cost of only ever playing the one looping clip
+#[derive(Debug, Clone, Asset, TypePath)]
+pub struct VertexAnimation {
+    pub texture: Handle<Image>,
+    pub frames: u32,
+    pub frame_rate: f32,
+
+    // the character's model transform, instances are spawned with it like the model would be
+    pub model_transform: Transform,
+
+    // one per skinned mesh in the model
+    pub meshes: Vec<(Handle<Mesh>, Handle<VertexAnimationMaterial>)>,
+}
+
+impl VertexAnimation {
+    pub fn duration(&self) -> f32 {
+        self.frames as f32 / self.frame_rate
+    }
+}
+
+pub type VertexAnimationMaterial = ExtendedMaterial<StandardMaterial, VertexAnimationExtension>;
+
+#[derive(Debug, Clone, Asset, TypePath, AsBindGroup)]
+pub struct VertexAnimationExtension {
+    #[uniform(100)]
+    pub settings: VertexAnimationSettings,
+
+    #[texture(101, sample_type = "float", filterable = false)]
+    pub texture: Handle<Image>,
+}
+
+#[derive(Debug, Clone, Copy, Default, ShaderType)]
+pub struct VertexAnimationSettings {
+    // where the mesh's joints start in each row of the texture
+    pub joint_offset: u32,
+    pub frames: u32,
+    pub frame_rate: f32,
+}
+
+const SHADER_ASSET_PATH: &str = "embedded://bevy_mixamo/vertex_animation.wgsl";
+
+// the skinned mesh's joint attributes are moved to these,
+// otherwise the mesh pipeline would expect a skin to go with them
+pub const ATTRIBUTE_VERTEX_ANIMATION_JOINT_INDEX: MeshVertexAttribute = MeshVertexAttribute::new(
+    "VertexAnimationJointIndex",
+    0x6d69_7861_6d6f_0001,
+    VertexFormat::Uint16x4,
+);
+pub const ATTRIBUTE_VERTEX_ANIMATION_JOINT_WEIGHT: MeshVertexAttribute = MeshVertexAttribute::new(
+    "VertexAnimationJointWeight",
+    0x6d69_7861_6d6f_0002,
+    VertexFormat::Float32x4,
+);
+
+impl MaterialExtension for VertexAnimationExtension {
+    fn vertex_shader() -> ShaderRef {
+        SHADER_ASSET_PATH.into()
+    }
+
+    // the prepass shaders don't know about the baked skinning
+    fn enable_prepass() -> bool {
+        false
+    }
+
+    fn enable_shadows() -> bool {
+        false
+    }
+
+    fn specialize(
+        _pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let mut attributes = vec![
+            Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+            Mesh::ATTRIBUTE_NORMAL.at_shader_location(1),
+        ];
+        if layout.0.contains(Mesh::ATTRIBUTE_UV_0) {
+            attributes.push(Mesh::ATTRIBUTE_UV_0.at_shader_location(2));
+        }
+        if layout.0.contains(Mesh::ATTRIBUTE_TANGENT) {
+            attributes.push(Mesh::ATTRIBUTE_TANGENT.at_shader_location(4));
+        }
+        attributes.push(ATTRIBUTE_VERTEX_ANIMATION_JOINT_INDEX.at_shader_location(8));
+        attributes.push(ATTRIBUTE_VERTEX_ANIMATION_JOINT_WEIGHT.at_shader_location(9));
+
+        descriptor.vertex.buffers = vec![layout.0.get_layout(&attributes)?];
+        Ok(())
+    }
+}
+
+// bakes a character's animation, the result is stored in VertexAnimations
+#[derive(Debug, Clone, Event)]
+pub struct BakeVertexAnimation {
+    pub character: String,
+    pub animation: String,
+
+    // samples per second, frames in between are blended on the GPU
+    pub frame_rate: f32,
+}
+
+impl BakeVertexAnimation {
+    pub fn new(character: impl Into<String>, animation: impl Into<String>) -> Self {
+        Self {
+            character: character.into(),
+            animation: animation.into(),
+            frame_rate: 30.0,
+        }
+    }
+
+    pub fn with_frame_rate(mut self, frame_rate: f32) -> Self {
+        self.frame_rate = frame_rate;
+        self
+    }
+}
+
+// triggered once a bake has finished
+#[derive(Debug, Clone, Event)]
+pub struct VertexAnimationBaked {
+    pub character: String,
+    pub animation: String,
+    pub vertex_animation: Handle<VertexAnimation>,
+}
+
+// baked animations by character then animation
+#[derive(Debug, Default, Resource)]
+pub struct VertexAnimations(HashMap<String, HashMap<String, Handle<VertexAnimation>>>);
+
+impl VertexAnimations {
+    pub fn get(
+        &self,
+        character: impl AsRef<str>,
+        animation: impl AsRef<str>,
+    ) -> Option<&Handle<VertexAnimation>> {
+        self.0.get(character.as_ref())?.get(animation.as_ref())
+    }
+
+    pub(crate) fn release(&mut self, character: impl AsRef<str>) {
+        self.0.remove(character.as_ref());
+    }
+}
+
+// plays a baked animation, the meshes are spawned as children
+#[derive(Debug, Clone, Component)]
+#[require(Transform, Visibility)]
+pub struct VertexAnimationInstance {
+    pub vertex_animation: Handle<VertexAnimation>,
+
+    // seconds into the animation to start at
+    pub start_time: f32,
+}
+
+// a hidden copy of the character that's stepped through the clip one frame per update
+#[derive(Component)]
+struct VertexAnimationBaker {
+    character: String,
+    animation: String,
+    animation_index: AnimationNodeIndex,
+    frame_rate: f32,
+    frames: u32,
+    model_transform: Transform,
+
+    // set once the scene has spawned
+    player: Option<Entity>,
+    skinned_meshes: Vec<Entity>,
+
+    // the frame that was sampled this update
+    frame: Option<u32>,
+    data: Vec<f32>,
+}
+
+pub(crate) fn setup_vertex_animations(app: &mut App) {
+    bevy::asset::embedded_asset!(app, "vertex_animation.wgsl");
+
+    app.add_plugins(MaterialPlugin::<VertexAnimationMaterial>::default())
+        .init_asset::<VertexAnimation>()
+        .init_resource::<VertexAnimations>()
+        .add_systems(
+            PostUpdate,
+            bake_vertex_animations.after(TransformSystems::Propagate),
+        )
+        .add_observer(on_bake_vertex_animation)
+        .add_observer(spawn_vertex_animation_meshes);
+}
+
+fn on_bake_vertex_animation(
+    event: On<BakeVertexAnimation>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    characters: Res<Characters>,
+    animation_graphs: Res<Assets<AnimationGraph>>,
+    animation_clips: Res<Assets<AnimationClip>>,
+) {
+    let Some(character) = characters.get(&event.character) else {
+        warn!("Cannot bake unknown character '{}'", event.character);
+        return;
+    };
+    if !character.is_ready(&asset_server) {
+        warn!(
+            "Cannot bake character '{}' before it has loaded",
+            event.character
+        );
+        return;
+    }
+    let Some(model) = character.model() else {
+        return;
+    };
+    let Some(animation_index) = character.animation_index(&event.animation) else {
+        warn!(
+            "Character '{}' has no animation '{}' to bake",
+            event.character, event.animation
+        );
+        return;
+    };
+    let Some(duration) = animation_graphs
+        .get(character.graph())
+        .and_then(|graph| graph.get(animation_index))
+        .and_then(|node| match &node.node_type {
+            AnimationNodeType::Clip(clip) => animation_clips.get(clip),
+            _ => None,
+        })
+        .map(AnimationClip::duration)
+    else {
+        warn!(
+            "Animation '{}' for character '{}' isn't a clip",
+            event.animation, event.character
+        );
+        return;
+    };
+
+    info!(
+        "Baking animation '{}' for character '{}' ...",
+        event.animation, event.character
+    );
+
+    let frame_rate = event.frame_rate.max(1.0);
+    let frames = ((duration * frame_rate).round() as u32).max(1);
+
+    let mut baker = commands.spawn((
+        SceneRoot(model.clone()),
+        Visibility::Hidden,
+        Name::new(format!("VertexAnimationBaker({})", event.character)),
+        VertexAnimationBaker {
+            character: event.character.clone(),
+            animation: event.animation.clone(),
+            animation_index,
+            // sample the whole clip so the last frame blends back into the first
+            frame_rate: frames as f32 / duration.max(f32::EPSILON),
+            frames,
+            model_transform: character.model_transform(),
+            player: None,
+            skinned_meshes: Vec::new(),
+            frame: None,
+            data: Vec::new(),
+        },
+    ));
+    baker.observe(start_vertex_animation_baker);
+
+    if character.retarget().normalize_names {
+        baker.observe(normalize_model_bone_names);
+    }
+}
+
+fn start_vertex_animation_baker(
+    scene_ready: On<SceneInstanceReady>,
+    mut commands: Commands,
+    characters: Res<Characters>,
+    mut bakers: Query<&mut VertexAnimationBaker>,
+    children: Query<&Children>,
+    mut animation_players: Query<&mut AnimationPlayer>,
+    skinned_meshes: Query<(), With<SkinnedMesh>>,
+) {
+    let Ok(mut baker) = bakers.get_mut(scene_ready.entity) else {
+        return;
+    };
+    let Some(character) = characters.get(&baker.character) else {
+        return;
+    };
+
+    for child in children.iter_descendants(scene_ready.entity) {
+        if skinned_meshes.contains(child) {
+            baker.skinned_meshes.push(child);
+        }
+
+        if baker.player.is_none()
+            && let Ok(mut player) = animation_players.get_mut(child)
+        {
+            // the baker seeks the clip itself
+            player.play(baker.animation_index).pause();
+
+            // and needs every frame to be sampled
+            commands
+                .entity(child)
+                .insert(AnimationGraphHandle(character.graph().clone()))
+                .remove::<AnimationLod>();
+            baker.player = Some(child);
+        }
+    }
+
+    if baker.player.is_none() || baker.skinned_meshes.is_empty() {
+        warn!(
+            "Character '{}' has no skinned meshes to bake",
+            baker.character
+        );
+        commands.entity(scene_ready.entity).despawn();
+    }
+}
+
+// each frame's pose is read after it's been sampled and propagated,
+// then the clip is seeked to the next frame for the next update
+#[allow(clippy::too_many_arguments)]
+fn bake_vertex_animations(
+    mut commands: Commands,
+    mut bakers: Query<(Entity, &mut VertexAnimationBaker, &GlobalTransform)>,
+    mut animation_players: Query<&mut AnimationPlayer>,
+    skinned_meshes: Query<(
+        &SkinnedMesh,
+        &Mesh3d,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+    )>,
+    transforms: Query<&GlobalTransform>,
+    inverse_bindposes: Res<Assets<SkinnedMeshInverseBindposes>>,
+    mut vertex_animations: ResMut<VertexAnimations>,
+    mut assets: VertexAnimationAssets,
+) {
+    for (entity, mut baker, root_transform) in &mut bakers {
+        let Some(player) = baker.player else {
+            continue;
+        };
+
+        // the skinning is relative to the model so that instances can be placed anywhere
+        if let Some(frame) = baker.frame {
+            let model_from_world = root_transform.affine().inverse();
+            let mut row = Vec::new();
+            for &skinned_mesh_entity in &baker.skinned_meshes {
+                let Ok((skinned_mesh, _, _)) = skinned_meshes.get(skinned_mesh_entity) else {
+                    continue;
+                };
+                let Some(bindposes) = inverse_bindposes.get(&skinned_mesh.inverse_bindposes) else {
+                    continue;
+                };
+
+                for (joint, bindpose) in skinned_mesh.joints.iter().zip(bindposes.iter()) {
+                    let joint_transform = transforms
+                        .get(*joint)
+                        .map(GlobalTransform::affine)
+                        .unwrap_or_default();
+                    let skin = Mat4::from(model_from_world * joint_transform) * *bindpose;
+
+                    // 3 rows, the last is always (0, 0, 0, 1)
+                    row.extend(skin.row(0).to_array());
+                    row.extend(skin.row(1).to_array());
+                    row.extend(skin.row(2).to_array());
+                }
+            }
+            baker.data.extend(row);
+
+            if frame + 1 == baker.frames {
+                finish_vertex_animation_bake(
+                    &baker,
+                    &skinned_meshes,
+                    &mut vertex_animations,
+                    &mut assets,
+                    &mut commands,
+                );
+                commands.entity(entity).despawn();
+                continue;
+            }
+        }
+
+        let next_frame = baker.frame.map_or(0, |frame| frame + 1);
+        baker.frame = Some(next_frame);
+
+        let Ok(mut player) = animation_players.get_mut(player) else {
+            continue;
+        };
+        if let Some(animation) = player.animation_mut(baker.animation_index) {
+            animation.seek_to(next_frame as f32 / baker.frame_rate);
+        }
+    }
+}
+
+type VertexAnimationAssets<'w> = (
+    ResMut<'w, Assets<VertexAnimation>>,
+    ResMut<'w, Assets<Image>>,
+    ResMut<'w, Assets<Mesh>>,
+    ResMut<'w, Assets<VertexAnimationMaterial>>,
+    Res<'w, Assets<StandardMaterial>>,
+);
+
+fn finish_vertex_animation_bake(
+    baker: &VertexAnimationBaker,
+    skinned_meshes: &Query<(
+        &SkinnedMesh,
+        &Mesh3d,
+        Option<&MeshMaterial3d<StandardMaterial>>,
+    )>,
+    vertex_animations: &mut VertexAnimations,
+    assets: &mut VertexAnimationAssets,
+    commands: &mut Commands,
+) {
+    let (vertex_animation_assets, images, meshes, materials, standard_materials) = assets;
+    let row_len = baker.data.len() / baker.frames as usize;
+    let width = (row_len / 4) as u32;
+    let texture = images.add(Image::new(
+        Extent3d {
+            width,
+            height: baker.frames,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        baker
+            .data
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect(),
+        TextureFormat::Rgba32Float,
+        RenderAssetUsages::RENDER_WORLD,
+    ));
+
+    let mut joint_offset = 0;
+    let mut vertex_animation_meshes = Vec::new();
+    for &entity in &baker.skinned_meshes {
+        let Ok((skinned_mesh, mesh, material)) = skinned_meshes.get(entity) else {
+            continue;
+        };
+        let joint_count = skinned_mesh.joints.len() as u32;
+
+        let base = material
+            .and_then(|material| standard_materials.get(&material.0))
+            .cloned()
+            .unwrap_or_default();
+        match meshes.get(&mesh.0).and_then(vertex_animation_mesh) {
+            Some(vertex_animation_mesh) => {
+                vertex_animation_meshes.push((
+                    meshes.add(vertex_animation_mesh),
+                    materials.add(VertexAnimationMaterial {
+                        base,
+                        extension: VertexAnimationExtension {
+                            settings: VertexAnimationSettings {
+                                joint_offset,
+                                frames: baker.frames,
+                                frame_rate: baker.frame_rate,
+                            },
+                            texture: texture.clone(),
+                        },
+                    }),
+                ));
+            }
+            None => {
+                warn!(
+                    "Skipping a mesh without joints when baking character '{}'",
+                    baker.character
+                );
+            }
+        }
+
+        joint_offset += joint_count;
+    }
+
+    let vertex_animation = vertex_animation_assets.add(VertexAnimation {
+        texture,
+        frames: baker.frames,
+        frame_rate: baker.frame_rate,
+        model_transform: baker.model_transform,
+        meshes: vertex_animation_meshes,
+    });
+
+    info!(
+        "Baked {} frames of animation '{}' for character '{}'",
+        baker.frames, baker.animation, baker.character
+    );
+
+    vertex_animations
+        .0
+        .entry(baker.character.clone())
+        .or_default()
+        .insert(baker.animation.clone(), vertex_animation.clone());
+
+    commands.trigger(VertexAnimationBaked {
+        character: baker.character.clone(),
+        animation: baker.animation.clone(),
+        vertex_animation,
+    });
+}
+
+// the skinned mesh with its joints moved to the vertex animation attributes
+// attributes the vertex shader doesn't pass through are dropped
+fn vertex_animation_mesh(mesh: &Mesh) -> Option<Mesh> {
+    let mut mesh = mesh.clone();
+    let joint_indices = mesh.remove_attribute(Mesh::ATTRIBUTE_JOINT_INDEX)?;
+    let joint_weights = mesh.remove_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT)?;
+    mesh.remove_attribute(Mesh::ATTRIBUTE_UV_1);
+    mesh.remove_attribute(Mesh::ATTRIBUTE_COLOR);
+
+    mesh.insert_attribute(ATTRIBUTE_VERTEX_ANIMATION_JOINT_INDEX, joint_indices);
+    mesh.insert_attribute(ATTRIBUTE_VERTEX_ANIMATION_JOINT_WEIGHT, joint_weights);
+    Some(mesh)
+}
+
+fn spawn_vertex_animation_meshes(
+    event: On<Add, VertexAnimationInstance>,
+    mut commands: Commands,
+    instances: Query<&VertexAnimationInstance>,
+    vertex_animations: Res<Assets<VertexAnimation>>,
+) {
+    let Ok(instance) = instances.get(event.entity) else {
+        return;
+    };
+    let Some(vertex_animation) = vertex_animations.get(&instance.vertex_animation) else {
+        warn!("Spawned a vertex animation instance before its animation was baked");
+        return;
+    };
+
+    let start_time = instance.start_time.rem_euclid(vertex_animation.duration());
+    commands.entity(event.entity).with_children(|parent| {
+        for (mesh, material) in &vertex_animation.meshes {
+            parent.spawn((
+                Mesh3d(mesh.clone()),
+                MeshMaterial3d(material.clone()),
+                // the instance's start offset, in milliseconds
+                MeshTag((start_time * 1000.0) as u32),
+                NotShadowCaster,
+            ));
+        }
+    });
+}
@@ -0,0 +1,103 @@
+// plays a baked vertex animation on a mesh without a skeleton
+// the texture holds one row per frame of 3x4 skinning matrices (one texel per matrix row)
+
+#import bevy_pbr::{
+    mesh_functions,
+    mesh_view_bindings::globals,
+    forward_io::VertexOutput,
+    view_transformations::position_world_to_clip,
+}
+
+struct VertexAnimation {
+    joint_offset: u32,
+    frames: u32,
+    frame_rate: f32,
+}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(100) var<uniform> vertex_animation: VertexAnimation;
+@group(#{MATERIAL_BIND_GROUP}) @binding(101) var vertex_animation_texture: texture_2d<f32>;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+#ifdef VERTEX_UVS_A
+    @location(2) uv: vec2<f32>,
+#endif
+#ifdef VERTEX_TANGENTS
+    @location(4) tangent: vec4<f32>,
+#endif
+    @location(8) joint_indices: vec4<u32>,
+    @location(9) joint_weights: vec4<f32>,
+};
+
+fn joint_matrix(frame: u32, joint: u32) -> mat4x4<f32> {
+    let x = i32((vertex_animation.joint_offset + joint) * 3u);
+    let y = i32(frame);
+    let r0 = textureLoad(vertex_animation_texture, vec2<i32>(x, y), 0);
+    let r1 = textureLoad(vertex_animation_texture, vec2<i32>(x + 1, y), 0);
+    let r2 = textureLoad(vertex_animation_texture, vec2<i32>(x + 2, y), 0);
+    return mat4x4<f32>(
+        vec4<f32>(r0.x, r1.x, r2.x, 0.0),
+        vec4<f32>(r0.y, r1.y, r2.y, 0.0),
+        vec4<f32>(r0.z, r1.z, r2.z, 0.0),
+        vec4<f32>(r0.w, r1.w, r2.w, 1.0),
+    );
+}
+
+fn frame_skin(frame: u32, indices: vec4<u32>, weights: vec4<f32>) -> mat4x4<f32> {
+    return weights.x * joint_matrix(frame, indices.x)
+        + weights.y * joint_matrix(frame, indices.y)
+        + weights.z * joint_matrix(frame, indices.z)
+        + weights.w * joint_matrix(frame, indices.w);
+}
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+    // the instance's tag is its start time in milliseconds
+    let start_time = f32(mesh_functions::get_tag(vertex.instance_index)) / 1000.0;
+    let time = (globals.time + start_time) * vertex_animation.frame_rate;
+    let frame = u32(floor(time)) % vertex_animation.frames;
+    let next_frame = (frame + 1u) % vertex_animation.frames;
+    let blend = fract(time);
+    let skin = frame_skin(frame, vertex.joint_indices, vertex.joint_weights) * (1.0 - blend)
+        + frame_skin(next_frame, vertex.joint_indices, vertex.joint_weights) * blend;
+
+    let mesh_world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+    let world_from_local = mesh_world_from_local * skin;
+
+    out.world_normal = mesh_functions::mesh_normal_local_to_world(
+        (skin * vec4<f32>(vertex.normal, 0.0)).xyz,
+        vertex.instance_index
+    );
+    out.world_position = mesh_functions::mesh_position_local_to_world(
+        world_from_local,
+        vec4<f32>(vertex.position, 1.0)
+    );
+    out.position = position_world_to_clip(out.world_position.xyz);
+
+#ifdef VERTEX_UVS_A
+    out.uv = vertex.uv;
+#endif
+
+#ifdef VERTEX_TANGENTS
+    out.world_tangent = mesh_functions::mesh_tangent_local_to_world(
+        world_from_local,
+        vertex.tangent,
+        vertex.instance_index
+    );
+#endif
+
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = vertex.instance_index;
+#endif
+
+#ifdef VISIBILITY_RANGE_DITHER
+    out.visibility_range_dither = mesh_functions::get_visibility_range_dither_level(
+        vertex.instance_index, mesh_world_from_local[3]);
+#endif
+
+    return out;
+}